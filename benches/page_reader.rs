@@ -0,0 +1,69 @@
+//! Compares the ways `PageReader` can read a database file.
+//!
+//! Run with `cargo bench --bench page_reader`, adding `--features mmap` to
+//! include the memory-mapped reader.
+
+use std::fs::File;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use rusqlite::Connection;
+use tempfile::TempDir;
+use wal_validator::db::{DbHeader, PageReader};
+
+/// Times every page of the database is read per measurement
+const ROUNDS: usize = 5;
+
+fn main() {
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("bench.db");
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, v BLOB);
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 50000)
+        INSERT INTO t SELECT i, randomblob(400) FROM n;
+    ",
+    )
+    .unwrap();
+    drop(conn);
+
+    let header = DbHeader::from_file(&db_path).unwrap();
+    println!(
+        "{} pages of {} bytes, each read {} times in scattered order",
+        header.page_count, header.page_size, ROUNDS
+    );
+
+    // Visit pages in a scattered order, as a B-tree walk would
+    let pages = header.page_count as u64;
+    let order: Vec<u32> = (0..pages).map(|i| (i * 7919 % pages) as u32 + 1).collect();
+
+    let (page_size, page_count) = (header.page_size, header.page_count);
+    let readers = [
+        ("open per read", PageReader::new(&db_path, page_size, page_count)),
+        (
+            "one open file",
+            PageReader::from_reader(File::open(&db_path).unwrap(), page_size, page_count).unwrap(),
+        ),
+        #[cfg(feature = "mmap")]
+        ("memory map", PageReader::open(&db_path, page_size, page_count).unwrap()),
+    ];
+
+    for (name, reader) in &readers {
+        let elapsed = time_reads(reader, &order);
+        let per_page = elapsed / (ROUNDS * order.len()) as u32;
+        println!("{:>14}: {:>10.3?} total, {:>8.2?} per page", name, elapsed, per_page);
+    }
+}
+
+/// Read the given pages `ROUNDS` times, returning the time taken
+fn time_reads(reader: &PageReader, order: &[u32]) -> Duration {
+    let started = Instant::now();
+    for _ in 0..ROUNDS {
+        for &page_num in order {
+            black_box(reader.page(page_num).unwrap());
+        }
+    }
+    started.elapsed()
+}
@@ -1,3 +1,7 @@
+use std::cmp::Ordering;
+
+use byteorder::{BigEndian, ByteOrder};
+
 use crate::error::{Result, WalValidatorError};
 
 /// Parse a SQLite varint (1-9 bytes)
@@ -34,25 +38,516 @@ pub fn parse_signed_varint(data: &[u8]) -> Result<(i64, usize)> {
     Ok((value as i64, len))
 }
 
+/// Encode a value as a SQLite varint, appending it to `out`
+pub fn encode_varint(value: u64, out: &mut Vec<u8>) {
+    // Values that need more than 56 bits use the 9-byte form, whose last byte holds 8 bits
+    if value > 0x00FF_FFFF_FFFF_FFFF {
+        for i in (1..9).rev() {
+            out.push(((value >> (i * 7 + 1)) as u8 & 0x7F) | 0x80);
+        }
+        out.push(value as u8);
+        return;
+    }
+
+    let mut groups = Vec::with_capacity(8);
+    let mut v = value;
+    loop {
+        groups.push((v & 0x7F) as u8);
+        v >>= 7;
+        if v == 0 {
+            break;
+        }
+    }
+
+    for (i, group) in groups.iter().rev().enumerate() {
+        if i + 1 < groups.len() {
+            out.push(group | 0x80);
+        } else {
+            out.push(*group);
+        }
+    }
+}
+
+/// How index keys are compared when grouping them for duplicate detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyComparison {
+    /// Compare the raw record bytes
+    #[default]
+    Binary,
+    /// Compare INTEGER and REAL columns by numeric value, as SQLite does
+    Numeric,
+    /// Compare by numeric value and by each column's declared collation
+    Collated,
+}
+
+/// A collating sequence SQLite uses to compare text in index keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Collation {
+    /// Compare text byte-for-byte
+    #[default]
+    Binary,
+    /// Ignore the case of ASCII letters
+    NoCase,
+    /// Ignore trailing spaces
+    RTrim,
+}
+
+impl Collation {
+    /// Look up a built-in collation by name (case-insensitive).
+    ///
+    /// Returns None for application-defined collations, whose rules are
+    /// unknown here.
+    pub fn from_name(name: &str) -> Option<Collation> {
+        match name.to_ascii_uppercase().as_str() {
+            "BINARY" => Some(Collation::Binary),
+            "NOCASE" => Some(Collation::NoCase),
+            "RTRIM" => Some(Collation::RTrim),
+            _ => None,
+        }
+    }
+
+    /// Rewrite text so that values equal under this collation have equal bytes.
+    ///
+    /// Text is folded a code unit at a time in the given encoding, so only
+    /// whole UTF-16 code units are lowercased or trimmed.
+    fn fold(self, text: &[u8], encoding: TextEncoding) -> Vec<u8> {
+        let unit_size = encoding.code_unit_size();
+        match self {
+            Collation::Binary => text.to_vec(),
+            Collation::NoCase => {
+                let mut folded = text.to_vec();
+                for unit in folded.chunks_mut(unit_size) {
+                    if let Some(c @ 0x41..=0x5a) = encoding.code_unit(unit) {
+                        unit.copy_from_slice(&encoding.encode_unit(c + 0x20)[..unit_size]);
+                    }
+                }
+                folded
+            }
+            Collation::RTrim => {
+                let end = text
+                    .chunks(unit_size)
+                    .rposition(|unit| encoding.code_unit(unit) != Some(0x20))
+                    .map_or(0, |i| (i + 1) * unit_size);
+                text[..end.min(text.len())].to_vec()
+            }
+        }
+    }
+}
+
+/// Encoding of the text values of a database, from its header
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf16le,
+    Utf16be,
+}
+
+impl TextEncoding {
+    /// Interpret the text encoding field of the database header. SQLite
+    /// treats 0 (a database without content yet) as UTF-8, and so does this
+    /// for values it doesn't know.
+    pub fn from_header(code: u32) -> Self {
+        match code {
+            2 => TextEncoding::Utf16le,
+            3 => TextEncoding::Utf16be,
+            _ => TextEncoding::Utf8,
+        }
+    }
+
+    /// Bytes in each code unit of text stored in this encoding
+    fn code_unit_size(self) -> usize {
+        match self {
+            TextEncoding::Utf8 => 1,
+            TextEncoding::Utf16le | TextEncoding::Utf16be => 2,
+        }
+    }
+
+    /// Value of one code unit, or None for a unit cut short at the end of the text
+    fn code_unit(self, unit: &[u8]) -> Option<u16> {
+        match (self, unit) {
+            (TextEncoding::Utf8, &[b]) => Some(b as u16),
+            (TextEncoding::Utf16le, &[a, b]) => Some(u16::from_le_bytes([a, b])),
+            (TextEncoding::Utf16be, &[a, b]) => Some(u16::from_be_bytes([a, b])),
+            _ => None,
+        }
+    }
+
+    /// Bytes of one code unit, of which the first `code_unit_size` are used
+    fn encode_unit(self, unit: u16) -> [u8; 2] {
+        match self {
+            TextEncoding::Utf8 => [unit as u8, 0],
+            TextEncoding::Utf16le => unit.to_le_bytes(),
+            TextEncoding::Utf16be => unit.to_be_bytes(),
+        }
+    }
+
+    /// Decode text stored in this encoding, replacing invalid sequences (an
+    /// odd trailing byte of UTF-16 text included)
+    pub fn decode(self, data: &[u8]) -> String {
+        match self {
+            TextEncoding::Utf8 => String::from_utf8_lossy(data).into_owned(),
+            TextEncoding::Utf16le => decode_utf16(data, u16::from_le_bytes),
+            TextEncoding::Utf16be => decode_utf16(data, u16::from_be_bytes),
+        }
+    }
+
+    /// Decode text stored in this encoding, or None if it isn't valid
+    pub fn decode_strict(self, data: &[u8]) -> Option<String> {
+        match self {
+            TextEncoding::Utf8 => String::from_utf8(data.to_vec()).ok(),
+            _ => {
+                let text = self.decode(data);
+                (!text.contains(char::REPLACEMENT_CHARACTER)).then_some(text)
+            }
+        }
+    }
+}
+
+/// Decode UTF-16 text whose code units are read by `unit`
+fn decode_utf16(data: &[u8], unit: fn([u8; 2]) -> u16) -> String {
+    let mut pairs = data.chunks_exact(2);
+    let mut text: String = char::decode_utf16(pairs.by_ref().map(|p| unit([p[0], p[1]])))
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+        .collect();
+    if !pairs.remainder().is_empty() {
+        text.push(char::REPLACEMENT_CHARACTER);
+    }
+    text
+}
+
+/// A decoded column value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Int(i64),
+    Float(f64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl std::fmt::Display for Value {
+    /// Format the value as a SQL literal
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Null => write!(f, "NULL"),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Text(text) => write!(f, "'{}'", text.replace('\'', "''")),
+            Value::Blob(data) => write!(f, "X'{}'", hex_encode(data)),
+        }
+    }
+}
+
+/// The decoded columns of a record, in order
+pub type Record = Vec<Value>;
+
+/// Decode every column of a record.
+///
+/// Text is decoded in the database's encoding, replacing invalid sequences.
+/// Fails if a column runs past the end of the payload or uses a reserved
+/// serial type (10, 11).
+pub fn parse_record(payload: &[u8], encoding: TextEncoding) -> Result<Record> {
+    let (serial_types, header_size) = parse_record_header(payload)?;
+    decode_columns(payload, &serial_types, header_size, encoding)
+}
+
+/// Decode the columns with the given serial types, whose data starts at `offset`
+fn decode_columns(
+    payload: &[u8],
+    serial_types: &[u64],
+    mut offset: usize,
+    encoding: TextEncoding,
+) -> Result<Record> {
+    let mut values = Vec::with_capacity(serial_types.len());
+
+    for &serial_type in serial_types {
+        let size = serial_type_size(serial_type);
+        let data = payload
+            .get(offset..offset + size)
+            .ok_or(WalValidatorError::UnexpectedEof)?;
+        offset += size;
+
+        let value = match serial_type {
+            0 => Value::Null,
+            1..=6 | 8 | 9 => Value::Int(read_integer(serial_type, data)?),
+            7 => Value::Float(BigEndian::read_f64(data)),
+            10 | 11 => return Err(WalValidatorError::UnexpectedEof),
+            n if n % 2 == 0 => Value::Blob(data.to_vec()),
+            _ => Value::Text(encoding.decode(data)),
+        };
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
 /// Represents an index key (the first few columns of an index entry)
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct IndexKey {
-    /// Raw bytes of the key for comparison
+    /// The key columns as a standalone record, without the trailing rowid
     pub raw: Vec<u8>,
 }
 
 impl std::fmt::Display for IndexKey {
+    /// Format the record bytes as hex, since reading its text needs the
+    /// database encoding; see `IndexKey::display` for decoded values
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", hex_encode(&self.raw))
+    }
+}
+
+/// Displays an `IndexKey` as its decoded column values, returned by
+/// `IndexKey::display`
+pub struct KeyDisplay<'k> {
+    key: &'k IndexKey,
+    encoding: TextEncoding,
+}
+
+impl std::fmt::Display for KeyDisplay<'_> {
+    /// Format the key as a list of SQL literals, or as hex if its record
+    /// can't be decoded
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Try to interpret as UTF-8 text, fall back to hex
-        if let Ok(s) = std::str::from_utf8(&self.raw) {
-            if s.chars().all(|c| !c.is_control() || c == '\n' || c == '\t') {
-                return write!(f, "\"{}\"", s);
+        match self.key.values(self.encoding) {
+            Ok(values) => {
+                let values: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                write!(f, "({})", values.join(", "))
             }
+            Err(_) => write!(f, "{}", self.key),
         }
-        write!(f, "0x{}", hex_encode(&self.raw))
     }
 }
 
+impl serde::Serialize for IndexKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        // Raw record bytes are not valid text in general, so always use hex
+        serializer.serialize_str(&hex_encode(&self.raw))
+    }
+}
+
+impl IndexKey {
+    /// Decode the key columns
+    pub fn values(&self, encoding: TextEncoding) -> Result<Record> {
+        parse_record(&self.raw, encoding)
+    }
+
+    /// Display the key's column values, reading text in the given encoding
+    pub fn display(&self, encoding: TextEncoding) -> KeyDisplay<'_> {
+        KeyDisplay {
+            key: self,
+            encoding,
+        }
+    }
+
+    /// Return a copy of this key with numeric columns in a canonical encoding.
+    ///
+    /// SQLite orders INTEGER and REAL values as a single numeric class, so `1`
+    /// and `1.0` are equal index keys even though their serial types differ
+    /// (columns with REAL affinity store integral values as integers on disk).
+    /// Integral reals and all integers are rewritten as 8-byte integers and
+    /// other reals as 8-byte floats, so equal numbers produce equal bytes.
+    pub fn numeric_normalized(&self) -> Result<IndexKey> {
+        Ok(IndexKey {
+            raw: self.canonical_record(&[], TextEncoding::default())?,
+        })
+    }
+
+    /// Return the key's bytes with numbers and text in a canonical encoding,
+    /// so that keys SQLite considers equal have equal bytes.
+    ///
+    /// Numbers are rewritten as by `numeric_normalized`, and text columns are
+    /// folded under the collation at the same position in `collations`
+    /// (BINARY past its end), reading text in the given encoding. A key whose
+    /// record can't be parsed is returned unchanged.
+    pub fn normalized(&self, collations: &[Collation], encoding: TextEncoding) -> Vec<u8> {
+        self.canonical_record(collations, encoding)
+            .unwrap_or_else(|_| self.raw.clone())
+    }
+
+    /// Rebuild the key columns of the record with canonical numbers and
+    /// collation-folded text
+    fn canonical_record(
+        &self,
+        collations: &[Collation],
+        encoding: TextEncoding,
+    ) -> Result<Vec<u8>> {
+        let (serial_types, header_size) = parse_record_header(&self.raw)?;
+
+        let mut types = Vec::with_capacity(serial_types.len());
+        let mut body = Vec::new();
+        let mut offset = header_size;
+
+        for (column, &st) in serial_types.iter().enumerate() {
+            let size = serial_type_size(st);
+            if offset + size > self.raw.len() {
+                return Err(WalValidatorError::UnexpectedEof);
+            }
+            let data = &self.raw[offset..offset + size];
+            offset += size;
+
+            let numeric = match st {
+                7 => {
+                    let value = BigEndian::read_f64(data);
+                    if value.fract() == 0.0 && value >= i64::MIN as f64 && value < i64::MAX as f64 {
+                        Some(Ok(value as i64))
+                    } else {
+                        Some(Err(value))
+                    }
+                }
+                1..=6 | 8 | 9 => Some(Ok(read_integer(st, data)?)),
+                _ => None,
+            };
+
+            match numeric {
+                Some(Ok(int)) => {
+                    types.push(6);
+                    body.extend_from_slice(&int.to_be_bytes());
+                }
+                Some(Err(float)) => {
+                    types.push(7);
+                    body.extend_from_slice(&float.to_be_bytes());
+                }
+                None if st >= 13 && st % 2 == 1 => {
+                    let collation = collations.get(column).copied().unwrap_or_default();
+                    let text = collation.fold(data, encoding);
+                    types.push(text.len() as u64 * 2 + 13);
+                    body.extend_from_slice(&text);
+                }
+                None => {
+                    types.push(st);
+                    body.extend_from_slice(data);
+                }
+            }
+        }
+
+        Ok(build_record(&types, &body))
+    }
+
+    /// Return the key to group by under the given comparison mode, folding
+    /// text columns (stored in `encoding`) under `collations` when comparing
+    /// by collation
+    pub fn comparable(
+        &self,
+        comparison: KeyComparison,
+        collations: &[Collation],
+        encoding: TextEncoding,
+    ) -> Result<IndexKey> {
+        match comparison {
+            KeyComparison::Binary => Ok(self.clone()),
+            KeyComparison::Numeric => self.numeric_normalized(),
+            KeyComparison::Collated => Ok(IndexKey {
+                raw: self.normalized(collations, encoding),
+            }),
+        }
+    }
+}
+
+/// Compare two records column by column, in the order SQLite sorts index
+/// entries under the BINARY collation with every column ascending.
+///
+/// NULLs sort first, then numbers (INTEGER and REAL by value), then text,
+/// then blobs; text and blobs compare byte-for-byte. Only the columns whose
+/// data both records hold are compared, so a record compares equal to any
+/// record it is a prefix of.
+pub fn compare_records(a: &[u8], b: &[u8]) -> Result<Ordering> {
+    let (a_types, mut a_offset) = parse_record_header(a)?;
+    let (b_types, mut b_offset) = parse_record_header(b)?;
+
+    for (&a_type, &b_type) in a_types.iter().zip(&b_types) {
+        let a_end = a_offset + serial_type_size(a_type);
+        let b_end = b_offset + serial_type_size(b_type);
+        if a_end > a.len() || b_end > b.len() {
+            break;
+        }
+        let a_data = &a[a_offset..a_end];
+        let b_data = &b[b_offset..b_end];
+        a_offset = a_end;
+        b_offset = b_end;
+
+        let ordering = match (sort_class(a_type), sort_class(b_type)) {
+            (1, 1) => compare_numbers(a_type, a_data, b_type, b_data)?,
+            (a_class, b_class) if a_class == b_class => a_data.cmp(b_data),
+            (a_class, b_class) => a_class.cmp(&b_class),
+        };
+        if ordering != Ordering::Equal {
+            return Ok(ordering);
+        }
+    }
+
+    Ok(Ordering::Equal)
+}
+
+/// Storage class rank of a serial type: NULL, number, text, then blob
+fn sort_class(serial_type: u64) -> u8 {
+    match serial_type {
+        0 | 10 | 11 => 0,
+        1..=9 => 1,
+        n if n % 2 == 1 => 2,
+        _ => 3,
+    }
+}
+
+/// Compare two numeric columns by value
+fn compare_numbers(a_type: u64, a: &[u8], b_type: u64, b: &[u8]) -> Result<Ordering> {
+    match (a_type, b_type) {
+        (7, 7) => Ok(BigEndian::read_f64(a).total_cmp(&BigEndian::read_f64(b))),
+        (7, _) => Ok(BigEndian::read_f64(a).total_cmp(&(read_integer(b_type, b)? as f64))),
+        (_, 7) => Ok((read_integer(a_type, a)? as f64).total_cmp(&BigEndian::read_f64(b))),
+        _ => Ok(read_integer(a_type, a)?.cmp(&read_integer(b_type, b)?)),
+    }
+}
+
+/// Assemble a record from serial types and the concatenated column data
+fn build_record(serial_types: &[u64], body: &[u8]) -> Vec<u8> {
+    let mut header = Vec::new();
+    for &st in serial_types {
+        encode_varint(st, &mut header);
+    }
+
+    // The header size varint counts itself
+    let mut header_size = header.len() as u64 + 1;
+    loop {
+        let mut size_bytes = Vec::new();
+        encode_varint(header_size, &mut size_bytes);
+        if size_bytes.len() as u64 + header.len() as u64 == header_size {
+            let mut record = size_bytes;
+            record.extend_from_slice(&header);
+            record.extend_from_slice(body);
+            return record;
+        }
+        header_size = size_bytes.len() as u64 + header.len() as u64;
+    }
+}
+
+/// Read an integer column given its serial type (1-6, 8 or 9)
+pub fn read_integer(serial_type: u64, data: &[u8]) -> Result<i64> {
+    let size = serial_type_size(serial_type);
+    if data.len() < size {
+        return Err(WalValidatorError::UnexpectedEof);
+    }
+
+    let value = match serial_type {
+        1 => data[0] as i8 as i64,
+        2 => BigEndian::read_i16(&data[0..2]) as i64,
+        3 => {
+            let b = &data[0..3];
+            (((b[0] as i32) << 24 | (b[1] as i32) << 16 | (b[2] as i32) << 8) >> 8) as i64
+        }
+        4 => BigEndian::read_i32(&data[0..4]) as i64,
+        5 => {
+            let mut buf = [0u8; 8];
+            buf[0..6].copy_from_slice(&data[0..6]);
+            i64::from_be_bytes(buf) >> 16
+        }
+        6 => BigEndian::read_i64(&data[0..8]),
+        8 => 0,
+        9 => 1,
+        _ => return Err(WalValidatorError::UnexpectedEof),
+    };
+
+    Ok(value)
+}
+
 fn hex_encode(data: &[u8]) -> String {
     data.iter().map(|b| format!("{:02x}", b)).collect()
 }
@@ -106,31 +601,30 @@ pub fn serial_type_size(serial_type: u64) -> usize {
 
 /// Extract the key portion of an index cell payload
 /// For indexes, the key is everything except the last column (which is the rowid)
+///
+/// A record holding only the rowid has no key columns and yields an empty key.
 pub fn extract_index_key(payload: &[u8]) -> Result<IndexKey> {
     // Parse the record header to find column boundaries
     let (serial_types, header_size) = parse_record_header(payload)?;
 
-    if serial_types.is_empty() {
+    if serial_types.len() < 2 {
         return Ok(IndexKey { raw: Vec::new() });
     }
 
     // Calculate total size of all columns except the last (rowid)
-    let key_columns = if serial_types.len() > 1 {
-        &serial_types[..serial_types.len() - 1]
-    } else {
-        &serial_types[..]
-    };
+    let key_columns = &serial_types[..serial_types.len() - 1];
 
     let key_size: usize = key_columns.iter().map(|&st| serial_type_size(st)).sum();
 
-    // Extract the key bytes (header + key column data)
     let key_end = header_size + key_size;
     if key_end > payload.len() {
         return Err(WalValidatorError::UnexpectedEof);
     }
 
+    // Rebuild the header without the rowid's serial type, so that entries with
+    // equal keys have equal bytes whatever size their rowids are stored in
     Ok(IndexKey {
-        raw: payload[..key_end].to_vec(),
+        raw: build_record(key_columns, &payload[header_size..key_end]),
     })
 }
 
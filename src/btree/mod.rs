@@ -1,7 +1,17 @@
 pub mod cell;
 pub mod page;
+pub mod ptrmap;
 pub mod scanner;
 
-pub use cell::{extract_index_rowid, parse_varint, IndexKey};
+pub use cell::{
+    extract_index_rowid, parse_record, parse_varint, Collation, IndexKey, KeyComparison,
+    KeyDisplay, Record, TextEncoding, Value,
+};
 pub use page::{BTreePageHeader, BTreePageType};
-pub use scanner::{BTreeInfo, BTreeScanner, RowidLocation};
+pub use ptrmap::{
+    is_lock_byte_page, pending_byte_page, PtrmapEntry, PtrmapLayout, PtrmapPage, PtrmapType,
+};
+pub use scanner::{
+    read_full_payload, BTreeClassification, BTreeInfo, BTreePages, BTreeScanner, FillFactor,
+    PageVisit, RowidLocation,
+};
@@ -1,5 +1,6 @@
 use byteorder::{BigEndian, ByteOrder};
 
+use crate::btree::cell::parse_varint;
 use crate::error::{Result, WalValidatorError};
 
 /// B-tree page types
@@ -36,6 +37,82 @@ impl BTreePageType {
     pub fn is_table(&self) -> bool {
         matches!(self, BTreePageType::TableInterior | BTreePageType::TableLeaf)
     }
+
+    /// Number of payload bytes stored in the cell itself; the rest spills
+    /// onto overflow pages. Follows the thresholds of the SQLite file format.
+    pub fn local_payload_size(&self, payload_size: usize, usable_size: usize) -> usize {
+        let max_local = if self.is_table() {
+            usable_size - 35
+        } else {
+            (usable_size - 12) * 64 / 255 - 23
+        };
+        if payload_size <= max_local {
+            return payload_size;
+        }
+
+        let min_local = (usable_size - 12) * 32 / 255 - 23;
+        let local = min_local + (payload_size - min_local) % (usable_size - 4);
+        if local <= max_local { local } else { min_local }
+    }
+
+    /// Size in bytes of the cell at the start of `cell`, including the
+    /// overflow page pointer when its payload spills
+    pub fn cell_size(&self, cell: &[u8], usable_size: usize) -> Result<usize> {
+        // Interior cells start with a 4-byte left child pointer
+        let mut size = if self.is_interior() { 4 } else { 0 };
+
+        // Table interior cells hold only the child pointer and a rowid
+        if *self == BTreePageType::TableInterior {
+            let (_, rowid_len) = parse_varint(cell.get(size..).unwrap_or_default())?;
+            return Ok(size + rowid_len);
+        }
+
+        let (payload_size, payload_len) = parse_varint(cell.get(size..).unwrap_or_default())?;
+        size += payload_len;
+
+        if *self == BTreePageType::TableLeaf {
+            let (_, rowid_len) = parse_varint(cell.get(size..).unwrap_or_default())?;
+            size += rowid_len;
+        }
+
+        let payload_size = payload_size as usize;
+        let local = self.local_payload_size(payload_size, usable_size);
+        size += local;
+        if local < payload_size {
+            size += 4;
+        }
+
+        Ok(size)
+    }
+
+    /// First overflow page of the cell at the start of `cell`, or None if
+    /// its payload fits on the page
+    pub fn overflow_page(&self, cell: &[u8], usable_size: usize) -> Result<Option<u32>> {
+        // Table interior cells have no payload
+        if *self == BTreePageType::TableInterior {
+            return Ok(None);
+        }
+
+        let mut offset = if self.is_interior() { 4 } else { 0 };
+        let (payload_size, payload_len) = parse_varint(cell.get(offset..).unwrap_or_default())?;
+        offset += payload_len;
+
+        if *self == BTreePageType::TableLeaf {
+            let (_, rowid_len) = parse_varint(cell.get(offset..).unwrap_or_default())?;
+            offset += rowid_len;
+        }
+
+        let payload_size = payload_size as usize;
+        let local = self.local_payload_size(payload_size, usable_size);
+        if local >= payload_size {
+            return Ok(None);
+        }
+
+        let pointer = cell
+            .get(offset + local..offset + local + 4)
+            .ok_or(WalValidatorError::UnexpectedEof)?;
+        Ok(Some(BigEndian::read_u32(pointer)))
+    }
 }
 
 /// B-tree page header
@@ -0,0 +1,161 @@
+//! Pointer-map (ptrmap) pages of auto-vacuum databases.
+//!
+//! With `auto_vacuum` set to FULL or INCREMENTAL, SQLite records the parent
+//! of every page so pages can be moved when the file is truncated. Page 2 is
+//! the first pointer-map page; each holds one 5-byte entry (a type byte and
+//! a big-endian parent page number) for every page that follows it, up to
+//! the next pointer-map page. Pointer-map pages are not B-tree pages.
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::db::DbHeader;
+
+/// Byte offset of the lock byte range, whose page is never used
+const PENDING_BYTE: u64 = 0x4000_0000;
+
+/// The page holding the lock byte range at 1 GiB, which SQLite never uses
+/// (it only exists in databases larger than that)
+pub fn pending_byte_page(page_size: u32) -> u32 {
+    (PENDING_BYTE / page_size as u64) as u32 + 1
+}
+
+/// Returns true if a page is the lock-byte page of a database with the
+/// given page size
+pub fn is_lock_byte_page(page_num: u32, page_size: u32) -> bool {
+    page_num == pending_byte_page(page_size)
+}
+
+/// What a page is, according to its pointer-map entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PtrmapType {
+    /// Root page of a B-tree (parent is 0)
+    RootPage,
+    /// Page on the freelist (parent is 0)
+    FreePage,
+    /// First page of an overflow chain (parent is the B-tree page holding the cell)
+    Overflow1,
+    /// Later page of an overflow chain (parent is the previous overflow page)
+    Overflow2,
+    /// Non-root B-tree page (parent is its parent B-tree page)
+    BTree,
+}
+
+impl PtrmapType {
+    /// Parse an entry type byte
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(PtrmapType::RootPage),
+            2 => Some(PtrmapType::FreePage),
+            3 => Some(PtrmapType::Overflow1),
+            4 => Some(PtrmapType::Overflow2),
+            5 => Some(PtrmapType::BTree),
+            _ => None,
+        }
+    }
+}
+
+/// One entry of a pointer-map page
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PtrmapEntry {
+    /// Page the entry describes
+    pub page_number: u32,
+    /// Raw entry type byte
+    pub entry_type: u8,
+    /// Parent page number (0 for root and free pages)
+    pub parent: u32,
+}
+
+impl PtrmapEntry {
+    /// The entry type, or None if the type byte is not a valid one
+    pub fn kind(&self) -> Option<PtrmapType> {
+        PtrmapType::from_byte(self.entry_type)
+    }
+}
+
+/// Where the pointer-map pages of a database are
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PtrmapLayout {
+    /// Entries per pointer-map page
+    entries_per_page: u32,
+    /// Page holding the lock byte range
+    pending_byte_page: u32,
+}
+
+impl PtrmapLayout {
+    /// Layout for a database, or None if it doesn't use pointer-map pages
+    pub fn from_header(header: &DbHeader) -> Option<Self> {
+        if !header.has_ptrmap() {
+            return None;
+        }
+
+        Some(PtrmapLayout {
+            entries_per_page: header.usable_size() / 5,
+            pending_byte_page: pending_byte_page(header.page_size),
+        })
+    }
+
+    /// The pointer-map page holding the entry for a page, or None for page 1
+    pub fn ptrmap_page_for(&self, page_num: u32) -> Option<u32> {
+        if page_num < 2 {
+            return None;
+        }
+
+        let pages_per_map = self.entries_per_page + 1;
+        let ptrmap_page = (page_num - 2) / pages_per_map * pages_per_map + 2;
+        if ptrmap_page == self.pending_byte_page {
+            Some(ptrmap_page + 1)
+        } else {
+            Some(ptrmap_page)
+        }
+    }
+
+    /// Returns true if a page is a pointer-map page
+    pub fn is_ptrmap_page(&self, page_num: u32) -> bool {
+        self.ptrmap_page_for(page_num) == Some(page_num)
+    }
+
+    /// Pointer-map pages of a database of `page_count` pages, in order
+    pub fn ptrmap_pages(&self, page_count: u32) -> Vec<u32> {
+        let pages_per_map = self.entries_per_page + 1;
+        (2..=page_count)
+            .step_by(pages_per_map as usize)
+            .filter_map(|page_num| self.ptrmap_page_for(page_num))
+            .filter(|&page_num| page_num <= page_count)
+            .collect()
+    }
+}
+
+/// A decoded pointer-map page
+#[derive(Debug, Clone)]
+pub struct PtrmapPage {
+    /// Page number of the pointer-map page
+    pub page_number: u32,
+    /// Entries for the pages it covers that exist in the database
+    pub entries: Vec<PtrmapEntry>,
+}
+
+impl PtrmapPage {
+    /// Decode the entries of pointer-map page `page_number` for a database
+    /// of `page_count` pages
+    pub fn parse(data: &[u8], page_number: u32, layout: &PtrmapLayout, page_count: u32) -> Self {
+        let entries = (page_number + 1..=page_count)
+            .take(layout.entries_per_page as usize)
+            .filter(|&page_num| page_num != layout.pending_byte_page)
+            .filter(|&page_num| layout.ptrmap_page_for(page_num) == Some(page_number))
+            .filter_map(|page_num| {
+                let offset = 5 * (page_num - page_number - 1) as usize;
+                let entry = data.get(offset..offset + 5)?;
+                Some(PtrmapEntry {
+                    page_number: page_num,
+                    entry_type: entry[0],
+                    parent: BigEndian::read_u32(&entry[1..5]),
+                })
+            })
+            .collect();
+
+        PtrmapPage {
+            page_number,
+            entries,
+        }
+    }
+}
@@ -1,8 +1,17 @@
+use std::collections::{HashMap, HashSet};
+
 use byteorder::{BigEndian, ByteOrder};
+use serde::Serialize;
 
-use crate::btree::cell::{extract_index_key, extract_index_rowid, parse_varint, IndexKey};
+use crate::btree::cell::{
+    extract_index_key, extract_index_rowid, parse_record, parse_record_header, parse_varint,
+    read_integer, serial_type_size, Collation, IndexKey, Record, TextEncoding,
+};
 use crate::btree::page::{BTreePageHeader, BTreePageType};
+use crate::btree::ptrmap::{is_lock_byte_page, PtrmapLayout};
+use crate::db::DbHeader;
 use crate::error::{Result, WalValidatorError};
+use crate::sql;
 use crate::validator::PageCache;
 
 /// Information about a B-tree
@@ -20,10 +29,211 @@ pub struct BTreeInfo {
     pub is_table: bool,
     /// True if this is a unique index (only relevant for indexes)
     pub is_unique: bool,
+    /// For indexes: the collation of each key column, from the CREATE INDEX
+    /// statement or else the table's column definition. Empty when unknown
+    /// (such as for autoindexes), which compares every column as BINARY.
+    pub collations: Vec<Collation>,
+}
+
+/// How an object is covered by the index integrity check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BTreeClassification {
+    /// Fully checked (all tables, and indexes over plain columns)
+    Checked,
+    /// Partial index (has a WHERE clause), intentionally covers a subset of rows
+    SkippedPartial,
+    /// Expression index, whose keys can't be derived from table columns
+    SkippedExpression,
+}
+
+impl BTreeInfo {
+    /// Classify this object for the index integrity check.
+    ///
+    /// Partial and expression indexes intentionally don't mirror their table
+    /// row for row, so they are skipped.
+    pub fn classification(&self) -> BTreeClassification {
+        if self.is_table {
+            return BTreeClassification::Checked;
+        }
+
+        // Autoindexes (no SQL) are created by SQLite for PRIMARY KEY / UNIQUE constraints
+        // These should be checked, so don't skip
+        let Some(ref sql) = self.sql else {
+            return BTreeClassification::Checked;
+        };
+        let sql_upper = sql.to_uppercase();
+
+        // Partial indexes have a WHERE clause
+        if sql_upper.contains(" WHERE ") {
+            return BTreeClassification::SkippedPartial;
+        }
+
+        // Expression indexes look like: CREATE INDEX idx ON table ((expr))
+        // We detect this by looking for (( pattern or function calls in the index definition
+        if sql_upper.contains("((") {
+            return BTreeClassification::SkippedExpression;
+        }
+
+        // Also check for common SQL functions that indicate expression indexes
+        let expr_patterns = [
+            "LOWER(", "UPPER(", "SUBSTR(", "LENGTH(", "ABS(",
+            "COALESCE(", "IFNULL(", "NULLIF(", "TYPEOF(",
+            "CAST(", "DATE(", "TIME(", "DATETIME(", "JULIANDAY(",
+            "JSON_EXTRACT(", "JSON(",
+        ];
+        if expr_patterns.iter().any(|p| sql_upper.contains(p)) {
+            return BTreeClassification::SkippedExpression;
+        }
+
+        BTreeClassification::Checked
+    }
+}
+
+/// Collations of an index's key columns.
+///
+/// A column without a COLLATE clause in the CREATE INDEX statement uses the
+/// collation declared for it in CREATE TABLE. Application-defined collations
+/// can't be reproduced and are treated as BINARY.
+fn index_collations(index: &BTreeInfo, btrees: &[BTreeInfo]) -> Vec<Collation> {
+    if index.is_table {
+        return Vec::new();
+    }
+    let Some(columns) = index.sql.as_deref().and_then(sql::parse_index_columns) else {
+        return Vec::new();
+    };
+    let table_columns = btrees
+        .iter()
+        .find(|b| b.is_table && b.name.is_some() && b.name == index.tbl_name)
+        .and_then(|b| b.sql.as_deref())
+        .and_then(sql::parse_table_columns)
+        .unwrap_or_default();
+
+    columns
+        .iter()
+        .map(|column| {
+            let declared = column.collation.as_deref().or_else(|| {
+                let name = column.name.as_deref()?;
+                table_columns
+                    .iter()
+                    .find(|c| c.name.eq_ignore_ascii_case(name))?
+                    .collation
+                    .as_deref()
+            });
+            declared.and_then(Collation::from_name).unwrap_or_default()
+        })
+        .collect()
+}
+
+/// A B-tree page reached by `BTreeScanner::walk_pages`
+pub struct PageVisit<'p> {
+    /// Page number
+    pub page_number: u32,
+    /// Interior page whose child pointer led here (None for the root)
+    pub parent: Option<u32>,
+    /// Parsed page header
+    pub header: &'p BTreePageHeader,
+    /// Offset just past the page header, where the cell pointers start
+    pub header_end: usize,
+    /// Page contents
+    pub data: &'p [u8],
+}
+
+/// Iterator over the pages of a B-tree, returned by `BTreeScanner::pages`.
+///
+/// Yields each page number with its parsed header, depth first and left to
+/// right, so leaf pages come in key order.
+pub struct BTreePages<'s, 'a> {
+    scanner: &'s mut BTreeScanner<'a>,
+    /// Pages still to visit, with the interior page that points to each
+    stack: Vec<(u32, Option<u32>)>,
+    visited: HashSet<u32>,
+    /// References to already visited pages, as `(page, parent)`
+    repeated: Vec<(u32, u32)>,
+    /// Set once an error has been yielded
+    failed: bool,
+}
+
+/// A page reached by `BTreePages`, with its contents
+struct WalkedPage {
+    page_number: u32,
+    parent: Option<u32>,
+    header: BTreePageHeader,
+    header_end: usize,
+    data: Vec<u8>,
+}
+
+impl BTreePages<'_, '_> {
+    /// Read the next page to visit, pushing its children
+    fn next_page(&mut self) -> Result<Option<WalkedPage>> {
+        self.walk(true)
+    }
+
+    /// Visit the next page, copying its contents only when `keep_data` is
+    /// set; otherwise the walked page's `data` is left empty
+    fn walk(&mut self, keep_data: bool) -> Result<Option<WalkedPage>> {
+        while let Some((page_num, parent)) = self.stack.pop() {
+            if self.scanner.is_skipped_page(page_num) {
+                continue;
+            }
+            if !self.visited.insert(page_num) {
+                self.repeated.extend(parent.map(|parent| (page_num, parent)));
+                continue;
+            }
+            let page_data = self.scanner.btree_page(page_num)?;
+            let (header, header_end) = BTreePageHeader::parse(page_data, page_num)?;
+
+            if header.page_type.is_interior() {
+                let cell_pointers = header.get_cell_pointers(page_data, page_num)?;
+
+                // Pushed right to left, so the leftmost child is walked first
+                if let Some(right_child) = header.right_child {
+                    self.stack.push((right_child, Some(page_num)));
+                }
+                for &cell_ptr in cell_pointers.iter().rev() {
+                    let cell_offset = cell_ptr as usize;
+                    if cell_offset + 4 > page_data.len() {
+                        continue;
+                    }
+
+                    // First 4 bytes are left child pointer
+                    let left_child = BigEndian::read_u32(&page_data[cell_offset..cell_offset + 4]);
+                    self.stack.push((left_child, Some(page_num)));
+                }
+            }
+
+            return Ok(Some(WalkedPage {
+                page_number: page_num,
+                parent,
+                header,
+                header_end,
+                data: if keep_data { page_data.to_vec() } else { Vec::new() },
+            }));
+        }
+
+        Ok(None)
+    }
+}
+
+impl Iterator for BTreePages<'_, '_> {
+    type Item = Result<(u32, BTreePageHeader)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.walk(false) {
+            Ok(page) => page.map(|page| Ok((page.page_number, page.header))),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
 }
 
 /// Location of a rowid or key within a B-tree
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct RowidLocation {
     /// Page number where this rowid was found
     pub page_number: u32,
@@ -33,14 +243,70 @@ pub struct RowidLocation {
     pub frame_index: Option<u64>,
 }
 
+/// How full the leaf pages of a B-tree are
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FillFactor {
+    /// Table or index name (if known)
+    pub name: Option<String>,
+    /// Root page number
+    pub root_page: u32,
+    /// Number of leaf pages
+    pub leaf_pages: u64,
+    /// Bytes taken by page headers, cell pointers and cells
+    pub used_bytes: u64,
+    /// Bytes available for them
+    pub usable_bytes: u64,
+}
+
+impl FillFactor {
+    /// Fraction of the usable space in use (above 1.0 is impossible)
+    pub fn ratio(&self) -> f64 {
+        if self.usable_bytes == 0 {
+            return 0.0;
+        }
+        self.used_bytes as f64 / self.usable_bytes as f64
+    }
+}
+
 /// Scanner for traversing B-trees and collecting rowids/keys
 pub struct BTreeScanner<'a> {
     page_cache: &'a mut PageCache,
+    /// Usable page size, read from page 1 on first use
+    usable_size: Option<usize>,
+    /// Text encoding, read from page 1 on first use
+    encoding: Option<TextEncoding>,
+    /// Pointer-map layout, read from page 1 on first use
+    ptrmap: Option<Option<PtrmapLayout>>,
 }
 
 impl<'a> BTreeScanner<'a> {
     pub fn new(page_cache: &'a mut PageCache) -> Self {
-        BTreeScanner { page_cache }
+        BTreeScanner {
+            page_cache,
+            usable_size: None,
+            encoding: None,
+            ptrmap: None,
+        }
+    }
+
+    /// Returns true if a page can't be part of a B-tree: page 0, a page past
+    /// the end of the database, the lock-byte page or a pointer-map page.
+    /// Only a corrupt child pointer leads to one of these.
+    fn is_skipped_page(&mut self, page_num: u32) -> bool {
+        page_num == 0
+            || page_num > self.page_cache.effective_page_count()
+            || is_lock_byte_page(page_num, self.page_cache.page_size())
+            || self.is_ptrmap_page(page_num)
+    }
+
+    /// Returns true if a page is a pointer-map page of an auto-vacuum database
+    fn is_ptrmap_page(&mut self, page_num: u32) -> bool {
+        let page_cache = &mut self.page_cache;
+        let layout = self.ptrmap.get_or_insert_with(|| {
+            let header = DbHeader::parse(page_cache.get_page_ref(1).ok()?).ok()?;
+            PtrmapLayout::from_header(&header)
+        });
+        layout.is_some_and(|layout| layout.is_ptrmap_page(page_num))
     }
 
     /// Discover all B-trees by reading sqlite_master (page 1)
@@ -49,47 +315,34 @@ impl<'a> BTreeScanner<'a> {
 
         self.scan_sqlite_master(1, &mut btrees)?;
 
+        let collations: Vec<_> = btrees
+            .iter()
+            .map(|btree| index_collations(btree, &btrees))
+            .collect();
+        for (btree, collations) in btrees.iter_mut().zip(collations) {
+            btree.collations = collations;
+        }
+
         Ok(btrees)
     }
 
     /// Scan sqlite_master pages to find all tables and indexes
     fn scan_sqlite_master(&mut self, root_page: u32, btrees: &mut Vec<BTreeInfo>) -> Result<()> {
-        let mut stack = vec![root_page];
-
-        while let Some(page_num) = stack.pop() {
-            let page_data = self.page_cache.get_page(page_num)?;
-            let (header, _) = BTreePageHeader::parse(&page_data, page_num)?;
-
-            match header.page_type {
-                BTreePageType::TableLeaf => {
-                    // Parse cells to find tables and indexes
-                    let cell_pointers = header.get_cell_pointers(&page_data, page_num)?;
-
-                    for cell_ptr in cell_pointers {
-                        if let Ok(btree_info) =
-                            self.parse_sqlite_master_cell(&page_data, cell_ptr as usize)
-                        {
-                            if let Some(info) = btree_info {
-                                btrees.push(info);
-                            }
-                        }
-                    }
-                }
-                BTreePageType::TableInterior => {
-                    // Push child pages
-                    let cell_pointers = header.get_cell_pointers(&page_data, page_num)?;
-                    for cell_ptr in cell_pointers {
-                        let ptr = cell_ptr as usize;
-                        if ptr + 4 <= page_data.len() {
-                            let child = BigEndian::read_u32(&page_data[ptr..ptr + 4]);
-                            stack.push(child);
-                        }
-                    }
-                    if let Some(right_child) = header.right_child {
-                        stack.push(right_child);
-                    }
+        let mut pages = self.pages(root_page);
+
+        while let Some(page) = pages.next_page()? {
+            if page.header.page_type != BTreePageType::TableLeaf {
+                continue;
+            }
+
+            // Parse cells to find tables and indexes
+            let cell_pointers = page.header.get_cell_pointers(&page.data, page.page_number)?;
+            for cell_ptr in cell_pointers {
+                if let Ok(Some(info)) =
+                    pages.scanner.parse_sqlite_master_cell(&page.data, cell_ptr as usize)
+                {
+                    btrees.push(info);
                 }
-                _ => {}
             }
         }
 
@@ -98,7 +351,7 @@ impl<'a> BTreeScanner<'a> {
 
     /// Parse a single sqlite_master cell to extract table/index info
     fn parse_sqlite_master_cell(
-        &self,
+        &mut self,
         page_data: &[u8],
         cell_offset: usize,
     ) -> Result<Option<BTreeInfo>> {
@@ -114,14 +367,13 @@ impl<'a> BTreeScanner<'a> {
         let (_rowid, rowid_len) = parse_varint(&cell_data[offset..])?;
         offset += rowid_len;
 
-        // Now we have the payload
+        // A long CREATE statement spills onto overflow pages
         let payload_size = payload_size as usize;
-        if offset + payload_size > cell_data.len() {
-            // Could be overflow, skip this cell
-            return Ok(None);
-        }
-
-        let payload = &cell_data[offset..offset + payload_size];
+        let usable_size = self.usable_size();
+        let local_len = BTreePageType::TableLeaf.local_payload_size(payload_size, usable_size);
+        let payload =
+            read_full_payload(self.page_cache, &cell_data[offset..], local_len, payload_size)?;
+        let payload = payload.as_slice();
 
         // Parse record header
         let (header_size, header_varint_len) = parse_varint(payload)?;
@@ -151,7 +403,7 @@ impl<'a> BTreeScanner<'a> {
         let mut column_offsets = Vec::new();
         for &st in &serial_types {
             column_offsets.push(data_offset);
-            data_offset += serial_type_content_size(st);
+            data_offset += serial_type_size(st);
         }
 
         // Column 0: type (TEXT)
@@ -174,14 +426,13 @@ impl<'a> BTreeScanner<'a> {
             if root_page > 0 && (obj_type == "table" || obj_type == "index") {
                 // Determine if index is unique:
                 // - Autoindexes (created for PRIMARY KEY/UNIQUE constraints) are always unique
-                // - For explicit indexes, check if SQL contains "UNIQUE"
+                // - For explicit indexes, check for CREATE UNIQUE INDEX
                 let is_unique = if obj_type == "index" {
                     if name.starts_with("sqlite_autoindex_") {
                         // Autoindexes are created for PRIMARY KEY and UNIQUE constraints
                         true
                     } else if let Some(ref sql) = sql_col {
-                        // Check if the CREATE INDEX statement includes UNIQUE
-                        sql.to_uppercase().contains("UNIQUE")
+                        sql::is_unique_index(sql)
                     } else {
                         // No SQL available, assume not unique to be safe
                         false
@@ -198,6 +449,7 @@ impl<'a> BTreeScanner<'a> {
                     sql: sql_col,
                     is_table: obj_type == "table",
                     is_unique,
+                    collations: Vec::new(),
                 }));
             }
         }
@@ -206,7 +458,7 @@ impl<'a> BTreeScanner<'a> {
     }
 
     fn read_text_column(
-        &self,
+        &mut self,
         payload: &[u8],
         serial_types: &[u64],
         offsets: &[usize],
@@ -217,7 +469,7 @@ impl<'a> BTreeScanner<'a> {
         }
 
         let st = serial_types[col];
-        if st < 13 || st % 2 == 0 {
+        if st < 13 || st.is_multiple_of(2) {
             return Ok(None); // Not text
         }
 
@@ -228,9 +480,10 @@ impl<'a> BTreeScanner<'a> {
             return Ok(None);
         }
 
-        Ok(String::from_utf8(payload[offset..offset + size].to_vec()).ok())
+        Ok(self.encoding().decode_strict(&payload[offset..offset + size]))
     }
 
+    /// Read an integer column, or None if it is NULL, text, a blob or cut short
     fn read_int_column(
         &self,
         payload: &[u8],
@@ -244,54 +497,130 @@ impl<'a> BTreeScanner<'a> {
 
         let st = serial_types[col];
         let offset = offsets[col];
+        let Some(data) = payload.get(offset..offset + serial_type_size(st)) else {
+            return Ok(None);
+        };
 
         let value = match st {
-            0 => return Ok(None), // NULL
-            1 => {
-                if offset >= payload.len() {
-                    return Ok(None);
-                }
-                payload[offset] as i8 as i64
-            }
-            2 => {
-                if offset + 2 > payload.len() {
-                    return Ok(None);
-                }
-                BigEndian::read_i16(&payload[offset..offset + 2]) as i64
-            }
-            3 => {
-                if offset + 3 > payload.len() {
-                    return Ok(None);
+            1..=6 | 8 | 9 => read_integer(st, data)?,
+            // A float is truncated toward zero, as CAST(x AS INTEGER) does
+            // (saturating at the i64 range, with NaN read as 0)
+            7 => BigEndian::read_f64(data) as i64,
+            // NULL, text or blob
+            _ => return Ok(None),
+        };
+
+        Ok(Some(value))
+    }
+
+    /// Walk every interior and leaf page of a B-tree once, depth first,
+    /// calling `visit` with each page as it is reached.
+    ///
+    /// A page referenced again after it has been visited is not walked a
+    /// second time, which also keeps a corrupt tree with a cycle from looping
+    /// forever. Each such repeated reference is returned as `(page, parent)`.
+    pub fn walk_pages<F>(&mut self, root_page: u32, mut visit: F) -> Result<Vec<(u32, u32)>>
+    where
+        F: FnMut(&PageVisit) -> Result<()>,
+    {
+        let mut pages = self.pages(root_page);
+        while let Some(page) = pages.next_page()? {
+            visit(&PageVisit {
+                page_number: page.page_number,
+                parent: page.parent,
+                header: &page.header,
+                header_end: page.header_end,
+                data: &page.data,
+            })?;
+        }
+
+        Ok(pages.repeated)
+    }
+
+    /// Iterate over every interior and leaf page of a B-tree with its parsed
+    /// header, in the order `walk_pages` visits them.
+    ///
+    /// The iterator ends after the first error, such as a page that isn't a
+    /// B-tree page.
+    pub fn pages(&mut self, root_page: u32) -> BTreePages<'_, 'a> {
+        BTreePages {
+            scanner: self,
+            stack: vec![(root_page, None)],
+            visited: HashSet::new(),
+            repeated: Vec::new(),
+            failed: false,
+        }
+    }
+
+    /// Collect the page numbers of every interior and leaf page in a B-tree
+    pub fn collect_btree_pages(&mut self, root_page: u32) -> Result<Vec<u32>> {
+        self.pages(root_page).map(|page| page.map(|(page_num, _)| page_num)).collect()
+    }
+
+    /// Collect the overflow pages of every cell in a B-tree, following each
+    /// chain until it ends, leaves the database or loops.
+    pub fn collect_overflow_pages(&mut self, root_page: u32) -> Result<Vec<u32>> {
+        let usable_size = self.usable_size();
+        let mut first_pages = Vec::new();
+        self.walk_pages(root_page, |visit| {
+            let cell_pointers = visit.header.get_cell_pointers(visit.data, visit.page_number)?;
+            for cell_ptr in cell_pointers {
+                let cell = visit.data.get(cell_ptr as usize..).unwrap_or_default();
+                let page_type = visit.header.page_type;
+                if let Ok(Some(overflow)) = page_type.overflow_page(cell, usable_size) {
+                    first_pages.push(overflow);
                 }
-                let b = &payload[offset..offset + 3];
-                ((b[0] as i32) << 16 | (b[1] as i32) << 8 | b[2] as i32) as i64
             }
-            4 => {
-                if offset + 4 > payload.len() {
-                    return Ok(None);
-                }
-                BigEndian::read_i32(&payload[offset..offset + 4]) as i64
+            Ok(())
+        })?;
+
+        let page_count = self.page_cache.effective_page_count();
+        let mut pages = Vec::new();
+        let mut visited = HashSet::new();
+        for first in first_pages {
+            let mut next = first;
+            while next != 0 && next <= page_count && visited.insert(next) {
+                pages.push(next);
+                let data = self.page_cache.get_page_ref(next)?;
+                next = data.get(..4).map_or(0, BigEndian::read_u32);
             }
-            5 => {
-                if offset + 6 > payload.len() {
-                    return Ok(None);
-                }
-                let mut buf = [0u8; 8];
-                buf[2..8].copy_from_slice(&payload[offset..offset + 6]);
-                i64::from_be_bytes(buf) >> 16
+        }
+        Ok(pages)
+    }
+
+    /// Measure how full the leaf pages of a B-tree are
+    pub fn fill_factor(&mut self, btree: &BTreeInfo) -> Result<FillFactor> {
+        let mut fill = FillFactor {
+            name: btree.name.clone(),
+            root_page: btree.root_page,
+            leaf_pages: 0,
+            used_bytes: 0,
+            usable_bytes: 0,
+        };
+
+        self.walk_pages(btree.root_page, |visit| {
+            let (header, page_data) = (visit.header, visit.data);
+            if header.page_type.is_interior() {
+                return Ok(());
             }
-            6 => {
-                if offset + 8 > payload.len() {
-                    return Ok(None);
-                }
-                BigEndian::read_i64(&payload[offset..offset + 8])
+
+            // Page 1 loses the first 100 bytes to the database header
+            let header_offset = if visit.page_number == 1 { 100 } else { 0 };
+            let cell_pointers = header.get_cell_pointers(page_data, visit.page_number)?;
+
+            let mut used = visit.header_end - header_offset + 2 * cell_pointers.len();
+            for &cell_ptr in &cell_pointers {
+                let cell = page_data.get(cell_ptr as usize..).unwrap_or_default();
+                used += header.page_type.cell_size(cell, page_data.len())?;
             }
-            8 => 0,
-            9 => 1,
-            _ => return Ok(None),
-        };
 
-        Ok(Some(value))
+            fill.leaf_pages += 1;
+            fill.used_bytes += used as u64;
+            fill.usable_bytes += (page_data.len() - header_offset) as u64;
+            Ok(())
+        })?;
+
+        Ok(fill)
     }
 
     /// Collect all rowids from a table B-tree
@@ -300,218 +629,438 @@ impl<'a> BTreeScanner<'a> {
         root_page: u32,
     ) -> Result<Vec<(i64, RowidLocation)>> {
         let mut rowids = Vec::new();
-        let mut stack = vec![root_page];
-
-        while let Some(page_num) = stack.pop() {
-            let frame_index = self.page_cache.get_frame_index(page_num);
-            let page_data = self.page_cache.get_page(page_num)?;
-            let (header, _) = BTreePageHeader::parse(&page_data, page_num)?;
-
-            match header.page_type {
-                BTreePageType::TableLeaf => {
-                    let cell_pointers = header.get_cell_pointers(&page_data, page_num)?;
-
-                    for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
-                        let cell_offset = cell_ptr as usize;
-                        if cell_offset >= page_data.len() {
-                            continue;
-                        }
-
-                        let cell_data = &page_data[cell_offset..];
-
-                        // Parse payload size varint
-                        let (_, payload_len) = parse_varint(cell_data)?;
-                        // Parse rowid varint
-                        let (rowid, _) = parse_varint(&cell_data[payload_len..])?;
-
-                        rowids.push((
-                            rowid as i64,
-                            RowidLocation {
-                                page_number: page_num,
-                                cell_index: cell_idx as u16,
-                                frame_index,
-                            },
-                        ));
-                    }
-                }
-                BTreePageType::TableInterior => {
-                    let cell_pointers = header.get_cell_pointers(&page_data, page_num)?;
-
-                    for &cell_ptr in &cell_pointers {
-                        let cell_offset = cell_ptr as usize;
-                        if cell_offset + 4 > page_data.len() {
-                            continue;
-                        }
-
-                        // First 4 bytes are left child pointer
-                        let left_child = BigEndian::read_u32(&page_data[cell_offset..cell_offset + 4]);
-                        stack.push(left_child);
-                    }
+        let mut pages = self.pages(root_page);
 
-                    // Don't forget the rightmost child
-                    if let Some(right_child) = header.right_child {
-                        stack.push(right_child);
-                    }
-                }
-                _ => {
-                    // Index pages in a table B-tree shouldn't happen
+        while let Some(page) = pages.next_page()? {
+            // Index pages in a table B-tree shouldn't happen
+            if page.header.page_type != BTreePageType::TableLeaf {
+                continue;
+            }
+
+            let page_num = page.page_number;
+            let frame_index = pages.scanner.page_cache.get_frame_index(page_num);
+            let cell_pointers = page.header.get_cell_pointers(&page.data, page_num)?;
+
+            for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
+                let cell_offset = cell_ptr as usize;
+                if cell_offset >= page.data.len() {
+                    continue;
                 }
+
+                let cell_data = &page.data[cell_offset..];
+
+                // Parse payload size varint
+                let (_, payload_len) = parse_varint(cell_data)?;
+                // Parse rowid varint
+                let (rowid, _) = parse_varint(&cell_data[payload_len..])?;
+
+                rowids.push((
+                    rowid as i64,
+                    RowidLocation {
+                        page_number: page_num,
+                        cell_index: cell_idx as u16,
+                        frame_index,
+                    },
+                ));
             }
         }
 
         Ok(rowids)
     }
 
-    /// Collect all keys from an index B-tree
-    pub fn collect_index_keys(
+    /// Collect the rowids of a table B-tree in key order, walking the tree
+    /// left to right
+    pub fn collect_table_rowids_in_order(
+        &mut self,
+        root_page: u32,
+    ) -> Result<Vec<(i64, RowidLocation)>> {
+        let mut rowids = Vec::new();
+        let mut pages = self.pages(root_page);
+
+        while let Some(page) = pages.next_page()? {
+            if page.header.page_type != BTreePageType::TableLeaf {
+                continue;
+            }
+
+            let page_num = page.page_number;
+            let frame_index = pages.scanner.page_cache.get_frame_index(page_num);
+            let cell_pointers = page.header.get_cell_pointers(&page.data, page_num)?;
+
+            for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
+                let cell_data = page.data.get(cell_ptr as usize..).unwrap_or_default();
+                let Ok((_, payload_len)) = parse_varint(cell_data) else {
+                    continue;
+                };
+                let Ok((rowid, _)) = parse_varint(&cell_data[payload_len..]) else {
+                    continue;
+                };
+                rowids.push((
+                    rowid as i64,
+                    RowidLocation {
+                        page_number: page_num,
+                        cell_index: cell_idx as u16,
+                        frame_index,
+                    },
+                ));
+            }
+        }
+
+        Ok(rowids)
+    }
+
+    /// Collect the decoded record of every row of a table B-tree, in rowid
+    /// order. Cells whose payload or record can't be read are left out.
+    pub fn collect_table_records(
+        &mut self,
+        root_page: u32,
+    ) -> Result<Vec<(i64, Record, RowidLocation)>> {
+        let mut rows = Vec::new();
+        let usable_size = self.usable_size();
+        let encoding = self.encoding();
+        let mut pages = self.pages(root_page);
+
+        while let Some(page) = pages.next_page()? {
+            if page.header.page_type != BTreePageType::TableLeaf {
+                continue;
+            }
+
+            let page_num = page.page_number;
+            let page_cache = &mut *pages.scanner.page_cache;
+            let frame_index = page_cache.get_frame_index(page_num);
+            let cell_pointers = page.header.get_cell_pointers(&page.data, page_num)?;
+
+            for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
+                let cell_data = page.data.get(cell_ptr as usize..).unwrap_or_default();
+                let Ok((payload_size, payload_len)) = parse_varint(cell_data) else {
+                    continue;
+                };
+                let Ok((rowid, rowid_len)) = parse_varint(&cell_data[payload_len..]) else {
+                    continue;
+                };
+                let payload_size = payload_size as usize;
+                let local_len =
+                    BTreePageType::TableLeaf.local_payload_size(payload_size, usable_size);
+                let payload = &cell_data[payload_len + rowid_len..];
+                let Ok(record) = read_full_payload(page_cache, payload, local_len, payload_size)
+                    .and_then(|payload| parse_record(&payload, encoding))
+                else {
+                    continue;
+                };
+                rows.push((
+                    rowid as i64,
+                    record,
+                    RowidLocation {
+                        page_number: page_num,
+                        cell_index: cell_idx as u16,
+                        frame_index,
+                    },
+                ));
+            }
+        }
+
+        Ok(rows)
+    }
+
+    /// Collect the keys of an index B-tree in key order, walking the tree
+    /// left to right. Unlike `collect_index_keys`, the keys held by interior
+    /// cells are included, each between the subtrees on either side of it.
+    pub fn collect_index_keys_in_order(
         &mut self,
         root_page: u32,
     ) -> Result<Vec<(IndexKey, RowidLocation)>> {
-        let mut keys = Vec::new();
-        let mut stack = vec![root_page];
-
-        while let Some(page_num) = stack.pop() {
-            let frame_index = self.page_cache.get_frame_index(page_num);
-            let page_data = self.page_cache.get_page(page_num)?;
-            let (header, _) = BTreePageHeader::parse(&page_data, page_num)?;
-
-            match header.page_type {
-                BTreePageType::IndexLeaf => {
-                    let cell_pointers = header.get_cell_pointers(&page_data, page_num)?;
-
-                    for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
-                        let cell_offset = cell_ptr as usize;
-                        if cell_offset >= page_data.len() {
-                            continue;
-                        }
-
-                        let cell_data = &page_data[cell_offset..];
-
-                        // Parse payload size varint
-                        let (payload_size, payload_len) = parse_varint(cell_data)?;
-                        let payload_size = payload_size as usize;
-
-                        // The payload starts right after the payload size
-                        if payload_len + payload_size > cell_data.len() {
-                            continue; // Overflow, skip
-                        }
-
-                        let payload = &cell_data[payload_len..payload_len + payload_size];
-                        if let Ok(key) = extract_index_key(payload) {
-                            keys.push((
-                                key,
-                                RowidLocation {
-                                    page_number: page_num,
-                                    cell_index: cell_idx as u16,
-                                    frame_index,
-                                },
-                            ));
-                        }
+        // The steps of each page in order: its keys, and for interior pages
+        // the child subtrees between them
+        let mut layouts = HashMap::new();
+        let mut pages = self.pages(root_page);
+
+        while let Some(page) = pages.next_page()? {
+            if !matches!(
+                page.header.page_type,
+                BTreePageType::IndexLeaf | BTreePageType::IndexInterior
+            ) {
+                continue;
+            }
+
+            let page_num = page.page_number;
+            let frame_index = pages.scanner.page_cache.get_frame_index(page_num);
+            let is_interior = page.header.page_type.is_interior();
+            let cell_pointers = page.header.get_cell_pointers(&page.data, page_num)?;
+            let mut steps = Vec::new();
+
+            for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
+                let mut cell_offset = cell_ptr as usize;
+
+                // Interior cells start with a 4-byte left child pointer
+                if is_interior {
+                    if cell_offset + 4 > page.data.len() {
+                        continue;
                     }
+                    steps.push(InOrder::Page(BigEndian::read_u32(&page.data[cell_offset..])));
+                    cell_offset += 4;
                 }
-                BTreePageType::IndexInterior => {
-                    let cell_pointers = header.get_cell_pointers(&page_data, page_num)?;
-
-                    for &cell_ptr in &cell_pointers {
-                        let cell_offset = cell_ptr as usize;
-                        if cell_offset + 4 > page_data.len() {
-                            continue;
-                        }
-
-                        // First 4 bytes are left child pointer
-                        let left_child = BigEndian::read_u32(&page_data[cell_offset..cell_offset + 4]);
-                        stack.push(left_child);
-                    }
 
-                    if let Some(right_child) = header.right_child {
-                        stack.push(right_child);
+                let cell_data = page.data.get(cell_offset..).unwrap_or_default();
+                if let Ok((payload_size, payload_len)) = parse_varint(cell_data)
+                    && let Ok(payload) = pages
+                        .scanner
+                        .index_payload(&cell_data[payload_len..], payload_size as usize)
+                    && let Ok(key) = extract_index_key(&payload)
+                {
+                    let location = RowidLocation {
+                        page_number: page_num,
+                        cell_index: cell_idx as u16,
+                        frame_index,
+                    };
+                    steps.push(InOrder::Key(key, location));
+                }
+            }
+            if let Some(right_child) = page.header.right_child {
+                steps.push(InOrder::Page(right_child));
+            }
+            layouts.insert(page_num, steps);
+        }
+
+        // Expand each page in place of the pointer to it. A page is only
+        // expanded once, so a tree that reaches a page twice still ends.
+        let mut keys = Vec::new();
+        let mut stack = vec![InOrder::Page(root_page)];
+        while let Some(step) = stack.pop() {
+            match step {
+                InOrder::Page(page_num) => {
+                    if let Some(steps) = layouts.remove(&page_num) {
+                        stack.extend(steps.into_iter().rev());
                     }
                 }
-                _ => {}
+                InOrder::Key(key, location) => keys.push((key, location)),
             }
         }
 
         Ok(keys)
     }
 
-    /// Collect all rowids referenced by an index B-tree
-    /// Returns the rowids that the index entries point to (the last column in each index entry)
-    pub fn collect_index_rowids(&mut self, root_page: u32) -> Result<Vec<i64>> {
-        let mut rowids = Vec::new();
-        let mut stack = vec![root_page];
+    /// Collect all keys from the leaf pages of an index B-tree
+    /// Each key comes with the rowid its entry points to, if the trailing
+    /// column holds an integer (WITHOUT ROWID tables store their primary key there)
+    pub fn collect_index_keys(
+        &mut self,
+        root_page: u32,
+    ) -> Result<Vec<(IndexKey, Option<i64>, RowidLocation)>> {
+        self.collect_index_entries(root_page, false)
+    }
 
-        while let Some(page_num) = stack.pop() {
-            let page_data = self.page_cache.get_page(page_num)?;
-            let (header, _) = BTreePageHeader::parse(&page_data, page_num)?;
+    /// Collect all keys from an index B-tree, including those held by
+    /// interior cells
+    ///
+    /// An index B-tree stores each entry exactly once, and the divider keys of
+    /// interior cells are entries in their own right rather than copies of
+    /// leaf keys, so they count towards uniqueness like any other.
+    pub fn collect_index_keys_including_interior(
+        &mut self,
+        root_page: u32,
+    ) -> Result<Vec<(IndexKey, Option<i64>, RowidLocation)>> {
+        self.collect_index_entries(root_page, true)
+    }
 
-            match header.page_type {
-                BTreePageType::IndexLeaf => {
-                    let cell_pointers = header.get_cell_pointers(&page_data, page_num)?;
+    /// Collect the keys of an index B-tree, from interior cells too if asked
+    fn collect_index_entries(
+        &mut self,
+        root_page: u32,
+        include_interior: bool,
+    ) -> Result<Vec<(IndexKey, Option<i64>, RowidLocation)>> {
+        let mut keys = Vec::new();
+        let mut pages = self.pages(root_page);
+
+        while let Some(page) = pages.next_page()? {
+            if !matches!(
+                page.header.page_type,
+                BTreePageType::IndexLeaf | BTreePageType::IndexInterior
+            ) {
+                continue;
+            }
 
-                    for &cell_ptr in &cell_pointers {
-                        let cell_offset = cell_ptr as usize;
-                        if cell_offset >= page_data.len() {
-                            continue;
-                        }
+            let page_num = page.page_number;
+            let frame_index = pages.scanner.page_cache.get_frame_index(page_num);
+            let is_interior = page.header.page_type.is_interior();
+            if is_interior && !include_interior {
+                continue;
+            }
+            let cell_pointers = page.header.get_cell_pointers(&page.data, page_num)?;
 
-                        let cell_data = &page_data[cell_offset..];
+            for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
+                // Interior cells start with a 4-byte left child pointer
+                let cell_offset = cell_ptr as usize + if is_interior { 4 } else { 0 };
+                if cell_offset >= page.data.len() {
+                    continue;
+                }
 
-                        // Parse payload size varint
-                        let (payload_size, payload_len) = parse_varint(cell_data)?;
-                        let payload_size = payload_size as usize;
+                let cell_data = &page.data[cell_offset..];
 
-                        // The payload starts right after the payload size
-                        if payload_len + payload_size > cell_data.len() {
-                            continue; // Overflow, skip
-                        }
+                // Parse payload size varint
+                let (payload_size, payload_len) = parse_varint(cell_data)?;
+                let payload_size = payload_size as usize;
 
-                        let payload = &cell_data[payload_len..payload_len + payload_size];
-                        if let Ok(rowid) = extract_index_rowid(payload) {
-                            rowids.push(rowid);
-                        }
-                    }
+                // The payload starts right after the payload size
+                let Ok(payload) =
+                    pages.scanner.index_payload(&cell_data[payload_len..], payload_size)
+                else {
+                    continue;
+                };
+                if let Ok(key) = extract_index_key(&payload) {
+                    keys.push((
+                        key,
+                        extract_index_rowid(&payload).ok(),
+                        RowidLocation {
+                            page_number: page_num,
+                            cell_index: cell_idx as u16,
+                            frame_index,
+                        },
+                    ));
                 }
-                BTreePageType::IndexInterior => {
-                    let cell_pointers = header.get_cell_pointers(&page_data, page_num)?;
-
-                    for &cell_ptr in &cell_pointers {
-                        let cell_offset = cell_ptr as usize;
-                        if cell_offset + 4 > page_data.len() {
-                            continue;
-                        }
-
-                        // First 4 bytes are left child pointer
-                        let left_child =
-                            BigEndian::read_u32(&page_data[cell_offset..cell_offset + 4]);
-                        stack.push(left_child);
-                    }
+            }
+        }
 
-                    if let Some(right_child) = header.right_child {
-                        stack.push(right_child);
-                    }
-                }
-                _ => {}
+        Ok(keys)
+    }
+
+    /// Collect the number of record columns in every index entry
+    /// Covers both leaf and interior cells, since interior cells of an index carry keys too.
+    /// An entry whose record header can't be parsed has a count of None.
+    pub fn collect_index_column_counts(
+        &mut self,
+        root_page: u32,
+    ) -> Result<Vec<(Option<usize>, RowidLocation)>> {
+        let mut counts = Vec::new();
+        let mut pages = self.pages(root_page);
+
+        while let Some(page) = pages.next_page()? {
+            if !matches!(
+                page.header.page_type,
+                BTreePageType::IndexLeaf | BTreePageType::IndexInterior
+            ) {
+                continue;
+            }
+
+            let page_num = page.page_number;
+            let frame_index = pages.scanner.page_cache.get_frame_index(page_num);
+            let is_interior = page.header.page_type.is_interior();
+            let cell_pointers = page.header.get_cell_pointers(&page.data, page_num)?;
+
+            for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
+                // Interior cells start with a 4-byte left child pointer
+                let cell_offset = cell_ptr as usize + if is_interior { 4 } else { 0 };
+
+                // The record header sits at the start of the payload, so it can be
+                // read even when the rest of the payload overflows
+                let cell_data = page.data.get(cell_offset..).unwrap_or_default();
+                let count = parse_varint(cell_data)
+                    .and_then(|(_, payload_len)| parse_record_header(&cell_data[payload_len..]))
+                    .map(|(serial_types, _)| serial_types.len());
+                counts.push((
+                    count.ok(),
+                    RowidLocation {
+                        page_number: page_num,
+                        cell_index: cell_idx as u16,
+                        frame_index,
+                    },
+                ));
             }
         }
 
-        Ok(rowids)
+        Ok(counts)
+    }
+
+    /// Collect all rowids referenced by an index B-tree
+    /// Returns the rowids that the index entries point to (the last column in each index entry),
+    /// from interior cells as well as leaf cells
+    pub fn collect_index_rowids(&mut self, root_page: u32) -> Result<Vec<i64>> {
+        let entries = self.collect_index_entries(root_page, true)?;
+        Ok(entries.into_iter().filter_map(|(_, rowid, _)| rowid).collect())
+    }
+
+    /// Full payload of an index cell, following its overflow chain if the
+    /// payload doesn't fit on the page. `cell_data` starts at the payload.
+    fn index_payload(&mut self, cell_data: &[u8], payload_size: usize) -> Result<Vec<u8>> {
+        // The rest of the page follows the cell, so whether the payload
+        // overflows depends on the local size limit, not on the bytes left
+        let usable_size = self.usable_size();
+        let local_len = BTreePageType::IndexLeaf.local_payload_size(payload_size, usable_size);
+        read_full_payload(self.page_cache, cell_data, local_len, payload_size)
+    }
+
+    /// Borrow a B-tree page without the bytes reserved at its end, so that a
+    /// cell pointer into the reserved region reads as out of bounds
+    fn btree_page(&mut self, page_num: u32) -> Result<&[u8]> {
+        let usable_size = self.usable_size();
+        let page_data = self.page_cache.get_page_ref(page_num)?;
+        Ok(&page_data[..usable_size.min(page_data.len())])
+    }
+
+    /// Usable size of each page, read from page 1 on first use. Without a
+    /// readable page 1, no bytes are assumed to be reserved.
+    fn usable_size(&mut self) -> usize {
+        let page_cache = &mut self.page_cache;
+        *self.usable_size.get_or_insert_with(|| {
+            let header = page_cache.get_page_ref(1).ok().and_then(|p| DbHeader::parse(p).ok());
+            header.map_or(page_cache.page_size(), |h| h.usable_size()) as usize
+        })
+    }
+
+    /// Text encoding of the database, UTF-8 if page 1 can't be read
+    pub fn encoding(&mut self) -> TextEncoding {
+        let page_cache = &mut self.page_cache;
+        *self.encoding.get_or_insert_with(|| {
+            let header = page_cache.get_page_ref(1).ok().and_then(|p| DbHeader::parse(p).ok());
+            header.map_or_else(TextEncoding::default, |h| h.encoding())
+        })
     }
 }
 
-/// Get the content size for a serial type
-fn serial_type_content_size(serial_type: u64) -> usize {
-    match serial_type {
-        0 => 0,
-        1 => 1,
-        2 => 2,
-        3 => 3,
-        4 => 4,
-        5 => 6,
-        6 => 8,
-        7 => 8,
-        8 | 9 => 0,
-        n if n >= 12 && n % 2 == 0 => ((n - 12) / 2) as usize,
-        n if n >= 13 && n % 2 == 1 => ((n - 13) / 2) as usize,
-        _ => 0,
+/// Step of an in-order walk of an index B-tree
+enum InOrder {
+    /// A child page, whose subtree comes at this point
+    Page(u32),
+    /// A key held by a cell of the page
+    Key(IndexKey, RowidLocation),
+}
+
+/// Assemble the full payload of a cell whose content spills onto overflow
+/// pages. `cell_data` starts at the payload: its first `local_len` bytes are
+/// stored on the page, followed by the 4-byte number of the first overflow
+/// page. Each overflow page holds the number of the next one, then content.
+pub fn read_full_payload(
+    page_cache: &mut PageCache,
+    cell_data: &[u8],
+    local_len: usize,
+    total_len: usize,
+) -> Result<Vec<u8>> {
+    let mut payload = cell_data
+        .get(..local_len.min(total_len))
+        .ok_or(WalValidatorError::UnexpectedEof)?
+        .to_vec();
+    if local_len >= total_len {
+        return Ok(payload);
+    }
+
+    let usable_size = DbHeader::parse(page_cache.get_page_ref(1)?)?.usable_size() as usize;
+    let pointer = cell_data
+        .get(local_len..local_len + 4)
+        .ok_or(WalValidatorError::UnexpectedEof)?;
+    let mut next = BigEndian::read_u32(pointer);
+    let mut visited = HashSet::new();
+
+    while payload.len() < total_len {
+        // A chain that ends early or loops back can't hold the payload
+        if next == 0 || !visited.insert(next) {
+            return Err(WalValidatorError::UnexpectedEof);
+        }
+        let page_data = page_cache.get_page_ref(next)?;
+        let content = page_data
+            .get(4..usable_size)
+            .ok_or(WalValidatorError::UnexpectedEof)?;
+        let wanted = (total_len - payload.len()).min(content.len());
+        payload.extend_from_slice(&content[..wanted]);
+        next = BigEndian::read_u32(&page_data[..4]);
     }
+
+    Ok(payload)
 }
@@ -1,8 +1,9 @@
 use byteorder::{BigEndian, ByteOrder};
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
+use crate::btree::TextEncoding;
 use crate::error::{Result, WalValidatorError};
 
 /// SQLite database file header (first 100 bytes of page 1)
@@ -10,10 +11,21 @@ use crate::error::{Result, WalValidatorError};
 pub struct DbHeader {
     /// Database page size in bytes
     pub page_size: u32,
+    /// Bytes reserved at the end of each page (e.g. by an encryption extension)
+    pub reserved_bytes: u8,
+    /// File change counter (incremented by each transaction that writes page 1)
+    pub change_counter: u32,
     /// Size of the database in pages
     pub page_count: u32,
+    /// Page number of the first freelist trunk page, 0 if there are no free pages
+    pub freelist_head: u32,
+    /// Total number of freelist pages, trunks and leaves alike
+    pub freelist_count: u32,
     /// Schema cookie (incremented on schema changes)
     pub schema_cookie: u32,
+    /// Page number of the largest root B-tree page in auto-vacuum and
+    /// incremental-vacuum databases, 0 otherwise
+    pub largest_root_page: u32,
     /// Text encoding (1=UTF-8, 2=UTF-16le, 3=UTF-16be)
     pub text_encoding: u32,
 }
@@ -24,12 +36,51 @@ const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
 impl DbHeader {
     /// Parse the database header from a file
     pub fn from_file(path: &Path) -> Result<Self> {
+        let file_size = std::fs::metadata(path)?.len();
+
+        // Report placeholder or partially-copied files explicitly
+        if file_size == 0 {
+            return Err(WalValidatorError::EmptyDatabase(path.to_path_buf()));
+        }
+        if file_size < 100 {
+            return Err(WalValidatorError::TruncatedDatabaseHeader { size: file_size });
+        }
+
         let mut file = File::open(path)?;
         let mut header = [0u8; 100];
         file.read_exact(&mut header)?;
         Self::parse(&header)
     }
 
+    /// Parse the database header from the start of any seekable source
+    pub fn from_reader<R: Read + Seek>(reader: &mut R) -> Result<Self> {
+        let size = reader.seek(SeekFrom::End(0))?;
+        if size == 0 {
+            return Err(WalValidatorError::EmptyDatabaseImage);
+        }
+        if size < 100 {
+            return Err(WalValidatorError::TruncatedDatabaseHeader { size });
+        }
+
+        let mut header = [0u8; 100];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut header)?;
+        Self::parse(&header)
+    }
+
+    /// Parse the database header from an in-memory database image
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        if data.is_empty() {
+            return Err(WalValidatorError::EmptyDatabaseImage);
+        }
+        if data.len() < 100 {
+            return Err(WalValidatorError::TruncatedDatabaseHeader {
+                size: data.len() as u64,
+            });
+        }
+        Self::parse(data)
+    }
+
     /// Parse the database header from bytes
     pub fn parse(data: &[u8]) -> Result<Self> {
         if data.len() < 100 {
@@ -51,24 +102,58 @@ impl DbHeader {
         };
 
         // Validate page size is a power of 2 between 512 and 65536
-        if !page_size.is_power_of_two() || page_size < 512 || page_size > 65536 {
+        if !page_size.is_power_of_two() || !(512..=65536).contains(&page_size) {
             return Err(WalValidatorError::InvalidPageSize(page_size));
         }
 
+        // Reserved space per page at offset 20
+        let reserved_bytes = data[20];
+
+        // File change counter at offset 24-27
+        let change_counter = BigEndian::read_u32(&data[24..28]);
+
         // Database size in pages at offset 28-31
         let page_count = BigEndian::read_u32(&data[28..32]);
 
+        // First freelist trunk page and freelist page count at offsets 32-39
+        let freelist_head = BigEndian::read_u32(&data[32..36]);
+        let freelist_count = BigEndian::read_u32(&data[36..40]);
+
         // Schema cookie at offset 40-43
         let schema_cookie = BigEndian::read_u32(&data[40..44]);
 
+        // Largest root page at offset 52-55 (non-zero only with auto-vacuum)
+        let largest_root_page = BigEndian::read_u32(&data[52..56]);
+
         // Text encoding at offset 56-59
         let text_encoding = BigEndian::read_u32(&data[56..60]);
 
         Ok(DbHeader {
             page_size,
+            reserved_bytes,
+            change_counter,
             page_count,
+            freelist_head,
+            freelist_count,
             schema_cookie,
+            largest_root_page,
             text_encoding,
         })
     }
+
+    /// Returns true if the database keeps pointer-map pages, which it does
+    /// in auto-vacuum and incremental-vacuum mode
+    pub fn has_ptrmap(&self) -> bool {
+        self.largest_root_page != 0
+    }
+
+    /// Bytes of each page available to the B-tree layer
+    pub fn usable_size(&self) -> u32 {
+        self.page_size - self.reserved_bytes as u32
+    }
+
+    /// Encoding of the database's text values
+    pub fn encoding(&self) -> TextEncoding {
+        TextEncoding::from_header(self.text_encoding)
+    }
 }
@@ -1,5 +1,7 @@
 pub mod header;
 pub mod page;
+pub mod page_hashes;
 
 pub use header::DbHeader;
 pub use page::PageReader;
+pub use page_hashes::PageHashes;
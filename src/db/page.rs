@@ -1,13 +1,46 @@
+use std::borrow::Cow;
+use std::fmt;
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
 use crate::error::{Result, WalValidatorError};
 
+/// Where a page reader gets its bytes from
+enum PageSource {
+    /// Database file on disk, opened for each read
+    File(PathBuf),
+    /// Database image held in memory
+    Memory(Vec<u8>),
+    /// Any other seekable source, such as a buffer received over the network
+    Reader(Mutex<Box<dyn ReadSeek>>),
+    /// Database file mapped into memory
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+/// A seekable byte source a page reader can own
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+impl fmt::Debug for PageSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageSource::File(path) => f.debug_tuple("File").field(path).finish(),
+            PageSource::Memory(data) => write!(f, "Memory({} bytes)", data.len()),
+            PageSource::Reader(_) => write!(f, "Reader"),
+            #[cfg(feature = "mmap")]
+            PageSource::Mapped(map) => write!(f, "Mapped({} bytes)", map.len()),
+        }
+    }
+}
+
 /// Reads pages from a SQLite database file
 #[derive(Debug)]
 pub struct PageReader {
-    path: PathBuf,
+    source: PageSource,
     page_size: u32,
     page_count: u32,
     /// Actual file size in bytes (used to check if page exists in file)
@@ -22,16 +55,83 @@ impl PageReader {
             .unwrap_or(0);
 
         PageReader {
-            path: path.to_path_buf(),
+            source: PageSource::File(path.to_path_buf()),
             page_size,
             page_count,
             file_size,
         }
     }
 
+    /// Open a database file for reading pages.
+    ///
+    /// Unlike [`PageReader::new`], the file is opened once rather than for
+    /// every read. With the `mmap` feature the file is mapped into memory and
+    /// pages are served from the mapping; if mapping fails (some filesystems
+    /// and platforms don't support it), pages are read through the open file.
+    ///
+    /// A mapped file must not be truncated while the reader is in use:
+    /// touching a page past the new end of the file raises `SIGBUS` and
+    /// kills the process. Use [`PageReader::new`] for a database that another
+    /// process may shrink, such as one that is still being checkpointed.
+    pub fn open(path: &Path, page_size: u32, page_count: u32) -> Result<Self> {
+        let file = File::open(path)?;
+
+        #[cfg(feature = "mmap")]
+        {
+            // SAFETY: the mapping is read-only. Another process writing the
+            // file (such as a checkpoint) changes the pages seen through it,
+            // which reads through the file would observe as well. Another
+            // process truncating the file is not safe: reading a mapped page
+            // past the new end raises SIGBUS, which Rust can't turn into an
+            // error. Callers opt into that risk by choosing `open` (see above).
+            if let Ok(map) = unsafe { memmap2::Mmap::map(&file) } {
+                return Ok(PageReader {
+                    file_size: map.len() as u64,
+                    source: PageSource::Mapped(map),
+                    page_size,
+                    page_count,
+                });
+            }
+        }
+
+        Self::from_reader(file, page_size, page_count)
+    }
+
+    /// Create a page reader over an in-memory database image
+    pub fn from_bytes(data: Vec<u8>, page_size: u32, page_count: u32) -> Self {
+        PageReader {
+            file_size: data.len() as u64,
+            source: PageSource::Memory(data),
+            page_size,
+            page_count,
+        }
+    }
+
+    /// Create a page reader over any seekable source of database bytes
+    pub fn from_reader<R: Read + Seek + Send + 'static>(
+        mut reader: R,
+        page_size: u32,
+        page_count: u32,
+    ) -> Result<Self> {
+        let file_size = reader.seek(SeekFrom::End(0))?;
+
+        Ok(PageReader {
+            source: PageSource::Reader(Mutex::new(Box::new(reader))),
+            page_size,
+            page_count,
+            file_size,
+        })
+    }
+
     /// Read a page from the database file (1-indexed)
     /// Returns None if the page doesn't exist in the file (may be in WAL only)
     pub fn read_page(&self, page_num: u32) -> Result<Vec<u8>> {
+        self.page(page_num).map(Cow::into_owned)
+    }
+
+    /// Read a page from the database file (1-indexed), borrowing it from
+    /// memory when the reader holds the whole file there
+    pub fn page(&self, page_num: u32) -> Result<Cow<'_, [u8]>> {
         if page_num == 0 {
             return Err(WalValidatorError::PageNotFound { page_num });
         }
@@ -44,14 +144,46 @@ impl PageReader {
             // Page doesn't exist in the database file - it may only exist in WAL
             return Err(WalValidatorError::PageNotFound { page_num });
         }
+        let range = offset as usize..end_offset as usize;
+
+        match &self.source {
+            PageSource::File(path) => {
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::Start(offset))?;
 
-        let mut file = File::open(&self.path)?;
-        file.seek(SeekFrom::Start(offset))?;
+                let mut buffer = vec![0u8; self.page_size as usize];
+                file.read_exact(&mut buffer)?;
 
-        let mut buffer = vec![0u8; self.page_size as usize];
-        file.read_exact(&mut buffer)?;
+                Ok(Cow::Owned(buffer))
+            }
+            PageSource::Memory(data) => Ok(Cow::Borrowed(&data[range])),
+            PageSource::Reader(reader) => {
+                // A panic elsewhere can't leave the reader half-used: every
+                // read seeks to its page first
+                let mut reader = reader.lock().unwrap_or_else(|e| e.into_inner());
+                reader.seek(SeekFrom::Start(offset))?;
 
-        Ok(buffer)
+                let mut buffer = vec![0u8; self.page_size as usize];
+                reader.read_exact(&mut buffer)?;
+
+                Ok(Cow::Owned(buffer))
+            }
+            #[cfg(feature = "mmap")]
+            PageSource::Mapped(map) => Ok(Cow::Borrowed(&map[range])),
+        }
+    }
+
+    /// Borrow a page (1-indexed) when the reader holds the whole file in
+    /// memory, either as an in-memory image or a mapping. Returns None for
+    /// readers that would have to read the page, and for missing pages.
+    pub fn page_in_memory(&self, page_num: u32) -> Option<&[u8]> {
+        match &self.source {
+            PageSource::File(_) | PageSource::Reader(_) => None,
+            _ => match self.page(page_num) {
+                Ok(Cow::Borrowed(page)) => Some(page),
+                _ => None,
+            },
+        }
     }
 
     /// Get the page size
@@ -63,4 +195,14 @@ impl PageReader {
     pub fn page_count(&self) -> u32 {
         self.page_count
     }
+
+    /// Size of the underlying file in bytes
+    pub fn file_size(&self) -> u64 {
+        self.file_size
+    }
+
+    /// Number of whole pages actually present in the file
+    pub fn pages_in_file(&self) -> u32 {
+        (self.file_size / self.page_size as u64) as u32
+    }
 }
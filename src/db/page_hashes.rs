@@ -0,0 +1,99 @@
+//! Per-page content hashes of a database file, saved between runs.
+//!
+//! A base page should only change when a checkpoint copies a WAL frame back
+//! into the database. Comparing the hashes from a previous run against the
+//! current file finds pages that changed with no frame to explain it, which
+//! points at bit-rot or an external writer.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::PageReader;
+use crate::error::{Result, WalValidatorError};
+
+/// Hashes of every page in a database file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageHashes {
+    /// Page size the hashes were computed with
+    pub page_size: u32,
+    /// Hash of each page, by page number
+    pub hashes: BTreeMap<u32, u64>,
+    /// Pages written by the WAL when the hashes were taken; a checkpoint
+    /// may legitimately copy these into the database before the next run
+    pub wal_pages: BTreeSet<u32>,
+}
+
+impl PageHashes {
+    /// Hash every page present in the database file
+    pub fn compute(reader: &PageReader, wal_pages: BTreeSet<u32>) -> Result<Self> {
+        let mut hashes = BTreeMap::new();
+        for page_num in 1..=reader.pages_in_file() {
+            let data = reader.read_page(page_num)?;
+            hashes.insert(page_num, fnv1a(&data));
+        }
+
+        Ok(PageHashes {
+            page_size: reader.page_size(),
+            hashes,
+            wal_pages,
+        })
+    }
+
+    /// Load hashes saved by a previous run.
+    /// Returns None if the file doesn't exist yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let reader = BufReader::new(File::open(path)?);
+        serde_json::from_reader(reader)
+            .map(Some)
+            .map_err(|e| WalValidatorError::InvalidHashFile {
+                path: path.to_path_buf(),
+                reason: e.to_string(),
+            })
+    }
+
+    /// Write the hashes to a file, replacing any previous contents
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, self).map_err(std::io::Error::from)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Pages whose hash differs from `previous` although no WAL frame, in
+    /// either run, wrote them. Pages present in only one of the two runs
+    /// are ignored, since the file may have grown or been truncated.
+    pub fn silent_changes(&self, previous: &PageHashes) -> Vec<u32> {
+        self.hashes
+            .iter()
+            .filter(|(page_num, hash)| {
+                previous
+                    .hashes
+                    .get(page_num)
+                    .is_some_and(|old| old != *hash)
+            })
+            .map(|(&page_num, _)| page_num)
+            .filter(|page_num| {
+                !self.wal_pages.contains(page_num) && !previous.wal_pages.contains(page_num)
+            })
+            .collect()
+    }
+}
+
+/// 64-bit FNV-1a hash. Used instead of the standard library hasher, whose
+/// output isn't guaranteed to stay the same across Rust releases.
+fn fnv1a(data: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    data.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
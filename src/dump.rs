@@ -0,0 +1,59 @@
+//! Hex dumps of raw page contents.
+
+use std::io::Write;
+
+/// Layout of a hex dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DumpOptions {
+    /// Bytes shown per row
+    pub width: usize,
+    /// Show the printable ASCII characters of each row after the hex bytes
+    pub ascii: bool,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            width: 16,
+            ascii: true,
+        }
+    }
+}
+
+/// Write `data` as rows of hex bytes, each prefixed with its offset in hex.
+///
+/// Bytes are grouped in eights. With the ASCII gutter enabled, each row
+/// ends with its printable characters between `|`, and `.` for the rest.
+pub fn write_hex_dump<W: Write>(
+    writer: &mut W,
+    data: &[u8],
+    options: &DumpOptions,
+) -> std::io::Result<()> {
+    let width = options.width.max(1);
+
+    for (row, bytes) in data.chunks(width).enumerate() {
+        let mut line = format!("{:08x} ", row * width);
+
+        for i in 0..width {
+            if i % 8 == 0 {
+                line.push(' ');
+            }
+            match bytes.get(i) {
+                Some(byte) => line.push_str(&format!("{:02x} ", byte)),
+                None => line.push_str("   "),
+            }
+        }
+
+        if options.ascii {
+            let gutter: String = bytes
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            line.push_str(&format!(" |{}|", gutter));
+        }
+
+        writeln!(writer, "{}", line.trim_end())?;
+    }
+
+    Ok(())
+}
@@ -14,25 +14,52 @@ pub enum WalValidatorError {
     WalNotFound(PathBuf),
 
     // Database Format Errors
+    #[error("Database file is empty: {}", .0.display())]
+    EmptyDatabase(PathBuf),
+
+    #[error("Database image is empty")]
+    EmptyDatabaseImage,
+
+    #[error("Database file is too short to hold a header: {size} bytes (expected at least 100)")]
+    TruncatedDatabaseHeader { size: u64 },
+
     #[error("Invalid SQLite database header magic bytes")]
     InvalidDbMagic,
 
     #[error("Invalid database page size: {0}")]
     InvalidPageSize(u32),
 
+    #[error("Invalid page hash file {}: {reason}", .path.display())]
+    InvalidHashFile { path: PathBuf, reason: String },
+
+    #[error("Invalid expected schema file {} at line {line}: {reason}", .path.display())]
+    InvalidSchemaFile { path: PathBuf, line: usize, reason: String },
+
     // WAL Format Errors
     #[error("Invalid WAL header magic: expected 0x377f0682 or 0x377f0683, got {0:#x}")]
     InvalidWalMagic(u32),
 
+    #[error("Unsupported WAL format version {0} (expected 3007000)")]
+    UnsupportedWalVersion(u32),
+
+    #[error("Invalid -shm WAL-index header: {0}")]
+    InvalidShmHeader(String),
+
     #[error("Page size mismatch: database says {db_size}, WAL says {wal_size}")]
     PageSizeMismatch { db_size: u32, wal_size: u32 },
 
     #[error("WAL checksum mismatch at frame {frame_index}")]
     ChecksumMismatch { frame_index: u64 },
 
+    #[error("WAL frame {frame_index} checksum validates under the opposite endianness")]
+    OppositeEndianChecksum { frame_index: u64 },
+
     #[error("Incomplete commit at end of WAL (started at frame {start_frame})")]
     IncompleteCommit { start_frame: u64 },
 
+    #[error("Commit {commit_index} not found; the WAL holds {commit_count} commits")]
+    CommitNotFound { commit_index: u64, commit_count: u64 },
+
     // B-tree Errors
     #[error("Invalid B-tree page type: {0:#x} at page {1}")]
     InvalidPageType(u8, u32),
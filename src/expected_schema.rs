@@ -0,0 +1,125 @@
+//! Expected schema files for `--expect-schema`.
+//!
+//! Each non-blank line names one table or index, optionally followed by the
+//! SQL that should define it:
+//!
+//! ```text
+//! # comment
+//! table users
+//! index idx_users_email CREATE UNIQUE INDEX idx_users_email ON users (email)
+//! table "order items"
+//! ```
+//!
+//! Names containing spaces are written in double quotes, with `""` for a
+//! literal quote.
+
+use std::path::Path;
+
+use crate::btree::BTreeInfo;
+use crate::error::{Result, WalValidatorError};
+use crate::sql;
+
+/// A table or index listed in an expected schema file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedObject {
+    /// True for a table, false for an index
+    pub is_table: bool,
+    /// Object name
+    pub name: String,
+    /// SQL the object should be defined with, if given
+    pub sql: Option<String>,
+}
+
+impl ExpectedObject {
+    /// "table" or "index"
+    pub fn object_type(&self) -> &'static str {
+        if self.is_table { "table" } else { "index" }
+    }
+
+    /// Returns true if `btree` is this object
+    pub fn matches(&self, btree: &BTreeInfo) -> bool {
+        btree.is_table == self.is_table
+            && btree
+                .name
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(&self.name))
+    }
+
+    /// Returns true if `actual` defines the object the same way as the
+    /// expected SQL, ignoring whitespace, comments and keyword case
+    pub fn sql_matches(&self, actual: Option<&str>) -> bool {
+        let Some(expected) = &self.sql else {
+            return true;
+        };
+        let normalize = |sql: &str| {
+            sql::tokenize(sql)
+                .into_iter()
+                .map(|token| match token {
+                    sql::Token::Word(word) => sql::Token::Word(word.to_ascii_uppercase()),
+                    other => other,
+                })
+                .collect::<Vec<_>>()
+        };
+        actual.is_some_and(|actual| normalize(actual) == normalize(expected))
+    }
+}
+
+/// Read an expected schema file
+pub fn load_expected_schema(path: &Path) -> Result<Vec<ExpectedObject>> {
+    let text = std::fs::read_to_string(path)?;
+    let mut objects = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let invalid = |reason: &str| WalValidatorError::InvalidSchemaFile {
+            path: path.to_path_buf(),
+            line: index + 1,
+            reason: reason.to_string(),
+        };
+
+        let (object_type, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let is_table = match object_type {
+            "table" => true,
+            "index" => false,
+            _ => return Err(invalid("expected \"table\" or \"index\"")),
+        };
+
+        let (name, sql) = split_name(rest.trim_start()).ok_or_else(|| invalid("missing name"))?;
+        let sql = sql.trim();
+        objects.push(ExpectedObject {
+            is_table,
+            name,
+            sql: (!sql.is_empty()).then(|| sql.to_string()),
+        });
+    }
+
+    Ok(objects)
+}
+
+/// Split a possibly quoted name off the start of `text`
+fn split_name(text: &str) -> Option<(String, &str)> {
+    let Some(quoted) = text.strip_prefix('"') else {
+        let end = text.find(char::is_whitespace).unwrap_or(text.len());
+        return (end > 0).then(|| (text[..end].to_string(), &text[end..]));
+    };
+
+    let mut name = String::new();
+    let mut chars = quoted.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c == '"' {
+            if chars.peek().is_some_and(|&(_, next)| next == '"') {
+                chars.next();
+            } else {
+                return Some((name, &quoted[i + 1..]));
+            }
+        }
+        name.push(c);
+    }
+
+    // Unterminated quote
+    None
+}
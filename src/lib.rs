@@ -1,22 +1,198 @@
 pub mod btree;
 pub mod db;
+pub mod dump;
 pub mod error;
+pub mod expected_schema;
+pub mod recover;
 pub mod report;
+pub mod schema;
+pub mod sql;
 pub mod validator;
 pub mod validators;
 
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{Cursor, Read, Seek};
 use std::path::Path;
+use std::time::{Duration, Instant};
 
-use crate::db::DbHeader;
+use serde::Serialize;
+
+use crate::btree::{BTreeClassification, BTreeInfo, FillFactor};
+use crate::db::{DbHeader, PageHashes, PageReader};
 use crate::error::{Result, WalValidatorError};
+use crate::schema::SchemaCache;
 use crate::validator::PageCache;
 use crate::validators::{
-    enabled_validators, ValidationContext, ValidationIssue, ValidatorConfig,
+    default_validators, enabled_validators, select_validators, IssueLocation, Severity,
+    ValidationContext, ValidationIssue, Validator, ValidatorConfig,
 };
-use crate::wal::CommitIterator;
+use crate::wal::{Commit, CommitIterator, Frame, ShmHeader, WalHeader};
 
 pub mod wal;
 
+#[cfg(feature = "parallel")]
+mod parallel;
+
+#[cfg(feature = "parallel")]
+pub use parallel::validate_parallel;
+
+/// Size of the database header at the start of page 1
+const DB_HEADER_SIZE: u64 = 100;
+
+/// Size of the smallest (leaf) B-tree page header
+const MIN_BTREE_HEADER_SIZE: u64 = 8;
+
+/// Outcome of a validation run.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationSummary {
+    /// Issues found by the validators
+    pub issues: Vec<ValidationIssue>,
+    /// Number of WAL commits processed
+    pub total_commits: u64,
+    /// Frames that rewrote a page with byte-identical content
+    /// (only counted when `detect_noop_frames` or `skip_noop_commits` is enabled)
+    pub noop_frames: u64,
+    /// Commits made up entirely of such frames, leaving the database unchanged
+    /// (only counted when `detect_noop_frames` or `skip_noop_commits` is enabled)
+    pub noop_commits: u64,
+    /// Page count declared by the base database header (None in WAL-only mode)
+    pub base_page_count: Option<u32>,
+    /// Database size declared by the final WAL commit (None if the WAL has no commits)
+    pub final_db_size: Option<u32>,
+    /// Header of the WAL (None if the WAL holds no header)
+    pub wal_header: Option<WalHeader>,
+    /// Frames up to and including the commit frame of the final WAL commit
+    pub committed_frames: u64,
+    /// Like `committed_frames`, but counting commits past `max_frame` that
+    /// were not applied, so a WAL-index's mxFrame can be compared against
+    /// the whole log
+    pub wal_committed_frames: u64,
+    /// Objects examined versus skipped in the final database state
+    /// (None if the schema couldn't be read)
+    pub coverage: Option<Coverage>,
+    /// Leaf page fill of each B-tree in the final database state
+    /// (only measured when `stats` is enabled)
+    pub fill_factors: Vec<FillFactor>,
+    /// Wall time spent in each validator across all states, slowest first
+    /// (only measured when `time_validators` is enabled)
+    pub validator_timings: Vec<(&'static str, Duration)>,
+    /// Position of each WAL commit in the file, in commit order
+    /// (only recorded when `commit_positions` is enabled)
+    pub commit_positions: Vec<CommitPosition>,
+}
+
+impl ValidationSummary {
+    /// Count a commit read from the WAL.
+    fn record_commit(&mut self, commit: &Commit, config: &ValidatorConfig) {
+        self.total_commits += 1;
+        self.final_db_size = Some(commit.db_size);
+        if let Some(last) = commit.frames.last() {
+            self.committed_frames = last.frame_index + 1;
+            self.wal_committed_frames = self.committed_frames;
+        }
+        if config.commit_positions {
+            self.commit_positions.push(CommitPosition::of(commit));
+        }
+    }
+
+    /// Add time spent in a validator when timing is enabled.
+    fn record_time(
+        &mut self,
+        config: &ValidatorConfig,
+        validator: &'static str,
+        started: Instant,
+    ) {
+        if config.time_validators {
+            self.add_time(validator, started.elapsed());
+        }
+    }
+
+    /// Add time spent in a validator to its total.
+    fn add_time(&mut self, validator: &'static str, elapsed: Duration) {
+        match self.validator_timings.iter_mut().find(|(name, _)| *name == validator) {
+            Some((_, total)) => *total += elapsed,
+            None => self.validator_timings.push((validator, elapsed)),
+        }
+    }
+}
+
+/// Where a commit sits in the WAL file.
+///
+/// WAL frames carry no timestamps, so the byte range is the closest proxy for
+/// when a commit happened relative to the rest of the session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CommitPosition {
+    /// Commit index
+    pub commit_index: u64,
+    /// Index of the commit's first frame
+    pub first_frame: u64,
+    /// Index of the commit's final (commit) frame
+    pub last_frame: u64,
+    /// Byte offset in the WAL file where the first frame starts
+    pub start_offset: u64,
+    /// Byte offset in the WAL file just past the final frame
+    pub end_offset: u64,
+}
+
+impl CommitPosition {
+    /// Position of a commit read from the WAL
+    pub fn of(commit: &Commit) -> Self {
+        CommitPosition {
+            commit_index: commit.index,
+            first_frame: commit.frames.first().map_or(0, |f| f.frame_index),
+            last_frame: commit.frames.last().map_or(0, |f| f.frame_index),
+            start_offset: commit.start_offset,
+            end_offset: commit.end_offset,
+        }
+    }
+}
+
+/// How many tables and indexes were examined versus skipped.
+///
+/// Counted from the schema of the final database state only. A table or
+/// index that existed in an earlier state (the base database or an earlier
+/// commit) and was dropped later is not counted, even though it was checked
+/// in the states that held it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Coverage {
+    /// Tables checked
+    pub tables: u64,
+    /// Indexes checked
+    pub indexes: u64,
+    /// Partial indexes skipped by the index integrity check
+    pub skipped_partial: u64,
+    /// Expression indexes skipped by the index integrity check
+    pub skipped_expression: u64,
+    /// Indexes skipped because index checks are disabled
+    pub skipped_disabled: u64,
+}
+
+impl Coverage {
+    /// Count checked and skipped objects from discovered B-trees.
+    pub fn from_btrees(btrees: &[BTreeInfo], check_indexes: bool) -> Self {
+        let mut coverage = Coverage::default();
+        for btree in btrees {
+            if !btree.is_table && !check_indexes {
+                coverage.skipped_disabled += 1;
+                continue;
+            }
+            match btree.classification() {
+                BTreeClassification::Checked if btree.is_table => coverage.tables += 1,
+                BTreeClassification::Checked => coverage.indexes += 1,
+                BTreeClassification::SkippedPartial => coverage.skipped_partial += 1,
+                BTreeClassification::SkippedExpression => coverage.skipped_expression += 1,
+            }
+        }
+        coverage
+    }
+
+    /// Total number of skipped objects.
+    pub fn skipped(&self) -> u64 {
+        self.skipped_partial + self.skipped_expression + self.skipped_disabled
+    }
+}
+
 /// Validate a SQLite database and WAL file.
 ///
 /// Runs all enabled validators against the base database state and each
@@ -30,12 +206,73 @@ pub mod wal;
 ///
 /// # Returns
 ///
-/// A tuple of (issues found, total commits processed).
+/// A summary holding the issues found and the number of commits processed.
 pub fn validate(
     db_path: &Path,
     wal_path: &Path,
     config: &ValidatorConfig,
-) -> Result<(Vec<ValidationIssue>, u64)> {
+) -> Result<ValidationSummary> {
+    validate_with_validators(db_path, wal_path, config, default_validators())
+}
+
+/// Validate a SQLite database and its WAL file with a custom set of validators.
+///
+/// Behaves like [`validate`], but runs the given validators instead of the
+/// built-in ones: at the base state and after every commit, selected by the
+/// configuration and ordered by their dependencies in the same way. Start
+/// from [`default_validators`] to run custom checks alongside the built-ins.
+///
+/// # Arguments
+///
+/// * `db_path` - Path to the SQLite database file
+/// * `wal_path` - Path to the WAL file
+/// * `config` - Validator configuration
+/// * `validators` - Validators to choose from
+pub fn validate_with_validators(
+    db_path: &Path,
+    wal_path: &Path,
+    config: &ValidatorConfig,
+    validators: Vec<Box<dyn Validator>>,
+) -> Result<ValidationSummary> {
+    let (page_reader, commits) = open_files(db_path, wal_path)?;
+    collect_issues(|on_issue| validate_pages(page_reader, commits, config, validators, on_issue))
+}
+
+/// Validate a SQLite database and WAL file, passing each issue to `on_issue`
+/// as soon as it is found.
+///
+/// Unlike [`validate`], issues are not kept once the callback returns, so
+/// memory use doesn't grow with the number of issues and output can start
+/// before the whole WAL has been read.
+///
+/// # Arguments
+///
+/// * `db_path` - Path to the SQLite database file
+/// * `wal_path` - Path to the WAL file
+/// * `config` - Validator configuration
+/// * `on_issue` - Called with each issue, in the order they are found
+///
+/// # Returns
+///
+/// A summary of the run, whose issues are left empty since each was already
+/// passed to `on_issue`.
+pub fn validate_streaming(
+    db_path: &Path,
+    wal_path: &Path,
+    config: &ValidatorConfig,
+    mut on_issue: impl FnMut(&ValidationIssue),
+) -> Result<ValidationSummary> {
+    let (page_reader, commits) = open_files(db_path, wal_path)?;
+    let validators = default_validators();
+    validate_pages(page_reader, commits, config, validators, &mut |issue| on_issue(&issue))
+}
+
+/// Open a database and its WAL for validation, reporting missing, empty and
+/// truncated files by path
+fn open_files(
+    db_path: &Path,
+    wal_path: &Path,
+) -> Result<(PageReader, Option<CommitIterator<File>>)> {
     // Verify files exist
     if !db_path.exists() {
         return Err(WalValidatorError::DatabaseNotFound(db_path.to_path_buf()));
@@ -44,52 +281,774 @@ pub fn validate(
         return Err(WalValidatorError::WalNotFound(wal_path.to_path_buf()));
     }
 
-    // Parse database header
     let db_header = DbHeader::from_file(db_path)?;
+    let page_reader = PageReader::open(db_path, db_header.page_size, db_header.page_count)?;
+
+    Ok((page_reader, CommitIterator::from_reader(File::open(wal_path)?)?))
+}
+
+/// Run a validation that streams its issues, collecting them into the summary
+fn collect_issues(
+    run: impl FnOnce(&mut dyn FnMut(ValidationIssue)) -> Result<ValidationSummary>,
+) -> Result<ValidationSummary> {
+    let mut issues = Vec::new();
+    let mut summary = run(&mut |issue| issues.push(issue))?;
+    summary.issues = issues;
+    Ok(summary)
+}
+
+/// Validate a database and WAL read from any seekable sources.
+///
+/// Behaves like [`validate`] for bytes that don't live in files, such as
+/// buffers received over the network, without copying them to disk first.
+///
+/// # Arguments
+///
+/// * `db` - Contents of the SQLite database file
+/// * `wal` - Contents of the WAL file
+/// * `config` - Validator configuration
+pub fn validate_readers<D, W>(db: D, wal: W, config: &ValidatorConfig) -> Result<ValidationSummary>
+where
+    D: Read + Seek + Send + 'static,
+    W: Read + Seek,
+{
+    collect_issues(|on_issue| validate_sources(db, wal, config, default_validators(), on_issue))
+}
+
+/// Run a set of validators over a database and WAL read from seekable sources
+fn validate_sources<D, W>(
+    mut db: D,
+    wal: W,
+    config: &ValidatorConfig,
+    validators: Vec<Box<dyn Validator>>,
+    on_issue: &mut dyn FnMut(ValidationIssue),
+) -> Result<ValidationSummary>
+where
+    D: Read + Seek + Send + 'static,
+    W: Read + Seek,
+{
+    let db_header = DbHeader::from_reader(&mut db)?;
+    let page_reader = PageReader::from_reader(db, db_header.page_size, db_header.page_count)?;
+    let commits = CommitIterator::from_reader(wal)?;
+
+    validate_pages(page_reader, commits, config, validators, on_issue)
+}
+
+/// Validate an in-memory database image and WAL.
+///
+/// Behaves like [`validate`] without touching the filesystem, which makes it
+/// the simplest entry point for tests and language bindings.
+///
+/// # Arguments
+///
+/// * `db` - Contents of the SQLite database file
+/// * `wal` - Contents of the WAL file
+/// * `config` - Validator configuration
+pub fn validate_bytes(
+    db: &[u8],
+    wal: &[u8],
+    config: &ValidatorConfig,
+) -> Result<ValidationSummary> {
+    let db_header = DbHeader::from_bytes(db)?;
+    let page_reader =
+        PageReader::from_bytes(db.to_vec(), db_header.page_size, db_header.page_count);
+
+    let commits = CommitIterator::from_reader(Cursor::new(wal))?;
+    collect_issues(|on_issue| {
+        validate_pages(page_reader, commits, config, default_validators(), on_issue)
+    })
+}
+
+/// Run the enabled validators of a set over a base database and the commits
+/// of its WAL.
+///
+/// Issues are passed to `on_issue` as they are found rather than kept in the
+/// returned summary.
+fn validate_pages<R: Read + Seek>(
+    page_reader: PageReader,
+    commits: Option<CommitIterator<R>>,
+    config: &ValidatorConfig,
+    validators: Vec<Box<dyn Validator>>,
+    on_issue: &mut dyn FnMut(ValidationIssue),
+) -> Result<ValidationSummary> {
+    let db_page_size = page_reader.page_size();
+    let db_file_size = page_reader.file_size();
 
     // Initialize page cache
-    let mut page_cache = PageCache::new(db_path, db_header.page_size, db_header.page_count);
+    let mut page_cache = PageCache::from_reader(page_reader);
 
     // Get enabled validators
-    let mut validators = enabled_validators(config);
+    let mut validators = select_validators(validators, config);
+
+    let mut summary = ValidationSummary {
+        base_page_count: Some(page_cache.base_page_count()),
+        ..ValidationSummary::default()
+    };
+
+    // Page 1 must hold a B-tree header after the 100-byte database header;
+    // without one the base state would be parsed from absent bytes
+    let page_one_truncated = db_file_size < DB_HEADER_SIZE + MIN_BTREE_HEADER_SIZE;
+    if page_one_truncated && config.since_commit.is_none() {
+        on_issue(ValidationIssue::new(
+            "database-header",
+            Severity::Error,
+            "page 1 truncated — no B-tree header",
+            IssueLocation::Page { page_number: 1 },
+            None,
+        ));
+    }
+
+    // Check base database state first (unless it is trusted or unreadable)
+    let mut schema_cache = SchemaCache::new();
+    if config.validates_base() && !page_one_truncated {
+        let ctx = ValidationContext::new(&mut page_cache, None, config)
+            .with_schema_cache(&mut schema_cache);
+        run_validators(&mut validators, ctx, &mut summary, on_issue)?;
+    }
+
+    // Iterate through WAL commits
+    if let Some(commit_iter) = commits {
+        // Verify page sizes match
+        if commit_iter.wal_header().page_size != db_page_size {
+            return Err(WalValidatorError::PageSizeMismatch {
+                db_size: db_page_size,
+                wal_size: commit_iter.wal_header().page_size,
+            });
+        }
+        summary.wal_header = Some(commit_iter.wal_header().clone());
+
+        let mut commit_iter = limit_to_max_frame(commit_iter, config, on_issue);
+        for commit_result in commit_iter.by_ref() {
+            let commit = match commit_result {
+                Ok(commit) => commit,
+                Err(WalValidatorError::OppositeEndianChecksum { frame_index }) => {
+                    let last_commit = summary.total_commits.checked_sub(1);
+                    on_issue(opposite_endian_issue(frame_index, last_commit));
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+            summary.record_commit(&commit, config);
 
-    let mut all_issues = Vec::new();
-    let mut total_commits = 0u64;
+            // Apply commit to page cache
+            let noop_commit = apply_commit(&mut page_cache, &commit, config, &mut summary)?;
+
+            // Commits up to --since-commit only build up the page state
+            if !config.validates_commit(commit.index) {
+                continue;
+            }
+            if noop_commit && config.skip_noop_commits {
+                on_issue(noop_commit_issue(commit.index));
+                continue;
+            }
+
+            // Run all validators
+            let ctx = ValidationContext::new(&mut page_cache, Some(&commit), config)
+                .with_schema_cache(&mut schema_cache);
+            run_validators(&mut validators, ctx, &mut summary, on_issue)?;
+        }
+        incomplete_tail_issue(commit_iter.incomplete_tail()).into_iter().for_each(&mut *on_issue);
+        count_frames_past_max_frame(&mut commit_iter, &mut summary);
+        noop_frames_issue(&summary, config).into_iter().for_each(on_issue);
+    }
+
+    summary.coverage = final_coverage(&mut page_cache, config);
+    summary.fill_factors = final_fill_factors(&mut page_cache, config);
+    summary
+        .validator_timings
+        .sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+    Ok(summary)
+}
+
+/// Run validators over one state, timing each when the config asks to
+fn run_validators(
+    validators: &mut [Box<dyn Validator>],
+    mut ctx: ValidationContext,
+    summary: &mut ValidationSummary,
+    on_issue: &mut dyn FnMut(ValidationIssue),
+) -> Result<()> {
+    for validator in validators {
+        let started = Instant::now();
+        validator.validate_streaming(&mut ctx, on_issue)?;
+        summary.record_time(ctx.config, validator.name(), started);
+    }
+    Ok(())
+}
+
+/// Stop the commit iterator at `config.max_frame`, noting how many frames
+/// the WAL holds past it.
+fn limit_to_max_frame<R: Read + Seek>(
+    commit_iter: CommitIterator<R>,
+    config: &ValidatorConfig,
+    on_issue: &mut dyn FnMut(ValidationIssue),
+) -> CommitIterator<R> {
+    let Some(max_frame) = config.max_frame else {
+        return commit_iter;
+    };
+
+    let beyond = commit_iter.frames_in_file().saturating_sub(max_frame);
+    if beyond > 0 {
+        on_issue(ValidationIssue::new(
+            "shm-max-frame",
+            Severity::Info,
+            format!(
+                "{} frame(s) present in WAL beyond the mxFrame ({}) of the WAL-index; \
+                 they were not applied",
+                beyond, max_frame
+            ),
+            IssueLocation::Database,
+            None,
+        ));
+    }
+    commit_iter.with_max_frame(max_frame)
+}
 
-    // Check base database state first
+/// Read the commits past `config.max_frame` without applying them, counting
+/// their frames in `wal_committed_frames`. Does nothing unless iteration
+/// stopped at the limit.
+fn count_frames_past_max_frame<R: Read + Seek>(
+    commit_iter: &mut CommitIterator<R>,
+    summary: &mut ValidationSummary,
+) {
+    commit_iter.lift_max_frame();
+    for commit in commit_iter.map_while(Result::ok) {
+        if let Some(last) = commit.frames.last() {
+            summary.wal_committed_frames = last.frame_index + 1;
+        }
+    }
+}
+
+/// Apply a commit to the page cache, counting frames that rewrite a page
+/// with identical content when no-op detection is enabled.
+///
+/// Returns true if the commit changed nothing: every frame was a no-op and
+/// the database size stayed the same. This happens when stale frames of a
+/// checkpointed WAL are replayed over a base that already holds them.
+fn apply_commit(
+    page_cache: &mut PageCache,
+    commit: &Commit,
+    config: &ValidatorConfig,
+    summary: &mut ValidationSummary,
+) -> Result<bool> {
+    if !config.detect_noop_frames && !config.skip_noop_commits {
+        page_cache.apply_commit(commit);
+        return Ok(false);
+    }
+    let previous_size = page_cache.effective_page_count();
+    let noop_frames = page_cache.apply_commit_counting_noops(commit)?;
+    summary.noop_frames += noop_frames;
+
+    let noop_commit = noop_frames == commit.frames.len() as u64 && commit.db_size == previous_size;
+    if noop_commit {
+        summary.noop_commits += 1;
+    }
+    Ok(noop_commit)
+}
+
+/// Note for the frames that rewrote a page with identical content, once
+/// every commit has been applied
+fn noop_frames_issue(
+    summary: &ValidationSummary,
+    config: &ValidatorConfig,
+) -> Option<ValidationIssue> {
+    if !config.detect_noop_frames || summary.noop_frames == 0 {
+        return None;
+    }
+
+    let mut message = format!(
+        "{} frame(s) rewrote pages with identical content",
+        summary.noop_frames
+    );
+    if summary.noop_commits > 0 {
+        message += &format!("; {} commit(s) left the database unchanged", summary.noop_commits);
+    }
+    Some(ValidationIssue::new(
+        "noop-frame",
+        Severity::Info,
+        message,
+        IssueLocation::Database,
+        None,
+    ))
+}
+
+/// Note for a commit whose validation was skipped because it changed nothing
+fn noop_commit_issue(commit_index: u64) -> ValidationIssue {
+    ValidationIssue::new(
+        "noop-commit",
+        Severity::Info,
+        "every frame rewrites its page with identical content; validators skipped",
+        IssueLocation::Database,
+        Some(commit_index),
+    )
+}
+
+/// Note for frames after the last commit frame, which belong to a
+/// transaction that never committed and so were not applied
+fn incomplete_tail_issue(tail: &[Frame]) -> Option<ValidationIssue> {
+    let first = tail.first()?;
+    Some(ValidationIssue::new(
+        "incomplete-commit",
+        Severity::Info,
+        format!(
+            "{} frame(s) in an uncommitted transaction starting at frame {} were ignored",
+            tail.len(),
+            first.frame_index
+        ),
+        IssueLocation::Database,
+        None,
+    ))
+}
+
+/// Warning for a frame whose checksum only validates under the other byte
+/// order. Frames from that point on can't be trusted, so iteration stops.
+///
+/// Like other issues, it is reported against the last commit applied (None
+/// before the first), the state in which it was found.
+fn opposite_endian_issue(frame_index: u64, last_commit: Option<u64>) -> ValidationIssue {
+    ValidationIssue::new(
+        "wal-checksum",
+        Severity::Warning,
+        format!(
+            "frame {} checksum validates under opposite endianness — WAL magic may be wrong",
+            frame_index
+        ),
+        IssueLocation::Database,
+        last_commit,
+    )
+}
+
+/// Coverage of the schema in the page cache's current state.
+fn final_coverage(page_cache: &mut PageCache, config: &ValidatorConfig) -> Option<Coverage> {
+    let check_indexes = !config.with_implied_settings().skip_indexes;
+    let mut scanner = btree::BTreeScanner::new(page_cache);
+    scanner
+        .discover_btrees()
+        .ok()
+        .map(|btrees| Coverage::from_btrees(&btrees, check_indexes))
+}
+
+/// Fill factors of the B-trees in the page cache's current state.
+fn final_fill_factors(page_cache: &mut PageCache, config: &ValidatorConfig) -> Vec<FillFactor> {
+    if !config.stats {
+        return Vec::new();
+    }
+
+    let mut scanner = btree::BTreeScanner::new(page_cache);
+    let Ok(btrees) = scanner.discover_btrees() else {
+        return Vec::new();
+    };
+    btrees
+        .iter()
+        .filter_map(|btree| scanner.fill_factor(btree).ok())
+        .collect()
+}
+
+/// Discover the tables and indexes of the base database.
+///
+/// Only the database file is read; the WAL is not applied.
+pub fn discover_schema(db_path: &Path) -> Result<Vec<BTreeInfo>> {
+    if !db_path.exists() {
+        return Err(WalValidatorError::DatabaseNotFound(db_path.to_path_buf()));
+    }
+
+    let db_header = DbHeader::from_file(db_path)?;
+    let mut page_cache = PageCache::new(db_path, db_header.page_size, db_header.page_count);
+    let mut scanner = btree::BTreeScanner::new(&mut page_cache);
+    scanner.discover_btrees()
+}
+
+/// Compare the base database against page hashes saved by a previous run.
+///
+/// Every base page whose content changed since the hashes were saved, and
+/// that no WAL frame wrote in either run, is reported as an error. The
+/// current hashes are then written to `hash_path` for the next run. If
+/// `hash_path` doesn't exist yet it is only created.
+///
+/// # Arguments
+///
+/// * `db_path` - Path to the SQLite database file
+/// * `wal_path` - Path to the WAL file
+/// * `hash_path` - Path to the saved page hashes
+pub fn check_page_hashes(
+    db_path: &Path,
+    wal_path: &Path,
+    hash_path: &Path,
+) -> Result<Vec<ValidationIssue>> {
+    let db_header = DbHeader::from_file(db_path)?;
+    let reader = PageReader::new(db_path, db_header.page_size, db_header.page_count);
+
+    // Pages the WAL currently holds may reach the database at any checkpoint
+    let mut wal_pages = BTreeSet::new();
+    if wal_path.exists()
+        && let Some(commit_iter) = CommitIterator::new(wal_path)?
     {
-        let mut ctx = ValidationContext::new(&mut page_cache, None, config);
-        for validator in &mut validators {
-            let issues = validator.validate(&mut ctx)?;
-            all_issues.extend(issues);
+        for commit in commit_iter {
+            let commit = match commit {
+                Ok(commit) => commit,
+                Err(WalValidatorError::OppositeEndianChecksum { .. }) => break,
+                Err(e) => return Err(e),
+            };
+            wal_pages.extend(commit.frames.iter().map(|f| f.header.page_number));
+        }
+    }
+
+    let current = PageHashes::compute(&reader, wal_pages)?;
+    let mut issues = Vec::new();
+
+    if let Some(previous) = PageHashes::load(hash_path)? {
+        if previous.page_size != current.page_size {
+            issues.push(ValidationIssue::new(
+                "page-hash",
+                Severity::Warning,
+                format!(
+                    "Saved hashes use page size {}, database uses {}; comparison skipped",
+                    previous.page_size, current.page_size
+                ),
+                IssueLocation::Database,
+                None,
+            ));
+        } else {
+            for page_number in current.silent_changes(&previous) {
+                issues.push(ValidationIssue::new(
+                    "page-hash",
+                    Severity::Error,
+                    "Base page changed since the last run without a WAL frame rewriting it",
+                    IssueLocation::Page { page_number },
+                    None,
+                ));
+            }
+        }
+    }
+
+    current.save(hash_path)?;
+    Ok(issues)
+}
+
+/// Cross-check a `-shm` WAL-index against the WAL it indexes.
+///
+/// A WAL-index left behind by a connection that crashed or closed is often
+/// stale without anything being wrong, so every discrepancy is reported as a
+/// warning: a header that can't be read or used, a checksum that doesn't
+/// match, a header for another WAL generation, and an mxFrame or database
+/// size that disagrees with the committed frames of the WAL.
+///
+/// The WAL is not read again: the WAL-index is compared against the header
+/// and committed frames seen by the validation run that produced `summary`,
+/// including any past the `max_frame` that run stopped at.
+///
+/// # Arguments
+///
+/// * `shm_path` - Path to the `-shm` file
+/// * `summary` - Summary of a validation run over the WAL
+pub fn check_shm(shm_path: &Path, summary: &ValidationSummary) -> Vec<ValidationIssue> {
+    let warning = |message: String| {
+        ValidationIssue::new("shm", Severity::Warning, message, IssueLocation::Database, None)
+    };
+
+    let shm = match ShmHeader::from_file(shm_path) {
+        Ok(shm) => shm,
+        Err(WalValidatorError::InvalidShmHeader(reason)) => {
+            return vec![warning(format!("WAL-index header is unusable: {}", reason))];
+        }
+        Err(e) => return vec![warning(format!("WAL-index can't be read: {}", e))],
+    };
+
+    let mut issues = Vec::new();
+    if !shm.checksum_matches {
+        issues.push(warning("WAL-index header checksum doesn't match its contents".to_string()));
+    }
+    if shm.backfilled > shm.max_frame {
+        issues.push(warning(format!(
+            "WAL-index says {} frame(s) were checkpointed, more than the {} it marks as committed",
+            shm.backfilled, shm.max_frame
+        )));
+    }
+
+    let Some(wal_header) = &summary.wal_header else {
+        if shm.max_frame > 0 {
+            issues.push(warning(format!(
+                "WAL-index marks {} frame(s) as committed, but the WAL has none",
+                shm.max_frame
+            )));
+        }
+        return issues;
+    };
+    if !shm.describes(wal_header) {
+        issues.push(warning(
+            "WAL-index describes a different WAL generation (salts differ); its frame count \
+             was not compared"
+                .to_string(),
+        ));
+        return issues;
+    }
+    if shm.page_size != wal_header.page_size {
+        issues.push(warning(format!(
+            "WAL-index page size {} differs from the WAL page size {}",
+            shm.page_size, wal_header.page_size
+        )));
+    }
+
+    if shm.max_frame as u64 != summary.wal_committed_frames {
+        issues.push(warning(format!(
+            "WAL-index marks {} frame(s) as committed, but the WAL holds {} committed frame(s)",
+            shm.max_frame, summary.wal_committed_frames
+        )));
+    } else if let Some(db_size) = summary.final_db_size
+        && shm.page_count != db_size
+    {
+        issues.push(warning(format!(
+            "WAL-index database size is {} page(s), but the last commit leaves {}",
+            shm.page_count, db_size
+        )));
+    }
+
+    issues
+}
+
+/// Compare the schema after the last WAL commit against an expected schema file.
+///
+/// Expected objects that are missing, or whose SQL differs from the expected
+/// SQL, are reported as errors. Objects that the file doesn't list are
+/// reported as warnings, except SQLite's internal `sqlite_` objects.
+///
+/// # Arguments
+///
+/// * `db_path` - Path to the SQLite database file
+/// * `wal_path` - Path to the WAL file
+/// * `schema_path` - Path to the expected schema file
+pub fn check_expected_schema(
+    db_path: &Path,
+    wal_path: &Path,
+    schema_path: &Path,
+) -> Result<Vec<ValidationIssue>> {
+    let expected = expected_schema::load_expected_schema(schema_path)?;
+    let mut page_cache = replay_wal(db_path, wal_path)?;
+    let btrees = btree::BTreeScanner::new(&mut page_cache).discover_btrees()?;
+    let mut issues = Vec::new();
+
+    let issue = |severity, message: String| {
+        ValidationIssue::new("expected-schema", severity, message, IssueLocation::Database, None)
+    };
+
+    for object in &expected {
+        match btrees.iter().find(|btree| object.matches(btree)) {
+            None => issues.push(issue(
+                Severity::Error,
+                format!("Expected {} '{}' is missing", object.object_type(), object.name),
+            )),
+            Some(btree) if !object.sql_matches(btree.sql.as_deref()) => issues.push(issue(
+                Severity::Error,
+                format!(
+                    "{} '{}' is defined as {} instead of the expected SQL",
+                    if btree.is_table { "Table" } else { "Index" },
+                    object.name,
+                    btree.sql.as_deref().unwrap_or("<no SQL>")
+                ),
+            )),
+            Some(_) => {}
         }
     }
 
-    // Open WAL and iterate through commits
+    for btree in &btrees {
+        let name = btree.name.as_deref().unwrap_or_default();
+        if name.starts_with("sqlite_") || expected.iter().any(|object| object.matches(btree)) {
+            continue;
+        }
+        issues.push(issue(
+            Severity::Warning,
+            format!(
+                "Unexpected {} '{}'",
+                if btree.is_table { "table" } else { "index" },
+                name
+            ),
+        ));
+    }
+
+    Ok(issues)
+}
+
+/// Find the first page where the WAL-reconstructed database differs from the
+/// database file.
+///
+/// After a checkpoint, replaying the WAL over the database file should
+/// reproduce the file page for page. The first page that doesn't match is
+/// where the checkpoint stopped short or where the file was corrupted.
+///
+/// # Returns
+///
+/// The first diverging page number, or None if every page matches.
+pub fn first_divergence(db_path: &Path, wal_path: &Path) -> Result<Option<u32>> {
+    if !db_path.exists() {
+        return Err(WalValidatorError::DatabaseNotFound(db_path.to_path_buf()));
+    }
+    if !wal_path.exists() {
+        return Err(WalValidatorError::WalNotFound(wal_path.to_path_buf()));
+    }
+
+    let db_header = DbHeader::from_file(db_path)?;
+    let base = PageReader::new(db_path, db_header.page_size, db_header.page_count);
+    let mut page_cache = replay_wal(db_path, wal_path)?;
+
+    let reconstructed_count = page_cache.effective_page_count();
+    let page_count = reconstructed_count.max(base.pages_in_file());
+    for page_num in 1..=page_count {
+        // A page present in only one of the two images diverges
+        if page_num > reconstructed_count {
+            return Ok(Some(page_num));
+        }
+
+        let reconstructed = page_cache.get_page_ref(page_num)?;
+        match base.page(page_num) {
+            Ok(page) if *page == *reconstructed => {}
+            Ok(_) | Err(WalValidatorError::PageNotFound { .. }) => return Ok(Some(page_num)),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Read a page as of the last WAL commit.
+///
+/// Returns the WAL's latest committed copy of the page, or the database
+/// file's copy if no commit wrote it.
+pub fn read_page(db_path: &Path, wal_path: &Path, page_num: u32) -> Result<Vec<u8>> {
+    if !db_path.exists() {
+        return Err(WalValidatorError::DatabaseNotFound(db_path.to_path_buf()));
+    }
+    if !wal_path.exists() {
+        return Err(WalValidatorError::WalNotFound(wal_path.to_path_buf()));
+    }
+
+    let mut page_cache = replay_wal(db_path, wal_path)?;
+    if page_num == 0 || page_num > page_cache.effective_page_count() {
+        return Err(WalValidatorError::PageNotFound { page_num });
+    }
+    page_cache.get_page(page_num)
+}
+
+/// Apply every WAL commit over the database file, without validating them
+fn replay_wal(db_path: &Path, wal_path: &Path) -> Result<PageCache> {
+    let db_header = DbHeader::from_file(db_path)?;
+    let mut page_cache = PageCache::new(db_path, db_header.page_size, db_header.page_count);
+
     if let Some(commit_iter) = CommitIterator::new(wal_path)? {
-        // Verify page sizes match
         if commit_iter.wal_header().page_size != db_header.page_size {
             return Err(WalValidatorError::PageSizeMismatch {
                 db_size: db_header.page_size,
                 wal_size: commit_iter.wal_header().page_size,
             });
         }
+        for commit in commit_iter {
+            let commit = match commit {
+                Ok(commit) => commit,
+                Err(WalValidatorError::OppositeEndianChecksum { .. }) => break,
+                Err(e) => return Err(e),
+            };
+            page_cache.apply_commit(&commit);
+        }
+    }
 
-        for commit_result in commit_iter {
-            let commit = commit_result?;
-            total_commits += 1;
+    Ok(page_cache)
+}
 
-            // Apply commit to page cache
-            page_cache.apply_commit(&commit);
+/// Validate a WAL file without its base database.
+///
+/// Useful for forensics when only the WAL survives. Each commit is validated
+/// against the pages written by the WAL so far; pages never written by the
+/// WAL are unavailable, so validators that need them are reported as
+/// warnings instead of failing the run.
+///
+/// # Arguments
+///
+/// * `wal_path` - Path to the WAL file
+/// * `config` - Validator configuration
+///
+/// # Returns
+///
+/// A summary holding the issues found and the number of commits processed.
+pub fn validate_wal_only(wal_path: &Path, config: &ValidatorConfig) -> Result<ValidationSummary> {
+    if !wal_path.exists() {
+        return Err(WalValidatorError::WalNotFound(wal_path.to_path_buf()));
+    }
 
-            // Run all validators
-            let mut ctx = ValidationContext::new(&mut page_cache, Some(commit.index), config);
-            for validator in &mut validators {
-                let issues = validator.validate(&mut ctx)?;
-                all_issues.extend(issues);
+    let mut summary = ValidationSummary::default();
+
+    let Some(commit_iter) = CommitIterator::new(wal_path)? else {
+        return Ok(summary);
+    };
+
+    // The WAL header is the only source of the page size
+    let mut page_cache = PageCache::wal_only(commit_iter.wal_header().page_size);
+    summary.wal_header = Some(commit_iter.wal_header().clone());
+    let mut validators = enabled_validators(config);
+
+    // Last failure reported per validator, so a missing page isn't repeated every commit
+    let mut last_failure: HashMap<&'static str, String> = HashMap::new();
+    let mut schema_cache = SchemaCache::new();
+
+    let mut commit_iter =
+        limit_to_max_frame(commit_iter, config, &mut |issue| summary.issues.push(issue));
+    for commit_result in commit_iter.by_ref() {
+        let commit = match commit_result {
+            Ok(commit) => commit,
+            Err(WalValidatorError::OppositeEndianChecksum { frame_index }) => {
+                let last_commit = summary.total_commits.checked_sub(1);
+                summary.issues.push(opposite_endian_issue(frame_index, last_commit));
+                break;
+            }
+            Err(e) => return Err(e),
+        };
+        summary.record_commit(&commit, config);
+
+        let noop_commit = apply_commit(&mut page_cache, &commit, config, &mut summary)?;
+
+        if !config.validates_commit(commit.index) {
+            continue;
+        }
+        if noop_commit && config.skip_noop_commits {
+            summary.issues.push(noop_commit_issue(commit.index));
+            continue;
+        }
+
+        let mut ctx = ValidationContext::new(&mut page_cache, Some(&commit), config)
+            .with_schema_cache(&mut schema_cache);
+        for validator in &mut validators {
+            let started = Instant::now();
+            let result = validator.validate(&mut ctx);
+            summary.record_time(config, validator.name(), started);
+            match result {
+                Ok(issues) => {
+                    last_failure.remove(validator.name());
+                    summary.issues.extend(issues);
+                }
+                Err(e) => {
+                    let message = format!("could not run on WAL-only state: {}", e);
+                    if last_failure.get(validator.name()) != Some(&message) {
+                        summary.issues.push(ValidationIssue::new(
+                            validator.name(),
+                            Severity::Warning,
+                            message.clone(),
+                            IssueLocation::Database,
+                            Some(commit.index),
+                        ));
+                        last_failure.insert(validator.name(), message);
+                    }
+                }
             }
         }
     }
+    summary
+        .issues
+        .extend(incomplete_tail_issue(commit_iter.incomplete_tail()));
+    count_frames_past_max_frame(&mut commit_iter, &mut summary);
+    let noop_frames = noop_frames_issue(&summary, config);
+    summary.issues.extend(noop_frames);
 
-    Ok((all_issues, total_commits))
+    summary.coverage = final_coverage(&mut page_cache, config);
+    summary.fill_factors = final_fill_factors(&mut page_cache, config);
+    summary
+        .validator_timings
+        .sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+    Ok(summary)
 }
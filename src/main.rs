@@ -1,11 +1,21 @@
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
+use wal_validator::btree::{KeyComparison, TextEncoding};
 use wal_validator::db::DbHeader;
-use wal_validator::report::{print_header, print_issue, print_summary};
-use wal_validator::validators::ValidatorConfig;
+use wal_validator::dump::{write_hex_dump, DumpOptions};
+use wal_validator::recover::{recover, RecoverFormat};
+use wal_validator::report::{
+    print_commit_position, print_first_divergence, print_header, print_issue, print_page_history,
+    print_recovery_report, print_repair_report, print_stream_summary, print_streamed_issue,
+    print_summary, write_csv, write_github_annotations, write_json_report, write_ndjson,
+    write_sarif,
+};
+use wal_validator::validators::{validator_names, Severity, ValidationIssue, ValidatorConfig};
+use wal_validator::wal::{page_history, repair_checksums, ShmHeader, WalHeader};
 
 #[derive(Parser, Debug)]
 #[command(name = "wal-validator")]
@@ -13,71 +23,724 @@ use wal_validator::validators::ValidatorConfig;
 #[command(version)]
 struct Cli {
     /// Path to the SQLite database file (.db)
-    #[arg(short, long)]
-    database: PathBuf,
+    #[arg(short, long, required_unless_present = "wal_only")]
+    database: Option<PathBuf>,
 
     /// Path to the WAL file (defaults to <database>-wal)
     #[arg(short, long)]
     wal: Option<PathBuf>,
+
+    /// Also list the byte range each commit occupies in the WAL file, in the
+    /// summary and the JSON report
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Skip validating the base database and only check WAL commits
+    #[arg(long)]
+    skip_base: bool,
+
+    /// Apply commits 0..=N without validating them and only report later commits
+    #[arg(long, value_name = "N")]
+    since_commit: Option<u64>,
+
+    /// Count WAL frames that rewrite a page with byte-identical content
+    #[arg(long)]
+    detect_noop_frames: bool,
+
+    /// Skip validating commits that rewrite every page they touch with identical content
+    #[arg(long)]
+    skip_noop_commits: bool,
+
+    /// Report which tables and indexes each commit modified and which changed the schema
+    #[arg(long)]
+    describe_commits: bool,
+
+    /// Show duplicate index keys as decoded column values instead of raw bytes
+    #[arg(long)]
+    decode_keys: bool,
+
+    /// Show the byte offset in the WAL file of each frame holding a duplicate
+    #[arg(long)]
+    frame_offsets: bool,
+
+    /// Show at most N locations of each duplicated key
+    #[arg(long, value_name = "N")]
+    max_locations_per_entry: Option<usize>,
+
+    /// Show at most N duplicated keys per issue, and list at most N values in messages
+    #[arg(long, value_name = "N")]
+    max_entries_per_issue: Option<usize>,
+
+    /// How index keys are compared when looking for duplicates [default: binary,
+    /// or collated with the thorough preset]
+    #[arg(long, value_enum)]
+    key_comparison: Option<KeyComparisonArg>,
+
+    /// Skip the validators that scan index B-trees
+    #[arg(long)]
+    skip_indexes: bool,
+
+    /// Check the B-tree headers of pages newly allocated by each commit
+    #[arg(long)]
+    check_new_pages: bool,
+
+    /// Warn when the file change counter doesn't increase across commits
+    #[arg(long)]
+    check_change_counter: bool,
+
+    /// Warn about non-zero reserved bytes at the end of pages
+    #[arg(long)]
+    check_reserved_bytes: bool,
+
+    /// Check that keys are in order across each table and index B-tree
+    #[arg(long)]
+    check_ordering: bool,
+
+    /// Check that rows hold no NULL in columns declared NOT NULL
+    #[arg(long)]
+    check_constraints: bool,
+
+    /// Check that foreign keys reference existing parent rows
+    #[arg(long)]
+    check_foreign_keys: bool,
+
+    /// Report rowids that reappear after an earlier commit deleted them
+    #[arg(long)]
+    check_resurrected_rows: bool,
+
+    /// Report how full each table and index's leaf pages are
+    #[arg(long)]
+    stats: bool,
+
+    /// Report the time spent in each validator
+    #[arg(long)]
+    time_validators: bool,
+
+    /// Run only this validator (repeatable)
+    #[arg(long, value_name = "NAME")]
+    only: Vec<String>,
+
+    /// Don't run this validator (repeatable)
+    #[arg(long, value_name = "NAME")]
+    skip: Vec<String>,
+
+    /// Start from a preset bundle of checks (other flags add to it)
+    #[arg(long, value_enum)]
+    preset: Option<Preset>,
+
+    /// Lowest issue severity that makes the run exit with code 2
+    #[arg(long, value_enum, default_value_t = FailOn::Error)]
+    fail_on: FailOn,
+
+    /// Print each issue as soon as it is found instead of after the whole WAL
+    /// (text output only; the summary then only counts issues and commits)
+    #[arg(long, conflicts_with_all = ["wal_only", "hash_db", "expect_schema"])]
+    stream: bool,
+
+    /// Output format for the report
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+
+    /// When to color the text report (auto: only when stdout is a terminal
+    /// and NO_COLOR isn't set)
+    #[arg(long, value_enum, default_value_t = ColorChoice::Auto)]
+    color: ColorChoice,
+
+    /// Indent the JSON report instead of writing it compactly
+    #[arg(long)]
+    json_pretty: bool,
+
+    /// Include the discovered tables and indexes in the JSON report
+    #[arg(long)]
+    include_schema: bool,
+
+    /// Compare base pages against hashes saved in this file, then update it
+    #[arg(long, value_name = "FILE", conflicts_with = "wal_only")]
+    hash_db: Option<PathBuf>,
+
+    /// Compare the final schema against the tables and indexes listed in this file
+    #[arg(long, value_name = "FILE", conflicts_with = "wal_only")]
+    expect_schema: Option<PathBuf>,
+
+    /// Also write each issue as a line of JSON to this already-open file descriptor
+    #[cfg(unix)]
+    #[arg(long, value_name = "FD", value_parser = clap::value_parser!(i32).range(0..))]
+    ndjson_fd: Option<i32>,
+
+    /// Cross-check this -shm WAL-index against the WAL (defaults to <database>-shm);
+    /// when given and usable, also replay only the frames it marks as committed
+    #[arg(long, value_name = "PATH")]
+    shm: Option<PathBuf>,
+
+    /// Inspect the WAL alone, without a base database (requires --wal)
+    #[arg(long, requires = "wal")]
+    wal_only: bool,
+
+    /// Replay the WAL and report the first page that differs from the
+    /// (already checkpointed) database file, then exit
+    #[arg(long, conflicts_with = "wal_only")]
+    first_divergence: bool,
+
+    /// List the commits and frames that wrote page N, then exit
+    #[arg(long, value_name = "N")]
+    page_history: Option<u32>,
+
+    /// Hex dump page N as of the last WAL commit, then exit
+    #[arg(long, value_name = "N", conflicts_with = "wal_only")]
+    page: Option<u32>,
+
+    /// Bytes per row of the --page hex dump
+    #[arg(long, value_name = "BYTES", default_value = "16", value_parser = ["16", "32"])]
+    dump_width: String,
+
+    /// Leave out the ASCII column of the --page hex dump
+    #[arg(long)]
+    no_ascii: bool,
+
+    /// Write the rows that can still be decoded to one file per table in DIR, then exit
+    #[arg(long, value_name = "DIR", conflicts_with = "wal_only")]
+    recover: Option<PathBuf>,
+
+    /// File format of the tables written by --recover
+    #[arg(long, value_enum, default_value_t = RecoverFormatArg::Csv)]
+    recover_format: RecoverFormatArg,
+
+    /// Write a copy of the WAL with recomputed frame checksums to this path
+    #[arg(long, value_name = "OUT")]
+    repair_checksums: Option<PathBuf>,
+
+    /// Confirm a risky recovery operation such as --repair-checksums
+    #[arg(long)]
+    i_know_what_im_doing: bool,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum KeyComparisonArg {
+    /// Compare raw record bytes
+    Binary,
+    /// Compare INTEGER and REAL columns by numeric value
+    Numeric,
+    /// Also compare text under each column's declared collation
+    Collated,
+}
+
+impl From<KeyComparisonArg> for KeyComparison {
+    fn from(arg: KeyComparisonArg) -> Self {
+        match arg {
+            KeyComparisonArg::Binary => KeyComparison::Binary,
+            KeyComparisonArg::Numeric => KeyComparison::Numeric,
+            KeyComparisonArg::Collated => KeyComparison::Collated,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum RecoverFormatArg {
+    /// CSV with a header row of column names
+    Csv,
+    /// INSERT statements
+    Sql,
+}
+
+impl From<RecoverFormatArg> for RecoverFormat {
+    fn from(arg: RecoverFormatArg) -> Self {
+        match arg {
+            RecoverFormatArg::Csv => RecoverFormat::Csv,
+            RecoverFormatArg::Sql => RecoverFormat::Sql,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// Human-readable colored report
+    Text,
+    /// A single JSON object with the issues and commit counts
+    Json,
+    /// GitHub Actions workflow commands (::error::, ::warning::, ::notice::)
+    Github,
+    /// A SARIF 2.1.0 log for GitHub code scanning
+    Sarif,
+    /// One CSV row per location of each duplicate rowid or index key
+    Csv,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ColorChoice {
+    /// Color when stdout is a terminal and NO_COLOR isn't set
+    Auto,
+    /// Always color, even when piped
+    Always,
+    /// Never color
+    Never,
+}
+
+impl ColorChoice {
+    /// Returns whether the report should be colored
+    fn should_color(self) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                // https://no-color.org: any non-empty value disables color
+                let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+                !no_color && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum FailOn {
+    /// Fail only on errors
+    Error,
+    /// Fail on warnings and errors
+    Warning,
+    /// Fail on any issue
+    Info,
+    /// Never fail because of issues
+    Never,
+}
+
+impl FailOn {
+    /// Returns true if an issue of this severity should fail the run
+    fn fails_on(self, severity: Severity) -> bool {
+        match self {
+            FailOn::Error => severity == Severity::Error,
+            FailOn::Warning => matches!(severity, Severity::Error | Severity::Warning),
+            FailOn::Info => true,
+            FailOn::Never => false,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Preset {
+    /// Duplicate rowid detection only
+    Fast,
+    /// Structural checks without the index integrity comparison
+    CorruptionOnly,
+    /// Every available check and report
+    Thorough,
+}
+
+/// Default location of a file SQLite keeps next to a database, such as
+/// `<database>-wal` for the suffix `wal`
+fn companion_path(database: &Path, suffix: &str) -> PathBuf {
+    let mut path = database.to_path_buf();
+    let filename = path
+        .file_name()
+        .map(|f| format!("{}-{}", f.to_string_lossy(), suffix))
+        .unwrap_or_else(|| format!("database-{}", suffix));
+    path.set_file_name(filename);
+    path
 }
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
+    colored::control::set_override(cli.color.should_color());
 
-    // Determine WAL path
-    let wal_path = cli.wal.unwrap_or_else(|| {
-        let mut wal = cli.database.clone();
-        let filename = wal
-            .file_name()
-            .map(|f| format!("{}-wal", f.to_string_lossy()))
-            .unwrap_or_else(|| "database-wal".to_string());
-        wal.set_file_name(filename);
-        wal
-    });
+    // Catch misspelled validator names before doing any work
+    let names = validator_names();
+    if let Some(unknown) = cli.only.iter().chain(&cli.skip).find(|n| !names.contains(&n.as_str())) {
+        eprintln!(
+            "Error: unknown validator '{}' (available: {})",
+            unknown,
+            names.join(", ")
+        );
+        return ExitCode::FAILURE;
+    }
+
+    // The base database is not used in WAL-only mode
+    let database = if cli.wal_only {
+        None
+    } else {
+        cli.database.clone()
+    };
+
+    // Determine WAL path (clap guarantees one of --wal or --database is present)
+    let wal_path = match (&cli.wal, &database) {
+        (Some(wal), _) => wal.clone(),
+        (None, Some(database)) => companion_path(database, "wal"),
+        (None, None) => unreachable!("--wal is required in WAL-only mode"),
+    };
 
     // Validate database exists
-    if !cli.database.exists() {
-        eprintln!("Error: Database file not found: {}", cli.database.display());
+    if let Some(database) = &database
+        && !database.exists()
+    {
+        eprintln!("Error: Database file not found: {}", database.display());
         return ExitCode::FAILURE;
     }
 
+    // Recovery is a standalone salvage operation, and works without a WAL
+    if let Some(out_dir) = &cli.recover
+        && let Some(database) = &database
+    {
+        return match recover(database, &wal_path, out_dir, cli.recover_format.into()) {
+            Ok(report) => {
+                print_recovery_report(&report, out_dir);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error recovering rows: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
     // Validate WAL exists
     if !wal_path.exists() {
         eprintln!("Error: WAL file not found: {}", wal_path.display());
         return ExitCode::FAILURE;
     }
 
-    // Get page size for header
-    let page_size = match DbHeader::from_file(&cli.database) {
-        Ok(header) => header.page_size,
-        Err(e) => {
-            eprintln!("Error reading database header: {}", e);
+    // Page history is a standalone forensic query
+    if let Some(page_number) = cli.page_history {
+        return match page_history(&wal_path, page_number) {
+            Ok(writes) => {
+                print_page_history(page_number, &writes);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error reading WAL commits: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // Page dumps are a standalone forensic query
+    if let Some(page_number) = cli.page
+        && let Some(database) = &database
+    {
+        let options = DumpOptions {
+            width: cli.dump_width.parse().expect("clap only accepts 16 or 32"),
+            ascii: !cli.no_ascii,
+        };
+        let dumped = wal_validator::read_page(database, &wal_path, page_number)
+            .and_then(|data| Ok(write_hex_dump(&mut std::io::stdout().lock(), &data, &options)?));
+        return match dumped {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("Error dumping page {}: {}", page_number, e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // Divergence from the checkpointed file is a standalone forensic query
+    if cli.first_divergence
+        && let Some(database) = &database
+    {
+        return match wal_validator::first_divergence(database, &wal_path) {
+            Ok(first_divergence) => {
+                print_first_divergence(first_divergence);
+                if first_divergence.is_some() {
+                    ExitCode::from(2)
+                } else {
+                    ExitCode::SUCCESS
+                }
+            }
+            Err(e) => {
+                eprintln!("Error comparing against the database file: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // Checksum repair is a standalone recovery operation
+    if let Some(output_path) = &cli.repair_checksums {
+        if !cli.i_know_what_im_doing {
+            eprintln!(
+                "Error: --repair-checksums can hide real data corruption; \
+                 pass --i-know-what-im-doing to proceed"
+            );
             return ExitCode::FAILURE;
         }
+
+        return match repair_checksums(&wal_path, output_path) {
+            Ok(report) => {
+                print_repair_report(&report, output_path);
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("Error repairing checksums: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // Get page size for header, and the text encoding to decode keys with
+    // (WAL-only mode has no database header and assumes UTF-8)
+    let (page_size, encoding) = match &database {
+        Some(database) => match DbHeader::from_file(database) {
+            Ok(header) => (header.page_size, header.encoding()),
+            Err(e) => {
+                eprintln!("Error reading database header: {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => match WalHeader::from_file(&wal_path) {
+            Ok(header) => (header.page_size, TextEncoding::default()),
+            Err(e) => {
+                eprintln!("Error reading WAL header: {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
     };
 
+    let text_output = cli.format == OutputFormat::Text;
+    let key_encoding = cli.decode_keys.then_some(encoding);
+    let wal_page_size = cli.frame_offsets.then_some(page_size);
+
     // Print header
-    print_header(&cli.database, &wal_path, page_size);
+    if text_output {
+        print_header(database.as_deref(), &wal_path, page_size);
+    }
 
     // Build validator config
-    let config = ValidatorConfig::default();
+    let mut config = match cli.preset {
+        Some(Preset::Fast) => ValidatorConfig::fast(),
+        Some(Preset::CorruptionOnly) => ValidatorConfig::corruption_only(),
+        Some(Preset::Thorough) => ValidatorConfig::thorough(),
+        None => ValidatorConfig::default(),
+    };
+    config.skip_base |= cli.skip_base;
+    config.since_commit = cli.since_commit;
+    config.detect_noop_frames |= cli.detect_noop_frames;
+    config.skip_noop_commits |= cli.skip_noop_commits;
+    config.describe_commits |= cli.describe_commits;
+    config.skip_indexes |= cli.skip_indexes;
+    config.check_new_pages |= cli.check_new_pages;
+    config.check_change_counter |= cli.check_change_counter;
+    config.check_reserved_bytes |= cli.check_reserved_bytes;
+    config.check_ordering |= cli.check_ordering;
+    config.check_constraints |= cli.check_constraints;
+    config.check_foreign_keys |= cli.check_foreign_keys;
+    config.check_resurrected_rows |= cli.check_resurrected_rows;
+    config.stats |= cli.stats;
+    config.time_validators |= cli.time_validators;
+    config.commit_positions |= cli.verbose;
+    config.only.extend(cli.only.iter().cloned());
+    config.skip.extend(cli.skip.iter().cloned());
+    config.max_locations_per_entry = cli.max_locations_per_entry;
+    config.max_entries_per_issue = cli.max_entries_per_issue;
+    // Only a usable WAL-index for this WAL generation limits the replay; a
+    // stale or damaged one is reported by the cross-check instead
+    if let Some(shm_path) = &cli.shm
+        && let Ok(shm) = ShmHeader::from_file(shm_path)
+        && shm.checksum_matches
+        && WalHeader::from_file(&wal_path).is_ok_and(|wal_header| shm.describes(&wal_header))
+    {
+        config.max_frame = Some(shm.max_frame as u64);
+    }
+    if let Some(key_comparison) = cli.key_comparison {
+        config.key_comparison = key_comparison.into();
+    }
+
+    // Without --shm, a WAL-index next to the database is only cross-checked
+    let shm_path = cli.shm.clone().or_else(|| {
+        let shm_path = companion_path(database.as_deref()?, "shm");
+        shm_path.exists().then_some(shm_path)
+    });
+
+    // --stream conflicts with --wal-only, so a database is always given
+    if cli.stream
+        && let Some(database) = &database
+    {
+        return stream_validation(
+            &cli,
+            database,
+            &wal_path,
+            shm_path.as_deref(),
+            &config,
+            key_encoding,
+            wal_page_size,
+        );
+    }
+
+    let mut ndjson = match NdjsonSink::open(&cli) {
+        Ok(ndjson) => ndjson,
+        Err(e) => {
+            eprintln!("Error opening the --ndjson-fd descriptor: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Run validation, writing NDJSON lines as issues are found
+    let result = match &database {
+        Some(database) if ndjson.is_open() => {
+            let mut issues = Vec::new();
+            let on_issue = |issue: &ValidationIssue| {
+                ndjson.write(issue);
+                issues.push(issue.clone());
+            };
+            wal_validator::validate_streaming(database, &wal_path, &config, on_issue).map(
+                |mut summary| {
+                    summary.issues = issues;
+                    summary
+                },
+            )
+        }
+        Some(database) => wal_validator::validate(database, &wal_path, &config),
+        None => wal_validator::validate_wal_only(&wal_path, &config)
+            .inspect(|summary| summary.issues.iter().for_each(|issue| ndjson.write(issue))),
+    };
 
-    // Run validation
-    match wal_validator::validate(&cli.database, &wal_path, &config) {
-        Ok((issues, total_commits)) => {
-            // Print each issue
-            for issue in &issues {
-                print_issue(issue);
+    // Cross-check the WAL-index, look for base pages that changed since the
+    // previous --hash-db run, and compare the schema against --expect-schema
+    let result = result.and_then(|mut summary| {
+        let mut issues = Vec::new();
+        if let Some(shm_path) = &shm_path {
+            issues.extend(wal_validator::check_shm(shm_path, &summary));
+        }
+        if let (Some(hash_path), Some(database)) = (&cli.hash_db, &database) {
+            issues.extend(wal_validator::check_page_hashes(database, &wal_path, hash_path)?);
+        }
+        if let (Some(schema_path), Some(database)) = (&cli.expect_schema, &database) {
+            issues.extend(wal_validator::check_expected_schema(database, &wal_path, schema_path)?);
+        }
+        issues.iter().for_each(|issue| ndjson.write(issue));
+        summary.issues.extend(issues);
+        Ok(summary)
+    });
+
+    match result {
+        Ok(summary) => {
+            if let Err(e) = ndjson.finish() {
+                eprintln!("Error writing NDJSON: {}", e);
+                return ExitCode::FAILURE;
             }
 
-            // Print summary
-            print_summary(&issues, total_commits);
+            match cli.format {
+                OutputFormat::Text => {
+                    // Print each issue
+                    for issue in &summary.issues {
+                        print_issue(issue, key_encoding, wal_page_size, &config);
+                    }
 
-            // Exit with error code if issues were found
-            if issues.is_empty() {
-                ExitCode::SUCCESS
+                    // Print summary
+                    print_summary(&summary, &config);
+                }
+                OutputFormat::Json => {
+                    // The schema comes from the base database, which WAL-only mode lacks
+                    let schema = match &database {
+                        Some(database) if cli.include_schema => {
+                            match wal_validator::discover_schema(database) {
+                                Ok(schema) => Some(schema),
+                                Err(e) => {
+                                    eprintln!("Error discovering schema: {}", e);
+                                    return ExitCode::FAILURE;
+                                }
+                            }
+                        }
+                        _ => None,
+                    };
+
+                    let mut stdout = std::io::stdout().lock();
+                    let written = write_json_report(
+                        &mut stdout,
+                        &summary,
+                        schema.as_deref(),
+                        cli.json_pretty,
+                    );
+                    if let Err(e) = written {
+                        eprintln!("Error writing JSON report: {}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+                OutputFormat::Github => {
+                    let mut stdout = std::io::stdout().lock();
+                    if let Err(e) = write_github_annotations(&mut stdout, &summary.issues) {
+                        eprintln!("Error writing annotations: {}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+                OutputFormat::Sarif => {
+                    let mut stdout = std::io::stdout().lock();
+                    let written =
+                        write_sarif(&mut stdout, &summary.issues, database.as_deref(), &wal_path);
+                    if let Err(e) = written {
+                        eprintln!("Error writing SARIF log: {}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+                OutputFormat::Csv => {
+                    let mut stdout = std::io::stdout().lock();
+                    if let Err(e) = write_csv(&mut stdout, &summary.issues) {
+                        eprintln!("Error writing CSV: {}", e);
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+
+            // Exit with error code if issues at or above --fail-on were found
+            if summary.issues.iter().any(|issue| cli.fail_on.fails_on(issue.severity)) {
+                ExitCode::from(2) // Issues found
             } else {
+                ExitCode::SUCCESS
+            }
+        }
+        Err(e) => {
+            eprintln!("Error during validation: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Validate with issues printed as they are found, for --stream
+fn stream_validation(
+    cli: &Cli,
+    database: &Path,
+    wal_path: &Path,
+    shm_path: Option<&Path>,
+    config: &ValidatorConfig,
+    key_encoding: Option<TextEncoding>,
+    wal_page_size: Option<u32>,
+) -> ExitCode {
+    if !matches!(cli.format, OutputFormat::Text) {
+        eprintln!("Error: --stream only supports the text output format");
+        return ExitCode::FAILURE;
+    }
+    let mut ndjson = match NdjsonSink::open(cli) {
+        Ok(ndjson) => ndjson,
+        Err(e) => {
+            eprintln!("Error opening the --ndjson-fd descriptor: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut total_issues = 0;
+    let mut failed = false;
+    let mut report = |issue: &ValidationIssue| {
+        total_issues += 1;
+        failed |= cli.fail_on.fails_on(issue.severity);
+        ndjson.write(issue);
+        print_streamed_issue(issue, total_issues, key_encoding, wal_page_size, config);
+    };
+    let result = wal_validator::validate_streaming(database, wal_path, config, &mut report);
+    let result = result.inspect(|summary| {
+        if let Some(shm_path) = shm_path {
+            wal_validator::check_shm(shm_path, summary).iter().for_each(&mut report);
+        }
+    });
+
+    if let Err(e) = ndjson.finish() {
+        eprintln!("Error writing NDJSON: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    match result {
+        Ok(summary) => {
+            if !summary.commit_positions.is_empty() {
+                println!("Commit positions in the WAL:");
+                summary.commit_positions.iter().for_each(print_commit_position);
+            }
+            print_stream_summary(total_issues, summary.total_commits);
+            if failed {
                 ExitCode::from(2) // Issues found
+            } else {
+                ExitCode::SUCCESS
             }
         }
         Err(e) => {
@@ -86,3 +749,49 @@ fn main() -> ExitCode {
         }
     }
 }
+
+/// Where --ndjson-fd sends each issue, as a line of JSON, as soon as it is found
+#[derive(Default)]
+struct NdjsonSink {
+    file: Option<std::fs::File>,
+    /// First write error; later issues are dropped and `finish` reports it
+    error: Option<std::io::Error>,
+}
+
+impl NdjsonSink {
+    /// Open the --ndjson-fd descriptor, or a sink that writes nothing without one
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    fn open(cli: &Cli) -> std::io::Result<Self> {
+        #[cfg(unix)]
+        if let Some(fd) = cli.ndjson_fd {
+            use std::os::fd::BorrowedFd;
+
+            // SAFETY: the descriptor is only borrowed long enough to duplicate
+            // it, which fails cleanly with EBADF if it isn't open
+            let owned = unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned()?;
+            return Ok(NdjsonSink {
+                file: Some(std::fs::File::from(owned)),
+                error: None,
+            });
+        }
+        Ok(NdjsonSink::default())
+    }
+
+    fn is_open(&self) -> bool {
+        self.file.is_some()
+    }
+
+    fn write(&mut self, issue: &ValidationIssue) {
+        if let Some(file) = &mut self.file
+            && self.error.is_none()
+            && let Err(e) = write_ndjson(file, std::slice::from_ref(issue))
+        {
+            self.error = Some(e);
+        }
+    }
+
+    /// The first error any write hit
+    fn finish(self) -> std::io::Result<()> {
+        self.error.map_or(Ok(()), Err)
+    }
+}
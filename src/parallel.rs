@@ -0,0 +1,240 @@
+//! Validation of WAL commits on several threads.
+//!
+//! Each state is the previous one plus a commit, so states can't be built
+//! independently. The commits are first applied in order on the calling
+//! thread, which copies the page cache's WAL overlay at the start of each
+//! range of commits; workers then rebuild the states of their range from
+//! that copy and validate them in parallel. Validators that compare a state
+//! with the ones before it (`Validator::compares_states`) need every state
+//! in order, so they run on the calling thread during the first pass.
+//!
+//! This trades memory for time: the whole WAL is held in memory at once,
+//! along with one overlay copy per range, each as large as the pages the WAL
+//! wrote before that range. With `threads` ranges this is up to `threads`
+//! times the overlay a sequential run keeps.
+
+use std::ops::Range;
+use std::path::Path;
+
+use rayon::prelude::*;
+
+use crate::db::{DbHeader, PageReader};
+use crate::error::{Result, WalValidatorError};
+use crate::schema::SchemaCache;
+use crate::validator::{Overlay, PageCache};
+use crate::validators::{
+    default_validators, select_validators, IssueLocation, Severity, ValidationContext,
+    ValidationIssue, Validator, ValidatorConfig,
+};
+use crate::wal::Commit;
+use crate::{
+    apply_commit, count_frames_past_max_frame, final_coverage, final_fill_factors, incomplete_tail_issue, limit_to_max_frame,
+    noop_commit_issue, noop_frames_issue, open_files, opposite_endian_issue, run_validators,
+    ValidationSummary, DB_HEADER_SIZE, MIN_BTREE_HEADER_SIZE,
+};
+
+/// Validate a SQLite database and its WAL file using several threads.
+///
+/// Finds the same issues as [`validate`](crate::validate), though not
+/// necessarily in the same order: they are sorted by the state they were
+/// found in, base database first. See the module documentation for the
+/// memory this costs.
+///
+/// # Arguments
+///
+/// * `db_path` - Path to the SQLite database file
+/// * `wal_path` - Path to the WAL file
+/// * `config` - Validator configuration
+/// * `threads` - Number of worker threads (0 for one per CPU)
+pub fn validate_parallel(
+    db_path: &Path,
+    wal_path: &Path,
+    config: &ValidatorConfig,
+    threads: usize,
+) -> Result<ValidationSummary> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(std::io::Error::other)?;
+
+    let (page_reader, commit_iter) = open_files(db_path, wal_path)?;
+    let db_page_size = page_reader.page_size();
+    let db_file_size = page_reader.file_size();
+    let mut page_cache = PageCache::from_reader(page_reader);
+    let mut sequential = validators(config, true);
+
+    let mut summary = ValidationSummary {
+        base_page_count: Some(page_cache.base_page_count()),
+        ..ValidationSummary::default()
+    };
+    let mut issues = Vec::new();
+    let mut on_issue = |issue| issues.push(issue);
+
+    // Same checks of the base state as the sequential path
+    let page_one_truncated = db_file_size < DB_HEADER_SIZE + MIN_BTREE_HEADER_SIZE;
+    if page_one_truncated && config.since_commit.is_none() {
+        on_issue(ValidationIssue::new(
+            "database-header",
+            Severity::Error,
+            "page 1 truncated — no B-tree header",
+            IssueLocation::Page { page_number: 1 },
+            None,
+        ));
+    }
+    let validate_base = config.validates_base() && !page_one_truncated;
+    let mut schema_cache = SchemaCache::new();
+    if validate_base {
+        let ctx = ValidationContext::new(&mut page_cache, None, config)
+            .with_schema_cache(&mut schema_cache);
+        run_validators(&mut sequential, ctx, &mut summary, &mut on_issue)?;
+    }
+
+    // Read every commit up front to split them into equal ranges
+    let mut commits = Vec::new();
+    if let Some(commit_iter) = commit_iter {
+        if commit_iter.wal_header().page_size != db_page_size {
+            return Err(WalValidatorError::PageSizeMismatch {
+                db_size: db_page_size,
+                wal_size: commit_iter.wal_header().page_size,
+            });
+        }
+        summary.wal_header = Some(commit_iter.wal_header().clone());
+
+        let mut commit_iter = limit_to_max_frame(commit_iter, config, &mut on_issue);
+        for commit_result in commit_iter.by_ref() {
+            match commit_result {
+                Ok(commit) => commits.push(commit),
+                Err(WalValidatorError::OppositeEndianChecksum { frame_index }) => {
+                    let last_commit = commits.last().map(|commit| commit.index);
+                    on_issue(opposite_endian_issue(frame_index, last_commit));
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        incomplete_tail_issue(commit_iter.incomplete_tail()).into_iter().for_each(&mut on_issue);
+        count_frames_past_max_frame(&mut commit_iter, &mut summary);
+    }
+
+    // Apply the commits in order, copying the overlay where each range starts
+    let range_len = commits.len().div_ceil(pool.current_num_threads()).max(1);
+    let mut starts = vec![Overlay::default()];
+    let mut validated = Vec::with_capacity(commits.len());
+    for (i, commit) in commits.iter().enumerate() {
+        if i > 0 && i % range_len == 0 {
+            starts.push(page_cache.overlay());
+        }
+        summary.record_commit(commit, config);
+
+        let noop_commit = apply_commit(&mut page_cache, commit, config, &mut summary)?;
+        let validates = if !config.validates_commit(commit.index) {
+            false
+        } else if noop_commit && config.skip_noop_commits {
+            on_issue(noop_commit_issue(commit.index));
+            false
+        } else {
+            true
+        };
+        if validates {
+            let ctx = ValidationContext::new(&mut page_cache, Some(commit), config)
+                .with_schema_cache(&mut schema_cache);
+            run_validators(&mut sequential, ctx, &mut summary, &mut on_issue)?;
+        }
+        validated.push(validates);
+    }
+    noop_frames_issue(&summary, config).into_iter().for_each(&mut on_issue);
+
+    let ranges: Vec<(Overlay, Range<usize>)> = starts
+        .into_iter()
+        .enumerate()
+        .map(|(i, start)| {
+            let first = i * range_len;
+            (start, first..commits.len().min(first + range_len))
+        })
+        .collect();
+    let results: Vec<Result<(Vec<ValidationIssue>, ValidationSummary)>> = pool.install(|| {
+        ranges
+            .into_par_iter()
+            .map(|(start, range)| {
+                let base = validate_base && range.start == 0;
+                let states = RangeStates {
+                    start,
+                    base,
+                    commits: &commits[range.clone()],
+                    validated: &validated[range],
+                };
+                validate_range(db_path, config, states)
+            })
+            .collect()
+    });
+    for result in results {
+        let (range_issues, range_summary) = result?;
+        issues.extend(range_issues);
+        for (validator, elapsed) in range_summary.validator_timings {
+            summary.add_time(validator, elapsed);
+        }
+    }
+
+    issues.sort_by_key(|issue| issue.commit_index);
+    summary.issues = issues;
+    summary.coverage = final_coverage(&mut page_cache, config);
+    summary.fill_factors = final_fill_factors(&mut page_cache, config);
+    summary
+        .validator_timings
+        .sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+    Ok(summary)
+}
+
+/// The states one worker validates
+struct RangeStates<'a> {
+    /// WAL overlay before the first commit
+    start: Overlay,
+    /// Whether the base database state is validated too
+    base: bool,
+    /// The commits, in order
+    commits: &'a [Commit],
+    /// Whether the state after each commit is validated
+    validated: &'a [bool],
+}
+
+/// Run the validators that don't compare states over a range of states,
+/// with a page cache of its own
+fn validate_range(
+    db_path: &Path,
+    config: &ValidatorConfig,
+    states: RangeStates,
+) -> Result<(Vec<ValidationIssue>, ValidationSummary)> {
+    let db_header = DbHeader::from_file(db_path)?;
+    let page_reader = PageReader::open(db_path, db_header.page_size, db_header.page_count)?;
+    let mut page_cache = PageCache::from_reader(page_reader).with_overlay(states.start);
+    let mut validators = validators(config, false);
+
+    let mut summary = ValidationSummary::default();
+    let mut issues = Vec::new();
+    let mut on_issue = |issue| issues.push(issue);
+    let mut schema_cache = SchemaCache::new();
+
+    if states.base {
+        let ctx = ValidationContext::new(&mut page_cache, None, config)
+            .with_schema_cache(&mut schema_cache);
+        run_validators(&mut validators, ctx, &mut summary, &mut on_issue)?;
+    }
+    for (commit, &validates) in states.commits.iter().zip(states.validated) {
+        page_cache.apply_commit(commit);
+        if validates {
+            let ctx = ValidationContext::new(&mut page_cache, Some(commit), config)
+                .with_schema_cache(&mut schema_cache);
+            run_validators(&mut validators, ctx, &mut summary, &mut on_issue)?;
+        }
+    }
+
+    Ok((issues, summary))
+}
+
+/// The enabled validators that do (or don't) compare states, in dependency order
+fn validators(config: &ValidatorConfig, compare_states: bool) -> Vec<Box<dyn Validator>> {
+    select_validators(default_validators(), config)
+        .into_iter()
+        .filter(|v| v.compares_states() == compare_states)
+        .collect()
+}
@@ -0,0 +1,414 @@
+//! Salvage rows from a damaged database.
+//!
+//! Recovery replays as much of the WAL as verifies, then walks every rowid
+//! table and writes the rows it can decode to one file per table. Pages
+//! that can't be read or parsed are skipped along with the rows under them,
+//! so a single bad page doesn't cost the rest of the table.
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::btree::{
+    parse_record, parse_varint, read_full_payload, BTreePageHeader, BTreePageType, BTreeScanner,
+    Value,
+};
+use crate::db::DbHeader;
+use crate::error::{Result, WalValidatorError};
+use crate::sql::{self, TableColumn};
+use crate::validator::PageCache;
+use crate::wal::CommitIterator;
+
+/// File format for recovered rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoverFormat {
+    /// One CSV file per table, with a header row of column names
+    Csv,
+    /// One file of INSERT statements per table
+    Sql,
+}
+
+impl RecoverFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            RecoverFormat::Csv => "csv",
+            RecoverFormat::Sql => "sql",
+        }
+    }
+}
+
+/// Outcome of recovering one table
+#[derive(Debug, Clone)]
+pub struct TableRecovery {
+    /// Table name
+    pub name: String,
+    /// File the rows were written to
+    pub path: PathBuf,
+    /// Rows written
+    pub rows: u64,
+    /// Cells that couldn't be decoded into a row
+    pub bad_cells: u64,
+    /// Pages skipped because they couldn't be read or parsed
+    pub skipped_pages: Vec<u32>,
+}
+
+/// Outcome of a recovery run
+#[derive(Debug, Clone, Default)]
+pub struct RecoveryReport {
+    /// WAL commits applied before recovering
+    pub commits_applied: u64,
+    /// Why WAL replay stopped before the end of the file, if it did
+    pub wal_error: Option<String>,
+    /// Tables recovered, in schema order
+    pub tables: Vec<TableRecovery>,
+    /// WITHOUT ROWID tables, which are not recovered
+    pub skipped_tables: Vec<String>,
+}
+
+/// Recover every rowid table of a database into `out_dir`.
+///
+/// The WAL is optional: commits are applied in order until the first one
+/// that fails to verify, and a missing WAL file recovers the database file
+/// alone. The sqlite_master table itself must be readable, since it lists
+/// the tables to recover.
+pub fn recover(
+    db_path: &Path,
+    wal_path: &Path,
+    out_dir: &Path,
+    format: RecoverFormat,
+) -> Result<RecoveryReport> {
+    if !db_path.exists() {
+        return Err(WalValidatorError::DatabaseNotFound(db_path.to_path_buf()));
+    }
+
+    let db_header = DbHeader::from_file(db_path)?;
+    let usable_size = db_header.usable_size() as usize;
+    let encoding = db_header.encoding();
+    let mut page_cache = PageCache::new(db_path, db_header.page_size, db_header.page_count);
+    let mut report = RecoveryReport::default();
+
+    if wal_path.exists() {
+        replay_verified_commits(&mut page_cache, wal_path, db_header.page_size, &mut report);
+    }
+
+    let btrees = BTreeScanner::new(&mut page_cache).discover_btrees()?;
+    fs::create_dir_all(out_dir)?;
+    let mut file_names = HashSet::new();
+
+    for btree in btrees.iter().filter(|b| b.is_table) {
+        let name = btree
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("root_{}", btree.root_page));
+        let table_sql = btree.sql.as_deref().unwrap_or_default();
+        if sql::is_without_rowid(table_sql) {
+            report.skipped_tables.push(name);
+            continue;
+        }
+        let columns = sql::parse_table_columns(table_sql).unwrap_or_default();
+
+        let path = out_dir.join(unique_file_name(&name, format, &mut file_names));
+        let mut writer = BufWriter::new(File::create(&path)?);
+        let mut table = TableRecovery {
+            name,
+            path,
+            rows: 0,
+            bad_cells: 0,
+            skipped_pages: Vec::new(),
+        };
+
+        let mut table_writer = TableWriter::new(&table.name, &columns, format);
+        for (rowid, payload) in walk_table(&mut page_cache, btree.root_page, usable_size, &mut table)
+        {
+            match parse_record(&payload, encoding) {
+                Ok(values) => {
+                    table_writer.write_row(&mut writer, rowid, values)?;
+                    table.rows += 1;
+                }
+                Err(_) => table.bad_cells += 1,
+            }
+        }
+        table_writer.finish(&mut writer)?;
+        writer.flush()?;
+
+        report.tables.push(table);
+    }
+
+    Ok(report)
+}
+
+/// Apply WAL commits until the first one that can't be read
+fn replay_verified_commits(
+    page_cache: &mut PageCache,
+    wal_path: &Path,
+    page_size: u32,
+    report: &mut RecoveryReport,
+) {
+    let commit_iter = match CommitIterator::new(wal_path) {
+        Ok(Some(commit_iter)) => commit_iter,
+        Ok(None) => return,
+        Err(e) => {
+            report.wal_error = Some(e.to_string());
+            return;
+        }
+    };
+    if commit_iter.wal_header().page_size != page_size {
+        report.wal_error = Some(
+            WalValidatorError::PageSizeMismatch {
+                db_size: page_size,
+                wal_size: commit_iter.wal_header().page_size,
+            }
+            .to_string(),
+        );
+        return;
+    }
+
+    for commit in commit_iter {
+        match commit {
+            Ok(commit) => {
+                page_cache.apply_commit(&commit);
+                report.commits_applied += 1;
+            }
+            Err(e) => {
+                report.wal_error = Some(e.to_string());
+                return;
+            }
+        }
+    }
+}
+
+/// Collect the (rowid, payload) of every readable cell in a table B-tree.
+/// Unreadable pages are recorded in `table` and skipped.
+fn walk_table(
+    page_cache: &mut PageCache,
+    root_page: u32,
+    usable_size: usize,
+    table: &mut TableRecovery,
+) -> Vec<(i64, Vec<u8>)> {
+    let mut rows = Vec::new();
+    let mut visited = HashSet::new();
+    let mut stack = vec![root_page];
+
+    while let Some(page_num) = stack.pop() {
+        // A corrupt child pointer can form a cycle
+        if !visited.insert(page_num) {
+            continue;
+        }
+
+        // Bytes reserved at the end of the page hold no cells
+        let page = page_cache.get_page_ref(page_num).and_then(|page_data| {
+            let page_data = &page_data[..usable_size.min(page_data.len())];
+            let (header, _) = BTreePageHeader::parse(page_data, page_num)?;
+            let cell_pointers = header.get_cell_pointers(page_data, page_num)?;
+            Ok((page_data.to_vec(), header, cell_pointers))
+        });
+        let Ok((page_data, header, cell_pointers)) = page else {
+            table.skipped_pages.push(page_num);
+            continue;
+        };
+
+        match header.page_type {
+            BTreePageType::TableLeaf => {
+                for &cell_ptr in &cell_pointers {
+                    let cell = page_data.get(cell_ptr as usize..).unwrap_or_default();
+                    match read_table_cell(page_cache, cell, usable_size) {
+                        Ok(row) => rows.push(row),
+                        Err(_) => table.bad_cells += 1,
+                    }
+                }
+            }
+            BTreePageType::TableInterior => {
+                // Push children right to left so rows come out in rowid order
+                if let Some(right_child) = header.right_child {
+                    stack.push(right_child);
+                }
+                for &cell_ptr in cell_pointers.iter().rev() {
+                    let cell_offset = cell_ptr as usize;
+                    if let Some(child) = page_data.get(cell_offset..cell_offset + 4) {
+                        stack.push(BigEndian::read_u32(child));
+                    }
+                }
+            }
+            _ => table.skipped_pages.push(page_num),
+        }
+    }
+
+    table.skipped_pages.sort_unstable();
+    rows
+}
+
+/// Read the rowid and full payload of a table leaf cell, following its
+/// overflow chain
+fn read_table_cell(
+    page_cache: &mut PageCache,
+    cell: &[u8],
+    usable_size: usize,
+) -> Result<(i64, Vec<u8>)> {
+    let (payload_size, size_len) = parse_varint(cell)?;
+    let (rowid, rowid_len) = parse_varint(cell.get(size_len..).unwrap_or_default())?;
+    let payload_size = payload_size as usize;
+    let start = size_len + rowid_len;
+
+    let local = BTreePageType::TableLeaf.local_payload_size(payload_size, usable_size);
+    let cell_data = cell.get(start..).ok_or(WalValidatorError::UnexpectedEof)?;
+    let payload = read_full_payload(page_cache, cell_data, local, payload_size)?;
+
+    Ok((rowid as i64, payload))
+}
+
+/// File name for a table's rows, made safe for the file system and unique
+/// among the names handed out so far
+fn unique_file_name(name: &str, format: RecoverFormat, taken: &mut HashSet<String>) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+
+    let mut file_name = format!("{}.{}", stem, format.extension());
+    let mut suffix = 2;
+    while !taken.insert(file_name.to_ascii_lowercase()) {
+        file_name = format!("{}_{}.{}", stem, suffix, format.extension());
+        suffix += 1;
+    }
+    file_name
+}
+
+/// Writes the rows of one table in the chosen format
+struct TableWriter {
+    table: String,
+    columns: Vec<TableColumn>,
+    format: RecoverFormat,
+    header_written: bool,
+}
+
+impl TableWriter {
+    fn new(table: &str, columns: &[TableColumn], format: RecoverFormat) -> Self {
+        TableWriter {
+            table: table.to_string(),
+            columns: columns.to_vec(),
+            format,
+            header_written: false,
+        }
+    }
+
+    /// Column names for a record with `count` values. The rowid comes first
+    /// unless a column aliases it; values beyond the declared columns (the
+    /// schema couldn't be parsed) get positional names.
+    fn column_names(&self, count: usize) -> Vec<String> {
+        let mut names = Vec::new();
+        if !self.columns.iter().any(|c| c.is_rowid_alias) {
+            names.push("rowid".to_string());
+        }
+        for i in 0..count.max(self.columns.len()) {
+            match self.columns.get(i) {
+                Some(column) => names.push(column.name.clone()),
+                None => names.push(format!("column{}", i + 1)),
+            }
+        }
+        names
+    }
+
+    fn write_row<W: Write>(
+        &mut self,
+        writer: &mut W,
+        rowid: i64,
+        mut values: Vec<Value>,
+    ) -> std::io::Result<()> {
+        // Columns added by ALTER TABLE after the row was written are absent
+        // from it; their default values are not tracked, so they come out NULL
+        values.resize(values.len().max(self.columns.len()), Value::Null);
+
+        let names = self.column_names(values.len());
+
+        // The record holds NULL in place of a rowid alias
+        for (value, column) in values.iter_mut().zip(&self.columns) {
+            if column.is_rowid_alias && *value == Value::Null {
+                *value = Value::Int(rowid);
+            }
+        }
+        if !self.columns.iter().any(|c| c.is_rowid_alias) {
+            values.insert(0, Value::Int(rowid));
+        }
+
+        match self.format {
+            RecoverFormat::Csv => {
+                if !self.header_written {
+                    self.write_csv_header(writer, &names)?;
+                }
+                let fields: Vec<String> = values.iter().map(csv_value).collect();
+                writeln!(writer, "{}", fields.join(","))
+            }
+            RecoverFormat::Sql => {
+                let names: Vec<String> = names.iter().map(|n| sql_identifier(n)).collect();
+                let literals: Vec<String> = values.iter().map(sql_literal).collect();
+                writeln!(
+                    writer,
+                    "INSERT INTO {}({}) VALUES ({});",
+                    sql_identifier(&self.table),
+                    names.join(", "),
+                    literals.join(", ")
+                )
+            }
+        }
+    }
+
+    /// Finish the table. A CSV file gets its header even when no rows were
+    /// recovered, named after the declared columns.
+    fn finish<W: Write>(&mut self, writer: &mut W) -> std::io::Result<()> {
+        if self.format == RecoverFormat::Csv && !self.header_written {
+            let names = self.column_names(0);
+            self.write_csv_header(writer, &names)?;
+        }
+        Ok(())
+    }
+
+    fn write_csv_header<W: Write>(
+        &mut self,
+        writer: &mut W,
+        names: &[String],
+    ) -> std::io::Result<()> {
+        let header: Vec<String> = names.iter().map(|n| csv_field(n)).collect();
+        writeln!(writer, "{}", header.join(","))?;
+        self.header_written = true;
+        Ok(())
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+pub(crate) fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// A value as a CSV field: NULL is empty and blobs are hex
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Int(n) => n.to_string(),
+        Value::Float(f) => format!("{:?}", f),
+        Value::Text(text) => csv_field(text),
+        Value::Blob(data) => data.iter().map(|b| format!("{:02x}", b)).collect(),
+    }
+}
+
+/// Quote an identifier for SQL
+fn sql_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// A value as a SQL literal
+fn sql_literal(value: &Value) -> String {
+    match value {
+        Value::Float(f) if f.is_nan() => "NULL".to_string(),
+        Value::Float(f) if f.is_infinite() => {
+            if *f > 0.0 { "1e999" } else { "-1e999" }.to_string()
+        }
+        _ => value.to_string(),
+    }
+}
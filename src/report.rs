@@ -1,24 +1,60 @@
 use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::Path;
 
-use crate::btree::{IndexKey, RowidLocation};
+use crate::btree::{
+    BTreeClassification, BTreeInfo, FillFactor, IndexKey, RowidLocation, TextEncoding,
+};
+use crate::recover::{csv_field, RecoveryReport};
 use crate::validators::{
-    DuplicateDetails, DuplicateEntry, IssueLocation, Severity, ValidationIssue,
+    DuplicateDetails, DuplicateEntry, IssueLocation, Severity, ValidationIssue, ValidatorConfig,
 };
+use crate::wal::{frame_offset, PageWrite, RepairReport};
+use crate::{CommitPosition, Coverage, ValidationSummary};
 
 /// Print the report header.
-pub fn print_header(db_path: &Path, wal_path: &Path, page_size: u32) {
+pub fn print_header(db_path: Option<&Path>, wal_path: &Path, page_size: u32) {
     println!("{}", "=".repeat(80));
     println!("{}", "SQLite WAL Validator Report".bold());
     println!("{}", "=".repeat(80));
-    println!("Database: {}", db_path.display());
+    match db_path {
+        Some(db_path) => println!("Database: {}", db_path.display()),
+        None => println!("Database: (none, WAL-only mode)"),
+    }
     println!("WAL File: {}", wal_path.display());
     println!("Page Size: {} bytes", page_size);
     println!();
 }
 
+/// Print the position a commit occupies in the WAL file, as a line of the
+/// summary's commit position list.
+pub fn print_commit_position(position: &CommitPosition) {
+    println!(
+        "  Commit #{}: frames {}-{}, bytes {:#x}-{:#x}",
+        position.commit_index,
+        position.first_frame,
+        position.last_frame,
+        position.start_offset,
+        position.end_offset
+    );
+}
+
 /// Print a validation issue.
-pub fn print_issue(issue: &ValidationIssue) {
+///
+/// With a `key_encoding`, duplicate index keys are shown as their decoded
+/// column values, reading text in that encoding, rather than raw record
+/// bytes. With a `wal_page_size`, each duplicate location written by the WAL
+/// also shows the byte offset of its frame in a WAL of that page size, for
+/// cross-referencing in a hex editor. Duplicate details are truncated to the config's
+/// `max_entries_per_issue` and `max_locations_per_entry`.
+pub fn print_issue(
+    issue: &ValidationIssue,
+    key_encoding: Option<TextEncoding>,
+    wal_page_size: Option<u32>,
+    config: &ValidatorConfig,
+) {
     println!("{}", "-".repeat(80));
 
     let location_str = match issue.commit_index {
@@ -61,23 +97,58 @@ pub fn print_issue(issue: &ValidationIssue) {
     if let Some(details) = &issue.duplicate_details {
         match details {
             DuplicateDetails::Rowid(dups) => {
-                for dup in dups {
-                    print_rowid_duplicate(dup);
+                let (shown, omitted) = config.truncate_entries(dups);
+                for dup in shown {
+                    print_rowid_duplicate(dup, wal_page_size, config);
                 }
+                print_omitted_entries(omitted);
             }
             DuplicateDetails::IndexKey(dups) => {
-                for dup in dups {
-                    print_key_duplicate(dup);
+                let (shown, omitted) = config.truncate_entries(dups);
+                for dup in shown {
+                    print_key_duplicate(dup, key_encoding, wal_page_size, config);
                 }
+                print_omitted_entries(omitted);
             }
         }
     }
 }
 
-fn format_location(loc: &RowidLocation, is_intra_page_last: bool) -> String {
-    let frame_str = match loc.frame_index {
-        Some(idx) => format!(" (frame {})", idx),
-        None => " (base db)".to_string(),
+fn print_omitted_entries(omitted: usize) {
+    if omitted > 0 {
+        println!("  ... and {} more duplicated key(s)", omitted);
+        println!();
+    }
+}
+
+fn print_locations<K>(
+    dup: &DuplicateEntry<K>,
+    wal_page_size: Option<u32>,
+    config: &ValidatorConfig,
+) {
+    let (shown, omitted) = config.truncate_locations(&dup.locations);
+    for loc in shown {
+        let is_last = loc == dup.locations.last().unwrap();
+        let is_intra_page_last = dup.is_intra_page() && is_last;
+        println!("{}", format_location(loc, is_intra_page_last, wal_page_size));
+    }
+    if omitted > 0 {
+        println!("    ... and {} more location(s)", omitted);
+    }
+    println!();
+}
+
+fn format_location(
+    loc: &RowidLocation,
+    is_intra_page_last: bool,
+    wal_page_size: Option<u32>,
+) -> String {
+    let frame_str = match (loc.frame_index, wal_page_size) {
+        (Some(idx), Some(page_size)) => {
+            format!(" (frame {} @ byte {:#x})", idx, frame_offset(idx, page_size))
+        }
+        (Some(idx), None) => format!(" (frame {})", idx),
+        (None, _) => " (base db)".to_string(),
     };
     let intra_page = if is_intra_page_last {
         "  [Intra-page]".yellow().to_string()
@@ -90,39 +161,217 @@ fn format_location(loc: &RowidLocation, is_intra_page_last: bool) -> String {
     )
 }
 
-fn print_rowid_duplicate(dup: &DuplicateEntry<i64>) {
+fn print_rowid_duplicate(
+    dup: &DuplicateEntry<i64>,
+    wal_page_size: Option<u32>,
+    config: &ValidatorConfig,
+) {
     println!("  Rowid {}:", format!("{}", dup.key).green());
+    print_locations(dup, wal_page_size, config);
+}
 
-    for loc in &dup.locations {
-        let is_last = loc == dup.locations.last().unwrap();
-        let is_intra_page_last = dup.is_intra_page() && is_last;
-        println!("{}", format_location(loc, is_intra_page_last));
+fn print_key_duplicate(
+    dup: &DuplicateEntry<IndexKey>,
+    key_encoding: Option<TextEncoding>,
+    wal_page_size: Option<u32>,
+    config: &ValidatorConfig,
+) {
+    let key = match key_encoding {
+        Some(encoding) => dup.key.display(encoding).to_string(),
+        None => dup.key.to_string(),
+    };
+    let rowids: Vec<String> = dup.rowids.iter().flatten().map(|r| r.to_string()).collect();
+    if rowids.len() == dup.locations.len() {
+        let mut distinct = dup.rowids.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        let same_row = if distinct.len() == 1 { " (the same row indexed twice)" } else { "" };
+        let (shown, omitted) = config.truncate_locations(&rowids);
+        let mut rowids = shown.join(", ");
+        if omitted > 0 {
+            rowids.push_str(&format!(" ... and {} more", omitted));
+        }
+        println!("  Key {} maps to rowids {}{}:", key.green(), rowids, same_row);
+    } else {
+        println!("  Key {}:", key.green());
     }
-    println!();
+    print_locations(dup, wal_page_size, config);
 }
 
-fn print_key_duplicate(dup: &DuplicateEntry<IndexKey>) {
-    println!("  Key {}:", format!("{}", dup.key).green());
+/// Print the summary footer.
+pub fn print_summary(summary: &ValidationSummary, config: &ValidatorConfig) {
+    println!("{}", "=".repeat(80));
 
-    for loc in &dup.locations {
-        let is_last = loc == dup.locations.last().unwrap();
-        let is_intra_page_last = dup.is_intra_page() && is_last;
-        println!("{}", format_location(loc, is_intra_page_last));
+    let counts = ReportSummary::from_issues(&summary.issues);
+    if counts.total_issues == 0 {
+        println!(
+            "{}",
+            "No issues found - database appears valid!".green().bold()
+        );
+    } else {
+        print_issue_counts(&counts);
     }
-    println!();
-}
 
-/// Print the summary footer.
-pub fn print_summary(issues: &[ValidationIssue], total_commits: u64) {
+    if !summary.commit_positions.is_empty() {
+        println!("Commit positions in the WAL:");
+        summary.commit_positions.iter().for_each(print_commit_position);
+    }
+
+    if let Some(coverage) = &summary.coverage {
+        print_coverage(coverage);
+    }
+
+    if !summary.fill_factors.is_empty() {
+        println!("Leaf page fill factor:");
+        for fill in &summary.fill_factors {
+            println!(
+                "  {} (root page {}): {:.1}% across {} page(s)",
+                fill.name.as_deref().unwrap_or("<unknown>"),
+                fill.root_page,
+                fill.ratio() * 100.0,
+                fill.leaf_pages
+            );
+        }
+    }
+
+    if !summary.validator_timings.is_empty() {
+        let breakdown: Vec<String> = summary
+            .validator_timings
+            .iter()
+            .map(|(name, duration)| format!("{}: {:.3}s", name, duration.as_secs_f64()))
+            .collect();
+        println!("Validator time: {}", breakdown.join(", "));
+    }
+
+    match (summary.base_page_count, summary.final_db_size) {
+        (Some(base), Some(last)) => println!(
+            "Database size: {} page(s) in base header, {} after final commit",
+            base, last
+        ),
+        (Some(base), None) => println!("Database size: {} page(s)", base),
+        (None, Some(last)) => println!("Database size: {} page(s) after final commit", last),
+        (None, None) => {}
+    }
+
+    if let Some(since) = config.since_commit {
+        println!("Commits 0-{} applied without validation", since);
+    } else if config.skip_base {
+        println!("Base database validation skipped");
+    }
+    println!("Total commits processed: {}", summary.total_commits);
     println!("{}", "=".repeat(80));
+}
+
+/// Print the issue counts of a run that found issues
+fn print_issue_counts(counts: &ReportSummary) {
+    println!(
+        "{}: {} issue(s) found",
+        "Summary".bold(),
+        counts.total_issues.to_string().red()
+    );
+
+    if counts.base_issues > 0 {
+        println!("  - {} in base database", counts.base_issues);
+    }
+    if counts.wal_issues > 0 {
+        println!("  - {} in WAL commits", counts.wal_issues);
+    }
 
-    let total_issues = issues.len();
-    let base_issues = issues
+    let severities: Vec<String> = counts
+        .by_severity
         .iter()
-        .filter(|i| i.commit_index.is_none())
-        .count();
-    let wal_issues = total_issues - base_issues;
+        .map(|(severity, count)| format!("{} {}", count, severity_label(*severity)))
+        .collect();
+    println!("By severity: {}", severities.join(", "));
+    let validators: Vec<String> = counts
+        .by_validator
+        .iter()
+        .map(|(validator, count)| format!("{}: {}", validator, count))
+        .collect();
+    println!("By validator: {}", validators.join(", "));
+    if counts.duplicate_entries > 0 {
+        println!("Duplicated rowids and keys: {}", counts.duplicate_entries);
+    }
+}
+
+/// Plural label of a severity for counts
+fn severity_label(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error(s)",
+        Severity::Warning => "warning(s)",
+        Severity::Info => "info",
+    }
+}
 
+/// Issue counts of a run, for triage.
+///
+/// Rendered by `print_summary` and included in the JSON report; library
+/// users can build it from any list of issues.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReportSummary {
+    /// Number of issues
+    pub total_issues: usize,
+    /// Issues found in the base database
+    pub base_issues: usize,
+    /// Issues found after a WAL commit
+    pub wal_issues: usize,
+    /// Issues per validator name
+    pub by_validator: BTreeMap<&'static str, usize>,
+    /// Issues per severity, most severe first
+    #[serde(serialize_with = "serialize_severity_counts")]
+    pub by_severity: Vec<(Severity, usize)>,
+    /// Duplicated rowids and index keys across all duplicate issues
+    pub duplicate_entries: usize,
+}
+
+impl ReportSummary {
+    /// Count a list of issues.
+    pub fn from_issues(issues: &[ValidationIssue]) -> Self {
+        let mut summary = ReportSummary {
+            total_issues: issues.len(),
+            ..ReportSummary::default()
+        };
+        for issue in issues {
+            if issue.commit_index.is_none() {
+                summary.base_issues += 1;
+            } else {
+                summary.wal_issues += 1;
+            }
+            *summary.by_validator.entry(issue.validator).or_default() += 1;
+            match summary.by_severity.iter_mut().find(|(s, _)| *s == issue.severity) {
+                Some((_, count)) => *count += 1,
+                None => summary.by_severity.push((issue.severity, 1)),
+            }
+            summary.duplicate_entries += issue.duplicate_count();
+        }
+        summary.by_severity.sort_by_key(|(severity, _)| severity.rank());
+        summary
+    }
+}
+
+/// Serialize severity counts as a map from severity to count
+fn serialize_severity_counts<S: serde::Serializer>(
+    counts: &[(Severity, usize)],
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_map(counts.iter().map(|(severity, count)| (severity, count)))
+}
+
+/// Print an issue as soon as it is found, followed by the running count.
+pub fn print_streamed_issue(
+    issue: &ValidationIssue,
+    issues_so_far: u64,
+    key_encoding: Option<TextEncoding>,
+    wal_page_size: Option<u32>,
+    config: &ValidatorConfig,
+) {
+    print_issue(issue, key_encoding, wal_page_size, config);
+    println!("{}", format!("({} issue(s) so far)", issues_so_far).dimmed());
+}
+
+/// Print the summary of a streamed validation, whose issues were already printed.
+pub fn print_stream_summary(total_issues: u64, total_commits: u64) {
+    println!("{}", "=".repeat(80));
     if total_issues == 0 {
         println!(
             "{}",
@@ -134,15 +383,493 @@ pub fn print_summary(issues: &[ValidationIssue], total_commits: u64) {
             "Summary".bold(),
             total_issues.to_string().red()
         );
+    }
+    println!("Total commits processed: {}", total_commits);
+    println!("{}", "=".repeat(80));
+}
+
+/// Print how many objects were checked and skipped, in the final schema.
+fn print_coverage(coverage: &Coverage) {
+    let mut line = format!(
+        "Checked {} table(s), {} index(es) in the final schema",
+        coverage.tables, coverage.indexes
+    );
+    if coverage.skipped() > 0 {
+        let reasons: Vec<String> = [
+            (coverage.skipped_partial, "partial"),
+            (coverage.skipped_expression, "expression"),
+            (coverage.skipped_disabled, "disabled by --skip-indexes"),
+        ]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, reason)| format!("{} {}", count, reason))
+        .collect();
+        line.push_str(&format!(
+            "; skipped {} index(es) ({})",
+            coverage.skipped(),
+            reasons.join(", ")
+        ));
+    }
+    println!("{}", line);
+}
+
+/// Print where the WAL-reconstructed database first differs from the file.
+pub fn print_first_divergence(first_divergence: Option<u32>) {
+    match first_divergence {
+        Some(page_number) => println!(
+            "{}: reconstructed database first differs from the database file at page {}",
+            "DIVERGENCE".red().bold(),
+            page_number
+        ),
+        None => println!(
+            "{}",
+            "Reconstructed database matches the database file".green().bold()
+        ),
+    }
+}
+
+/// Print every commit that wrote a page.
+pub fn print_page_history(page_number: u32, writes: &[PageWrite]) {
+    if writes.is_empty() {
+        println!("Page {} is not written by any WAL commit", page_number);
+        return;
+    }
+
+    let mut commits: Vec<String> = writes
+        .iter()
+        .map(|w| format!("#{}", w.commit_index))
+        .collect();
+    commits.dedup();
+    println!("Page {} written by commits {}", page_number, commits.join(", "));
+
+    for write in writes {
+        let kind = if write.is_commit_frame { " (commit frame)" } else { "" };
+        println!(
+            "  commit #{}: frame {}{}",
+            write.commit_index, write.frame_index, kind
+        );
+    }
+}
+
+/// Print the outcome of a checksum repair pass.
+pub fn print_repair_report(report: &RepairReport, output_path: &Path) {
+    println!("{}", "=".repeat(80));
+    println!("{}", "WAL Checksum Repair".bold());
+    println!("{}", "=".repeat(80));
+    println!("Output WAL: {}", output_path.display());
+    println!("Frames written: {}", report.frames_written);
+
+    if report.header_repaired {
+        println!("{}", "WAL header checksum was recomputed".yellow());
+    }
+
+    if report.repaired_frames.is_empty() {
+        println!("{}", "No frame checksums needed repair".green().bold());
+    } else {
+        println!(
+            "Repaired {} frame checksum(s): {:?}",
+            report.repaired_frames.len().to_string().yellow(),
+            report.repaired_frames
+        );
+    }
+    println!("{}", "=".repeat(80));
+}
+
+/// Print the outcome of a recovery run.
+pub fn print_recovery_report(report: &RecoveryReport, out_dir: &Path) {
+    println!("{}", "=".repeat(80));
+    println!("{}", "Row Recovery".bold());
+    println!("{}", "=".repeat(80));
+    println!("Output directory: {}", out_dir.display());
+    println!("WAL commits applied: {}", report.commits_applied);
+    if let Some(wal_error) = &report.wal_error {
+        println!("{}: {}", "WAL replay stopped early".yellow(), wal_error);
+    }
 
-        if base_issues > 0 {
-            println!("  - {} in base database", base_issues);
+    for table in &report.tables {
+        let mut line = format!("  {}: {} row(s)", table.name, table.rows);
+        if table.bad_cells > 0 {
+            line.push_str(&format!(", {} undecodable cell(s)", table.bad_cells));
         }
-        if wal_issues > 0 {
-            println!("  - {} in WAL commits", wal_issues);
+        if !table.skipped_pages.is_empty() {
+            line.push_str(&format!(", skipped page(s) {:?}", table.skipped_pages));
         }
+        let damaged = table.bad_cells > 0 || !table.skipped_pages.is_empty();
+        println!("{}", if damaged { line.yellow() } else { line.normal() });
+    }
+    for name in &report.skipped_tables {
+        println!("  {}: {}", name, "skipped (WITHOUT ROWID)".yellow());
     }
-
-    println!("Total commits processed: {}", total_commits);
     println!("{}", "=".repeat(80));
 }
+
+/// A discovered table or index, as listed in the JSON report.
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaEntry {
+    /// Object name
+    pub name: Option<String>,
+    /// "table" or "index"
+    #[serde(rename = "type")]
+    pub object_type: &'static str,
+    /// Root page number
+    pub root_page: u32,
+    /// Table the object belongs to
+    pub tbl_name: Option<String>,
+    /// True for unique indexes
+    pub is_unique: bool,
+    /// Whether the index integrity check covers this object
+    pub classification: BTreeClassification,
+}
+
+impl From<&BTreeInfo> for SchemaEntry {
+    fn from(btree: &BTreeInfo) -> Self {
+        Self {
+            name: btree.name.clone(),
+            object_type: if btree.is_table { "table" } else { "index" },
+            root_page: btree.root_page,
+            tbl_name: btree.tbl_name.clone(),
+            is_unique: btree.is_unique,
+            classification: btree.classification(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    total_commits: u64,
+    noop_frames: u64,
+    noop_commits: u64,
+    base_page_count: Option<u32>,
+    final_db_size: Option<u32>,
+    coverage: Option<Coverage>,
+    summary: ReportSummary,
+    issues: &'a [ValidationIssue],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    fill_factors: &'a [FillFactor],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    commit_positions: &'a [CommitPosition],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schema: Option<Vec<SchemaEntry>>,
+}
+
+/// Write the validation results as a single JSON object.
+///
+/// When `schema` is given, the discovered objects are listed alongside the
+/// issues so consumers can correlate them without re-parsing the database.
+/// The object is compact unless `pretty` asks for 2-space indentation.
+pub fn write_json_report<W: Write>(
+    writer: &mut W,
+    summary: &ValidationSummary,
+    schema: Option<&[BTreeInfo]>,
+    pretty: bool,
+) -> std::io::Result<()> {
+    let report = JsonReport {
+        total_commits: summary.total_commits,
+        noop_frames: summary.noop_frames,
+        noop_commits: summary.noop_commits,
+        base_page_count: summary.base_page_count,
+        final_db_size: summary.final_db_size,
+        coverage: summary.coverage,
+        summary: ReportSummary::from_issues(&summary.issues),
+        issues: &summary.issues,
+        fill_factors: &summary.fill_factors,
+        commit_positions: &summary.commit_positions,
+        schema: schema.map(|btrees| btrees.iter().map(SchemaEntry::from).collect()),
+    };
+    if pretty {
+        serde_json::to_writer_pretty(&mut *writer, &report)?;
+    } else {
+        serde_json::to_writer(&mut *writer, &report)?;
+    }
+    writeln!(writer)
+}
+
+/// Write a list of issues as a JSON array, without the rest of the report.
+pub fn write_json_issues<W: Write>(
+    writer: &mut W,
+    issues: &[ValidationIssue],
+) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, issues)?;
+    writeln!(writer)
+}
+
+/// Print a list of issues to stdout as a JSON array (see `write_json_issues`).
+pub fn print_json(issues: &[ValidationIssue]) -> std::io::Result<()> {
+    write_json_issues(&mut std::io::stdout().lock(), issues)
+}
+
+/// Write each issue as a compact JSON object on its own line (NDJSON).
+pub fn write_ndjson<W: Write>(writer: &mut W, issues: &[ValidationIssue]) -> std::io::Result<()> {
+    for issue in issues {
+        serde_json::to_writer(&mut *writer, issue)?;
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+/// Write each issue as a GitHub Actions workflow command.
+///
+/// Errors, warnings and info map to `::error::`, `::warning::` and
+/// `::notice::`, so issues show up as annotations in the job log.
+pub fn write_github_annotations<W: Write>(
+    writer: &mut W,
+    issues: &[ValidationIssue],
+) -> std::io::Result<()> {
+    for issue in issues {
+        let command = match issue.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "notice",
+        };
+        let state = match issue.commit_index {
+            Some(idx) => format!("commit #{}", idx),
+            None => "base database".to_string(),
+        };
+        let message = format!(
+            "[{}] {}, {}: {}",
+            issue.validator, state, issue.location, issue.message
+        );
+        writeln!(writer, "::{}::{}", command, escape_workflow_data(&message))?;
+    }
+    Ok(())
+}
+
+/// Escape a workflow command message so it stays on one line
+fn escape_workflow_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Columns of the `write_csv` output
+const CSV_HEADER: &str = "validator,table_or_index,root_page,key_or_rowid,page_number,\
+                          cell_index,frame_index,commit_index";
+
+/// Write every location of every duplicate as a CSV row.
+///
+/// Issues without duplicate details are left out. Rowids are written as
+/// integers and index keys as the hex of their record bytes; a location in
+/// the base database has an empty frame index, and an issue in the base
+/// database an empty commit index.
+pub fn write_csv<W: Write>(writer: &mut W, issues: &[ValidationIssue]) -> std::io::Result<()> {
+    writeln!(writer, "{}", CSV_HEADER)?;
+    for issue in issues {
+        let (name, root_page) = match &issue.location {
+            IssueLocation::Table { name, root_page } | IssueLocation::Index { name, root_page } => {
+                (name.as_deref().map_or_else(String::new, csv_field), root_page.to_string())
+            }
+            _ => (String::new(), String::new()),
+        };
+        let rows: Vec<(String, &[RowidLocation])> = match &issue.duplicate_details {
+            Some(DuplicateDetails::Rowid(dups)) => dups
+                .iter()
+                .map(|dup| (dup.key.to_string(), &dup.locations[..]))
+                .collect(),
+            Some(DuplicateDetails::IndexKey(dups)) => dups
+                .iter()
+                .map(|dup| {
+                    let hex = dup.key.raw.iter().map(|b| format!("{:02x}", b)).collect();
+                    (hex, &dup.locations[..])
+                })
+                .collect(),
+            None => continue,
+        };
+        let commit_index = issue.commit_index.map_or_else(String::new, |i| i.to_string());
+
+        for (key, locations) in rows {
+            for loc in locations {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{},{},{},{}",
+                    issue.validator,
+                    name,
+                    root_page,
+                    key,
+                    loc.page_number,
+                    loc.cell_index,
+                    loc.frame_index.map_or_else(String::new, |i| i.to_string()),
+                    commit_index
+                )?;
+            }
+        }
+    }
+    writer.flush()
+}
+
+/// SARIF log, as read by GitHub code scanning
+#[derive(Serialize)]
+struct SarifLog<'a> {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: [SarifRun<'a>; 1],
+}
+
+#[derive(Serialize)]
+struct SarifRun<'a> {
+    tool: SarifTool,
+    results: Vec<SarifResult<'a>>,
+}
+
+#[derive(Serialize)]
+struct SarifTool {
+    driver: SarifDriver,
+}
+
+#[derive(Serialize)]
+struct SarifDriver {
+    name: &'static str,
+    version: &'static str,
+    rules: Vec<SarifRule>,
+}
+
+#[derive(Serialize)]
+struct SarifRule {
+    id: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult<'a> {
+    rule_id: &'static str,
+    rule_index: usize,
+    level: &'static str,
+    message: SarifMessage<'a>,
+    locations: [SarifLocation; 1],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<SarifProperties>,
+}
+
+#[derive(Serialize)]
+struct SarifMessage<'a> {
+    text: &'a str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLocation {
+    physical_location: SarifPhysicalLocation,
+    logical_locations: [SarifLogicalLocation; 1],
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifPhysicalLocation {
+    artifact_location: SarifArtifactLocation,
+}
+
+#[derive(Serialize)]
+struct SarifArtifactLocation {
+    uri: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifLogicalLocation {
+    name: String,
+    fully_qualified_name: String,
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifProperties {
+    commit_index: u64,
+}
+
+impl From<&IssueLocation> for SarifLogicalLocation {
+    fn from(location: &IssueLocation) -> Self {
+        let (name, kind) = match location {
+            IssueLocation::Table { name, root_page } => (
+                name.clone().unwrap_or_else(|| format!("root page {}", root_page)),
+                "table",
+            ),
+            IssueLocation::Index { name, root_page } => (
+                name.clone().unwrap_or_else(|| format!("root page {}", root_page)),
+                "index",
+            ),
+            IssueLocation::Page { page_number } => (format!("page {}", page_number), "page"),
+            IssueLocation::Database => ("database".to_string(), "database"),
+        };
+        Self {
+            name,
+            fully_qualified_name: location.to_string(),
+            kind,
+        }
+    }
+}
+
+/// Write the issues as a SARIF 2.1.0 log for GitHub code scanning.
+///
+/// Each validator becomes a rule and each issue a result at the level
+/// matching its severity. Code scanning needs a file for every result, so
+/// issues in the base database point at `db_path` and issues found after a
+/// commit point at `wal_path` (as do all issues when there is no database).
+/// The table, index or page is given as the result's logical location.
+pub fn write_sarif<W: Write>(
+    writer: &mut W,
+    issues: &[ValidationIssue],
+    db_path: Option<&Path>,
+    wal_path: &Path,
+) -> std::io::Result<()> {
+    let mut rules: Vec<SarifRule> = Vec::new();
+    let mut results = Vec::with_capacity(issues.len());
+
+    for issue in issues {
+        let rule_index = match rules.iter().position(|rule| rule.id == issue.validator) {
+            Some(index) => index,
+            None => {
+                rules.push(SarifRule {
+                    id: issue.validator,
+                });
+                rules.len() - 1
+            }
+        };
+        let level = match issue.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "note",
+        };
+        let artifact = match (issue.commit_index, db_path) {
+            (None, Some(db_path)) => db_path,
+            _ => wal_path,
+        };
+
+        results.push(SarifResult {
+            rule_id: issue.validator,
+            rule_index,
+            level,
+            message: SarifMessage {
+                text: &issue.message,
+            },
+            locations: [SarifLocation {
+                physical_location: SarifPhysicalLocation {
+                    artifact_location: SarifArtifactLocation {
+                        uri: artifact.to_string_lossy().replace('\\', "/"),
+                    },
+                },
+                logical_locations: [SarifLogicalLocation::from(&issue.location)],
+            }],
+            properties: issue
+                .commit_index
+                .map(|commit_index| SarifProperties { commit_index }),
+        });
+    }
+
+    let log = SarifLog {
+        schema: "https://json.schemastore.org/sarif-2.1.0.json",
+        version: "2.1.0",
+        runs: [SarifRun {
+            tool: SarifTool {
+                driver: SarifDriver {
+                    name: env!("CARGO_PKG_NAME"),
+                    version: env!("CARGO_PKG_VERSION"),
+                    rules,
+                },
+            },
+            results,
+        }],
+    };
+    serde_json::to_writer(&mut *writer, &log)?;
+    writeln!(writer)
+}
@@ -0,0 +1,203 @@
+//! The tables and indexes of a database state, with their parsed definitions.
+//!
+//! `BTreeScanner::discover_btrees` reads sqlite_master; this module adds
+//! what the CREATE statements say about each object, such as the columns of
+//! a table or the indexes built on it, so that callers don't each tokenize
+//! the same statements. `SchemaCache` carries a discovered schema from one
+//! state to the next while it can't have changed.
+
+use std::rc::Rc;
+
+use crate::btree::{BTreeInfo, BTreeScanner};
+use crate::db::DbHeader;
+use crate::error::Result;
+use crate::sql::{self, ForeignKey, IndexColumn, TableColumn};
+use crate::validator::PageCache;
+
+/// Size of the database header at the start of page 1
+const DB_HEADER_SIZE: usize = 100;
+
+/// Every table and index of a database state, in sqlite_master order.
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    /// Tables and indexes that own a B-tree, with their parsed definitions
+    pub objects: Vec<SchemaObject>,
+}
+
+/// A table or index and its parsed CREATE statement.
+#[derive(Debug, Clone)]
+pub struct SchemaObject {
+    /// What sqlite_master says about the object
+    pub btree: BTreeInfo,
+    /// For tables: the stored columns, in record order (None if the
+    /// statement is missing or can't be parsed)
+    pub table_columns: Option<Vec<TableColumn>>,
+    /// For tables: the declared foreign keys
+    pub foreign_keys: Vec<ForeignKey>,
+    /// For tables: true if declared WITHOUT ROWID
+    pub without_rowid: bool,
+    /// For indexes: the indexed columns (None for automatic indexes, which
+    /// have no statement, or if the statement can't be parsed)
+    pub index_columns: Option<Vec<IndexColumn>>,
+}
+
+impl SchemaObject {
+    /// Parse the CREATE statement of a discovered B-tree
+    pub fn new(btree: BTreeInfo) -> Self {
+        let statement = btree.sql.as_deref();
+        let (table_columns, foreign_keys, without_rowid, index_columns) = match statement {
+            Some(statement) if btree.is_table => (
+                sql::parse_table_columns(statement),
+                sql::parse_foreign_keys(statement),
+                sql::is_without_rowid(statement),
+                None,
+            ),
+            Some(statement) => (None, Vec::new(), false, sql::parse_index_columns(statement)),
+            None => (None, Vec::new(), false, None),
+        };
+
+        SchemaObject {
+            btree,
+            table_columns,
+            foreign_keys,
+            without_rowid,
+            index_columns,
+        }
+    }
+
+    /// Object name, if sqlite_master names it
+    pub fn name(&self) -> Option<&str> {
+        self.btree.name.as_deref()
+    }
+
+    /// Returns true if the object is named `name` (case-insensitive, as
+    /// SQLite compares identifiers)
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name().is_some_and(|n| n.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Schema {
+    /// Discover the tables and indexes of the state held by a page cache.
+    pub fn from_page_cache(page_cache: &mut PageCache) -> Result<Self> {
+        let btrees = BTreeScanner::new(page_cache).discover_btrees()?;
+        Ok(Self::from_btrees(btrees))
+    }
+
+    /// Parse the definitions of already discovered B-trees.
+    pub fn from_btrees(btrees: Vec<BTreeInfo>) -> Self {
+        Schema {
+            objects: btrees.into_iter().map(SchemaObject::new).collect(),
+        }
+    }
+
+    /// The B-tree of every object, in sqlite_master order.
+    pub fn btrees(&self) -> impl Iterator<Item = &BTreeInfo> {
+        self.objects.iter().map(|o| &o.btree)
+    }
+
+    /// Every table, in sqlite_master order.
+    pub fn tables(&self) -> impl Iterator<Item = &SchemaObject> {
+        self.objects.iter().filter(|o| o.btree.is_table)
+    }
+
+    /// Every index, in sqlite_master order.
+    pub fn indexes(&self) -> impl Iterator<Item = &SchemaObject> {
+        self.objects.iter().filter(|o| !o.btree.is_table)
+    }
+
+    /// The table with the given name (case-insensitive).
+    pub fn table(&self, name: &str) -> Option<&SchemaObject> {
+        self.tables().find(|t| t.is_named(name))
+    }
+
+    /// The indexes built on a table, including automatic ones.
+    pub fn indexes_on<'a>(&'a self, table: &'a str) -> impl Iterator<Item = &'a SchemaObject> {
+        self.indexes().filter(move |i| {
+            i.btree.tbl_name.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(table))
+        })
+    }
+}
+
+/// Schema of an earlier state, reused by later states while it can't have
+/// changed.
+///
+/// The schema is read again when the schema cookie changes, which every DDL
+/// statement does, or when any page of the sqlite_master B-tree, including
+/// the overflow pages of long statements, changed since it was read, which
+/// catches edits that leave the cookie alone (such as `PRAGMA
+/// writable_schema` updates or a corrupt page). Page 1, which most commits
+/// that grow the database rewrite, is compared by content past the database
+/// header; other pages by the WAL frame that last wrote them, so the cache
+/// must only be used with page caches replaying the same WAL.
+#[derive(Debug, Default)]
+pub struct SchemaCache {
+    entry: Option<CachedSchema>,
+    discoveries: u64,
+}
+
+/// A schema with what it was read from
+#[derive(Debug)]
+struct CachedSchema {
+    schema: Rc<Schema>,
+    /// Schema cookie of page 1
+    cookie: u32,
+    /// Page 1 past the database header
+    page_one: Vec<u8>,
+    /// Other pages of the sqlite_master B-tree and its overflow chains, with
+    /// the frame that last wrote each one (None for the base database)
+    pages: Vec<(u32, Option<u64>)>,
+}
+
+impl SchemaCache {
+    /// Create an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Schema of the state held by a page cache, reusing the cached one if
+    /// sqlite_master is unchanged.
+    pub fn schema(&mut self, page_cache: &mut PageCache) -> Result<Rc<Schema>> {
+        let page_one = page_cache.get_page_ref(1)?;
+        let cookie = DbHeader::parse(page_one)?.schema_cookie;
+        if let Some(entry) = &self.entry
+            && entry.cookie == cookie
+            && entry.page_one == page_one[DB_HEADER_SIZE..]
+            && entry
+                .pages
+                .iter()
+                .all(|&(page, frame)| page_cache.get_frame_index(page) == frame)
+        {
+            return Ok(Rc::clone(&entry.schema));
+        }
+
+        self.entry = None;
+        self.discoveries += 1;
+        let schema = Rc::new(Schema::from_page_cache(page_cache)?);
+        // Without the page list there is nothing to check reuse against
+        let mut scanner = BTreeScanner::new(page_cache);
+        let pages = scanner.collect_btree_pages(1).and_then(|mut pages| {
+            pages.extend(scanner.collect_overflow_pages(1)?);
+            Ok(pages)
+        });
+        if let Ok(pages) = pages {
+            let pages = pages
+                .into_iter()
+                .filter(|&page| page != 1)
+                .map(|page| (page, page_cache.get_frame_index(page)))
+                .collect();
+            self.entry = Some(CachedSchema {
+                schema: Rc::clone(&schema),
+                cookie,
+                page_one: page_cache.get_page_ref(1)?[DB_HEADER_SIZE..].to_vec(),
+                pages,
+            });
+        }
+        Ok(schema)
+    }
+
+    /// Number of times the schema was read rather than reused.
+    pub fn discoveries(&self) -> u64 {
+        self.discoveries
+    }
+}
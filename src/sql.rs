@@ -0,0 +1,497 @@
+//! Minimal SQL parsing for the CREATE statements stored in sqlite_master.
+//!
+//! This is not a SQL parser: it tokenizes a statement just well enough to
+//! pick out the parts validators care about (such as the column list of a
+//! CREATE INDEX) without being fooled by quoted identifiers, string literals
+//! or comments.
+
+/// A lexical token of a SQL statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// Unquoted keyword or identifier
+    Word(String),
+    /// Quoted identifier (`"x"`, `[x]` or `` `x` ``), without the quotes
+    Quoted(String),
+    /// String literal (`'x'`), without the quotes
+    Literal(String),
+    /// Numeric literal
+    Number(String),
+    /// Any other single character, such as `(`, `,` or `.`
+    Symbol(char),
+}
+
+impl Token {
+    /// Returns true if this is the given keyword (case-insensitive)
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Returns the name if this token can be an identifier
+    pub fn identifier(&self) -> Option<&str> {
+        match self {
+            Token::Word(name) | Token::Quoted(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+/// Split a SQL statement into tokens, dropping whitespace and comments
+pub fn tokenize(sql: &str) -> Vec<Token> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && chars.get(i + 1) == Some(&'-') {
+            // Line comment
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            // Block comment (an unterminated one runs to the end)
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '\'' {
+            let (text, next) = read_quoted(&chars, i, '\'');
+            tokens.push(Token::Literal(text));
+            i = next;
+        } else if c == '"' || c == '`' {
+            let (text, next) = read_quoted(&chars, i, c);
+            tokens.push(Token::Quoted(text));
+            i = next;
+        } else if c == '[' {
+            let end = chars[i + 1..]
+                .iter()
+                .position(|&ch| ch == ']')
+                .map_or(chars.len(), |p| i + 1 + p);
+            tokens.push(Token::Quoted(chars[i + 1..end].iter().collect()));
+            i = end + 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Number(chars[start..i].iter().collect()));
+        } else if c.is_alphanumeric() || c == '_' {
+            let start = i;
+            while i < chars.len() && is_identifier_char(chars[i]) {
+                i += 1;
+            }
+            tokens.push(Token::Word(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(Token::Symbol(c));
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+/// Characters allowed after the first character of an unquoted identifier
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Read a quoted token starting at `start`, where a doubled quote is an
+/// escaped quote. Returns the unquoted text and the index after the token.
+fn read_quoted(chars: &[char], start: usize, quote: char) -> (String, usize) {
+    let mut text = String::new();
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                text.push(quote);
+                i += 2;
+                continue;
+            }
+            return (text, i + 1);
+        }
+        text.push(chars[i]);
+        i += 1;
+    }
+
+    (text, i)
+}
+
+/// One entry of a CREATE INDEX column list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexColumn {
+    /// Column name, or None for an expression
+    pub name: Option<String>,
+    /// Collation named by a COLLATE clause, if any
+    pub collation: Option<String>,
+}
+
+/// Parse the indexed columns of a CREATE INDEX statement.
+///
+/// Returns None if the statement doesn't look like a CREATE INDEX.
+pub fn parse_index_columns(sql: &str) -> Option<Vec<IndexColumn>> {
+    let tokens = tokenize(sql);
+
+    // CREATE [UNIQUE] INDEX ... ON table (
+    if !tokens.first()?.is_keyword("CREATE") {
+        return None;
+    }
+    let on = tokens.iter().position(|t| t.is_keyword("ON"))?;
+    let open = on + tokens[on..].iter().position(|t| *t == Token::Symbol('('))?;
+
+    let mut columns = Vec::new();
+    let mut current: Vec<&Token> = Vec::new();
+    let mut depth = 0;
+
+    for token in &tokens[open + 1..] {
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') if depth == 0 => {
+                columns.push(index_column(&current));
+                return Some(columns);
+            }
+            Token::Symbol(')') => depth -= 1,
+            Token::Symbol(',') if depth == 0 => {
+                columns.push(index_column(&current));
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(token);
+    }
+
+    // Unterminated column list
+    None
+}
+
+/// Interpret the tokens of one indexed column
+fn index_column(tokens: &[&Token]) -> IndexColumn {
+    // A plain column is a single identifier, optionally followed by
+    // COLLATE <name> and/or ASC/DESC
+    let mut rest = tokens;
+    let mut collation = None;
+    if let [.., collate, name] = rest
+        && collate.is_keyword("COLLATE")
+    {
+        collation = name.identifier().map(str::to_string);
+        rest = &rest[..rest.len() - 2];
+    }
+    if let [.., order] = rest
+        && (order.is_keyword("ASC") || order.is_keyword("DESC"))
+    {
+        rest = &rest[..rest.len() - 1];
+    }
+    if let [.., collate, name] = rest
+        && collate.is_keyword("COLLATE")
+    {
+        collation = name.identifier().map(str::to_string);
+        rest = &rest[..rest.len() - 2];
+    }
+
+    let name = match rest {
+        [token] => token.identifier().map(str::to_string),
+        _ => None,
+    };
+    IndexColumn { name, collation }
+}
+
+/// Returns true if a statement starts with CREATE UNIQUE INDEX.
+///
+/// Only the prefix counts: the word UNIQUE in a quoted name, a comment or a
+/// WHERE clause doesn't make an index unique.
+pub fn is_unique_index(sql: &str) -> bool {
+    matches!(
+        &tokenize(sql)[..],
+        [create, unique, index, ..]
+            if create.is_keyword("CREATE")
+                && unique.is_keyword("UNIQUE")
+                && index.is_keyword("INDEX")
+    )
+}
+
+/// Returns true if a CREATE TABLE statement declares a WITHOUT ROWID table
+pub fn is_without_rowid(sql: &str) -> bool {
+    tokenize(sql)
+        .windows(2)
+        .any(|pair| pair[0].is_keyword("WITHOUT") && pair[1].is_keyword("ROWID"))
+}
+
+/// One column of a CREATE TABLE statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableColumn {
+    /// Column name
+    pub name: String,
+    /// True if the column is an INTEGER PRIMARY KEY, which SQLite stores as
+    /// the rowid (the record holds NULL in its place)
+    pub is_rowid_alias: bool,
+    /// Collation named by a COLLATE constraint, if any
+    pub collation: Option<String>,
+    /// True if the column has a NOT NULL constraint
+    pub not_null: bool,
+    /// True if the column is part of the PRIMARY KEY, declared on the column
+    /// or as a table constraint
+    pub primary_key: bool,
+}
+
+/// Parse the stored columns of a CREATE TABLE statement, in record order.
+///
+/// Virtual generated columns are left out, since records don't hold them.
+/// Returns None if the statement doesn't look like a CREATE TABLE with a
+/// column list (for example CREATE TABLE ... AS SELECT).
+pub fn parse_table_columns(sql: &str) -> Option<Vec<TableColumn>> {
+    let tokens = tokenize(sql);
+
+    let definitions = table_definitions(&tokens)?;
+
+    let mut columns = Vec::new();
+    let mut integer_columns = Vec::new();
+    let mut table_primary_key = Vec::new();
+
+    for definition in &definitions {
+        let first = definition.first()?;
+        if ["CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN"]
+            .iter()
+            .any(|k| first.is_keyword(k))
+        {
+            if let Some(key) = definition.iter().position(|t| t.is_keyword("PRIMARY")) {
+                table_primary_key = name_list(&definition[key..]);
+            }
+            continue;
+        }
+
+        let parsed = column_definition(definition)?;
+        if !parsed.is_virtual {
+            integer_columns.push(parsed.is_integer);
+            columns.push(parsed.column);
+        }
+    }
+
+    for name in &table_primary_key {
+        if let Some(column) = columns.iter_mut().find(|c| c.name.eq_ignore_ascii_case(name)) {
+            column.primary_key = true;
+        }
+    }
+
+    // A table constraint PRIMARY KEY over a single INTEGER column is also a
+    // rowid alias, even when declared DESC
+    if let [name] = table_primary_key.as_slice()
+        && let Some(i) = columns.iter().position(|c| c.name.eq_ignore_ascii_case(name))
+        && integer_columns[i]
+    {
+        columns[i].is_rowid_alias = true;
+    }
+
+    if is_without_rowid(sql) {
+        for column in &mut columns {
+            column.is_rowid_alias = false;
+        }
+    }
+
+    Some(columns)
+}
+
+/// Split the column list of a CREATE TABLE statement into its column and
+/// table constraint definitions.
+///
+/// Returns None if the tokens don't look like a CREATE TABLE with a column
+/// list (for example CREATE TABLE ... AS SELECT).
+fn table_definitions(tokens: &[Token]) -> Option<Vec<Vec<&Token>>> {
+    // CREATE [TEMP] TABLE [IF NOT EXISTS] name (
+    if !tokens.first()?.is_keyword("CREATE") {
+        return None;
+    }
+    let table = tokens.iter().position(|t| t.is_keyword("TABLE"))?;
+    let open = table + tokens[table..].iter().position(|t| *t == Token::Symbol('('))?;
+    if tokens[table..open].iter().any(|t| t.is_keyword("AS")) {
+        return None;
+    }
+
+    // Split the definitions at top-level commas
+    let mut definitions: Vec<Vec<&Token>> = vec![Vec::new()];
+    let mut depth = 0;
+    let mut closed = false;
+    for token in &tokens[open + 1..] {
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') if depth == 0 => {
+                closed = true;
+                break;
+            }
+            Token::Symbol(')') => depth -= 1,
+            Token::Symbol(',') if depth == 0 => {
+                definitions.push(Vec::new());
+                continue;
+            }
+            _ => {}
+        }
+        definitions.last_mut()?.push(token);
+    }
+    if !closed {
+        return None;
+    }
+    Some(definitions)
+}
+
+/// A FOREIGN KEY constraint of a CREATE TABLE statement
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKey {
+    /// Columns of the child table holding the reference
+    pub columns: Vec<String>,
+    /// Referenced (parent) table
+    pub parent_table: String,
+    /// Referenced columns of the parent table, or empty for its PRIMARY KEY
+    pub parent_columns: Vec<String>,
+}
+
+/// Parse the foreign keys of a CREATE TABLE statement, both column
+/// constraints (`a REFERENCES p (x)`) and table constraints
+/// (`FOREIGN KEY (a, b) REFERENCES p (x, y)`).
+pub fn parse_foreign_keys(sql: &str) -> Vec<ForeignKey> {
+    let tokens = tokenize(sql);
+    let Some(definitions) = table_definitions(&tokens) else {
+        return Vec::new();
+    };
+
+    let mut foreign_keys = Vec::new();
+    for definition in &definitions {
+        let Some(references) = definition.iter().position(|t| t.is_keyword("REFERENCES")) else {
+            continue;
+        };
+        let columns = match definition.iter().position(|t| t.is_keyword("FOREIGN")) {
+            Some(foreign) => name_list(&definition[foreign..references]),
+            None => definition
+                .first()
+                .and_then(|t| t.identifier())
+                .map(str::to_string)
+                .into_iter()
+                .collect(),
+        };
+        let Some(parent_table) = definition.get(references + 1).and_then(|t| t.identifier())
+        else {
+            continue;
+        };
+        let parent_columns = match definition.get(references + 2) {
+            Some(Token::Symbol('(')) => name_list(&definition[references + 2..]),
+            _ => Vec::new(),
+        };
+        foreign_keys.push(ForeignKey {
+            columns,
+            parent_table: parent_table.to_string(),
+            parent_columns,
+        });
+    }
+
+    foreign_keys
+}
+
+/// Keywords that end the type name of a column definition
+const COLUMN_CONSTRAINTS: [&str; 11] = [
+    "CONSTRAINT", "PRIMARY", "NOT", "NULL", "UNIQUE", "CHECK", "DEFAULT", "COLLATE",
+    "REFERENCES", "GENERATED", "AS",
+];
+
+/// A column definition as parsed from a CREATE TABLE statement
+struct ColumnDefinition {
+    column: TableColumn,
+    /// Declared type is exactly INTEGER
+    is_integer: bool,
+    /// Virtual generated column, not stored in records
+    is_virtual: bool,
+}
+
+/// Interpret the tokens of one column definition
+fn column_definition(tokens: &[&Token]) -> Option<ColumnDefinition> {
+    let name = tokens.first()?.identifier()?.to_string();
+
+    // Only look at nesting depth 0, so that the contents of CHECK (...) or
+    // DEFAULT (...) are not mistaken for constraints
+    let mut type_words = Vec::new();
+    let mut in_type = true;
+    let mut depth = 0;
+    let mut primary_key = false;
+    let mut descending = false;
+    let mut not_null = false;
+    let mut generated = false;
+    let mut stored = false;
+    let mut collation = None;
+
+    for (i, token) in tokens.iter().enumerate().skip(1) {
+        match token {
+            Token::Symbol('(') => {
+                depth += 1;
+                in_type = false;
+                continue;
+            }
+            Token::Symbol(')') => {
+                depth -= 1;
+                continue;
+            }
+            _ if depth > 0 => continue,
+            _ => {}
+        }
+
+        if in_type && COLUMN_CONSTRAINTS.iter().any(|k| token.is_keyword(k)) {
+            in_type = false;
+        }
+        if in_type {
+            type_words.push(token);
+        } else if token.is_keyword("PRIMARY")
+            && tokens.get(i + 1).is_some_and(|t| t.is_keyword("KEY"))
+        {
+            primary_key = true;
+            descending = tokens.get(i + 2).is_some_and(|t| t.is_keyword("DESC"));
+        } else if token.is_keyword("NOT")
+            && tokens.get(i + 1).is_some_and(|t| t.is_keyword("NULL"))
+        {
+            not_null = true;
+        } else if token.is_keyword("AS") {
+            generated = true;
+        } else if token.is_keyword("STORED") {
+            stored = true;
+        } else if token.is_keyword("COLLATE") {
+            collation = tokens.get(i + 1).and_then(|t| t.identifier()).map(str::to_string);
+        }
+    }
+
+    let is_integer = matches!(type_words.as_slice(), [word] if word.is_keyword("INTEGER"));
+
+    // INTEGER PRIMARY KEY DESC is not a rowid alias (a quirk kept by SQLite
+    // for backwards compatibility)
+    Some(ColumnDefinition {
+        column: TableColumn {
+            name,
+            is_rowid_alias: is_integer && primary_key && !descending,
+            collation,
+            not_null,
+            primary_key,
+        },
+        is_integer,
+        is_virtual: generated && !stored,
+    })
+}
+
+/// Column names of the first parenthesized list, such as that of a table
+/// constraint `PRIMARY KEY (a, b)`
+fn name_list(tokens: &[&Token]) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut expect_name = false;
+    for token in tokens {
+        match token {
+            Token::Symbol('(') | Token::Symbol(',') => expect_name = true,
+            Token::Symbol(')') => break,
+            _ if expect_name => {
+                if let Some(name) = token.identifier() {
+                    names.push(name.to_string());
+                }
+                expect_name = false;
+            }
+            _ => {}
+        }
+    }
+    names
+}
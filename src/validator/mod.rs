@@ -1,3 +1,3 @@
 pub mod page_cache;
 
-pub use page_cache::PageCache;
+pub use page_cache::{Overlay, PageCache};
@@ -1,54 +1,180 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
 use crate::db::PageReader;
-use crate::error::Result;
-use crate::wal::Commit;
+use crate::error::{Result, WalValidatorError};
+use crate::wal::{Commit, Frame};
+
+/// Number of base database pages kept in memory unless `with_capacity` says
+/// otherwise
+const DEFAULT_CAPACITY: usize = 2000;
+
+/// The WAL pages applied to a page cache, apart from its base database.
+///
+/// A copy of one state that another page cache over the same base database
+/// can continue from, so that commits can be validated from several starting
+/// points at once. It holds every page the WAL has written so far.
+#[derive(Debug, Clone, Default)]
+pub struct Overlay {
+    pages: HashMap<u32, (Vec<u8>, u64)>,
+    committed_db_size: Option<u32>,
+}
 
 /// Page cache that tracks current page state across WAL commits
 #[derive(Debug)]
 pub struct PageCache {
-    /// Path to the database file (kept for debugging)
+    /// Path to the database file (kept for debugging; None in WAL-only mode
+    /// or when the pages don't come from a file)
     #[allow(dead_code)]
-    db_path: PathBuf,
+    db_path: Option<PathBuf>,
     /// Page size in bytes
     page_size: u32,
     /// Total pages in base database
     db_page_count: u32,
+    /// Database size declared by the most recently applied commit
+    committed_db_size: Option<u32>,
     /// WAL overlay: page number -> (page data, frame index that last modified it)
     overlay: HashMap<u32, (Vec<u8>, u64)>,
-    /// Page reader for base database
-    page_reader: PageReader,
+    /// Page reader for base database (None in WAL-only mode)
+    page_reader: Option<PageReader>,
+    /// Recently read base database pages: page number -> (page data, last use)
+    base_pages: HashMap<u32, (Vec<u8>, u64)>,
+    /// Cached base database pages by last use, oldest first
+    recency: BTreeMap<u64, u32>,
+    /// Counter stamped on each cached page when it is used
+    clock: u64,
+    /// Most base database pages to keep cached before evicting the oldest
+    capacity: usize,
 }
 
 impl PageCache {
     /// Create a new page cache from a database file
     pub fn new(db_path: &Path, page_size: u32, page_count: u32) -> Self {
         PageCache {
-            db_path: db_path.to_path_buf(),
+            db_path: Some(db_path.to_path_buf()),
             page_size,
             db_page_count: page_count,
+            committed_db_size: None,
+            overlay: HashMap::new(),
+            page_reader: Some(PageReader::new(db_path, page_size, page_count)),
+            base_pages: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Create a page cache over an existing page reader
+    pub fn from_reader(page_reader: PageReader) -> Self {
+        PageCache {
+            db_path: None,
+            page_size: page_reader.page_size(),
+            db_page_count: page_reader.page_count(),
+            committed_db_size: None,
+            overlay: HashMap::new(),
+            page_reader: Some(page_reader),
+            base_pages: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+
+    /// Create a page cache backed only by WAL frames, for when the base
+    /// database is unavailable. Pages never written by the WAL are absent.
+    pub fn wal_only(page_size: u32) -> Self {
+        PageCache {
+            db_path: None,
+            page_size,
+            db_page_count: 0,
+            committed_db_size: None,
             overlay: HashMap::new(),
-            page_reader: PageReader::new(db_path, page_size, page_count),
+            page_reader: None,
+            base_pages: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            capacity: DEFAULT_CAPACITY,
         }
     }
 
+    /// Limit how many base database pages are kept in memory.
+    ///
+    /// Once the limit is reached, the least recently used base page is
+    /// evicted and read again from disk when next needed. Pages written by
+    /// the WAL are never evicted, since the base database can't restore them.
+    /// At least one page is always cached.
+    pub fn with_capacity(mut self, max_pages: usize) -> Self {
+        self.capacity = max_pages.max(1);
+        self.evict_to_capacity();
+        self
+    }
+
     /// Get a page, checking WAL overlay first, then base database
     pub fn get_page(&mut self, page_num: u32) -> Result<Vec<u8>> {
+        self.get_page_ref(page_num).map(<[u8]>::to_vec)
+    }
+
+    /// Borrow a page without copying it, checking WAL overlay first, then
+    /// base database.
+    ///
+    /// Takes `&mut self` because a base database page is cached on first
+    /// read, which may evict another.
+    pub fn get_page_ref(&mut self, page_num: u32) -> Result<&[u8]> {
         // Check WAL overlay first
-        if let Some((page, _frame_idx)) = self.overlay.get(&page_num) {
-            return Ok(page.clone());
+        if self.overlay.contains_key(&page_num) {
+            return Ok(&self.overlay[&page_num].0);
         }
 
-        // Fall back to base database
-        match self.page_reader.read_page(page_num) {
-            Ok(page) => Ok(page),
-            Err(crate::error::WalValidatorError::PageNotFound { .. }) => {
-                // Page doesn't exist in base DB file (may only exist in WAL)
-                // Return a zeroed page
-                Ok(vec![0u8; self.page_size as usize])
-            }
-            Err(e) => Err(e),
+        // Pages the reader holds in memory (an image or a mapping) are
+        // borrowed from it rather than copied into the cache
+        let in_memory = |r: &PageReader| r.page_in_memory(page_num).is_some();
+        if self.page_reader.as_ref().is_some_and(in_memory) {
+            let page_reader = self.page_reader.as_ref().expect("checked above");
+            return Ok(page_reader.page_in_memory(page_num).expect("checked above"));
+        }
+
+        // Without a base database, pages outside the WAL simply don't exist
+        let Some(page_reader) = &self.page_reader else {
+            return Err(WalValidatorError::PageNotFound { page_num });
+        };
+
+        self.clock += 1;
+        let now = self.clock;
+
+        if let Some((_, last_used)) = self.base_pages.get_mut(&page_num) {
+            self.recency.remove(last_used);
+            *last_used = now;
+        } else {
+            // Fall back to base database
+            let page = match page_reader.read_page(page_num) {
+                Ok(page) => page,
+                Err(WalValidatorError::PageNotFound { .. }) => {
+                    // Page doesn't exist in base DB file (may only exist in WAL)
+                    // Return a zeroed page
+                    vec![0u8; self.page_size as usize]
+                }
+                Err(e) => return Err(e),
+            };
+            self.base_pages.insert(page_num, (page, now));
+        }
+        self.recency.insert(now, page_num);
+        self.evict_to_capacity();
+
+        Ok(&self.base_pages[&page_num].0)
+    }
+
+    /// Get the number of base database pages currently cached
+    pub fn cached_page_count(&self) -> usize {
+        self.base_pages.len()
+    }
+
+    /// Drop least recently used base pages until the cache fits its capacity
+    fn evict_to_capacity(&mut self) {
+        while self.base_pages.len() > self.capacity {
+            let Some((_, page_num)) = self.recency.pop_first() else {
+                break;
+            };
+            self.base_pages.remove(&page_num);
         }
     }
 
@@ -60,16 +186,73 @@ impl PageCache {
     /// Apply a commit's frames to the overlay
     pub fn apply_commit(&mut self, commit: &Commit) {
         for frame in &commit.frames {
-            self.overlay.insert(
-                frame.header.page_number,
-                (frame.page_data.clone(), frame.frame_index),
-            );
+            self.apply_frame(frame);
         }
+        self.committed_db_size = Some(commit.db_size);
+    }
+
+    /// Apply a commit's frames to the overlay, returning how many frames
+    /// rewrote a page with byte-identical content.
+    ///
+    /// Each frame is compared against the page state it replaces, so a page
+    /// written twice within the same commit is compared against the first write.
+    /// A page that neither the WAL nor the base database holds (past the end
+    /// of the base, or any page in WAL-only mode) always counts as changed.
+    pub fn apply_commit_counting_noops(&mut self, commit: &Commit) -> Result<u64> {
+        let mut noop_frames = 0;
+
+        for frame in &commit.frames {
+            let page_num = frame.header.page_number;
+            let exists = self.overlay.contains_key(&page_num) || page_num <= self.db_page_count;
+            if exists && self.get_page_ref(page_num)? == frame.page_data.as_slice() {
+                noop_frames += 1;
+            }
+            self.apply_frame(frame);
+        }
+        self.committed_db_size = Some(commit.db_size);
+
+        Ok(noop_frames)
+    }
+
+    /// Put a frame's page in the overlay
+    fn apply_frame(&mut self, frame: &Frame) {
+        self.forget_base_page(frame.header.page_number);
+        self.overlay.insert(
+            frame.header.page_number,
+            (frame.page_data.clone(), frame.frame_index),
+        );
+    }
+
+    /// Drop the cached base copy of a page the WAL is about to overwrite
+    fn forget_base_page(&mut self, page_num: u32) {
+        if let Some((_, last_used)) = self.base_pages.remove(&page_num) {
+            self.recency.remove(&last_used);
+        }
+    }
+
+    /// Copy the WAL pages applied so far
+    pub fn overlay(&self) -> Overlay {
+        Overlay {
+            pages: self.overlay.clone(),
+            committed_db_size: self.committed_db_size,
+        }
+    }
+
+    /// Continue from a state copied by `overlay`, replacing the WAL pages
+    /// applied so far
+    pub fn with_overlay(mut self, overlay: Overlay) -> Self {
+        for &page_num in overlay.pages.keys() {
+            self.forget_base_page(page_num);
+        }
+        self.overlay = overlay.pages;
+        self.committed_db_size = overlay.committed_db_size;
+        self
     }
 
     /// Reset the overlay (for re-validation)
     pub fn reset(&mut self) {
         self.overlay.clear();
+        self.committed_db_size = None;
     }
 
     /// Get the page size
@@ -77,8 +260,21 @@ impl PageCache {
         self.page_size
     }
 
-    /// Get the current effective page count (base + WAL extensions)
+    /// Get the page count declared by the base database header
+    pub fn base_page_count(&self) -> u32 {
+        self.db_page_count
+    }
+
+    /// Get the current effective page count
+    ///
+    /// Once a commit has been applied its declared database size is
+    /// authoritative, since a commit can shrink the database (e.g. VACUUM).
+    /// Before that, the base size is extended by any pages in the overlay.
     pub fn effective_page_count(&self) -> u32 {
+        if let Some(db_size) = self.committed_db_size {
+            return db_size;
+        }
+
         let max_overlay = self.overlay.keys().max().copied().unwrap_or(0);
         std::cmp::max(self.db_page_count, max_overlay)
     }
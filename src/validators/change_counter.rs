@@ -0,0 +1,97 @@
+//! Validator for the database header's file change counter.
+//!
+//! The change counter at offset 24 of page 1 is bumped by every transaction
+//! that writes page 1. Across the WAL, each commit that rewrites page 1 should
+//! therefore leave a counter strictly greater than the previous one; a counter
+//! that goes backwards or stays put suggests frames from different histories
+//! were spliced together.
+//!
+//! SQLite does not guarantee the counter is bumped in WAL mode, so healthy
+//! WALs can trip the stagnant-counter check; the validator is opt-in via
+//! `check_change_counter`.
+
+use crate::db::DbHeader;
+use crate::error::Result;
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that checks the file change counter increases across commits.
+pub struct ChangeCounterValidator {
+    /// Change counter of the most recent state that had page 1 checked
+    previous_counter: Option<u32>,
+}
+
+impl ChangeCounterValidator {
+    /// Create a new change counter validator.
+    pub fn new() -> Self {
+        Self {
+            previous_counter: None,
+        }
+    }
+}
+
+impl Default for ChangeCounterValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for ChangeCounterValidator {
+    fn name(&self) -> &'static str {
+        "change-counter"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        // Only the base state and commits that rewrite page 1 carry a new counter
+        if let Some(commit) = ctx.commit
+            && !commit.frames.iter().any(|f| f.header.page_number == 1)
+        {
+            return Ok(issues);
+        }
+
+        let page_data = ctx.page_cache.get_page_ref(1)?;
+        let counter = DbHeader::parse(page_data)?.change_counter;
+
+        if let Some(previous) = self.previous_counter.replace(counter) {
+            let message = if counter < previous {
+                Some(format!(
+                    "File change counter went backwards from {} to {}",
+                    previous, counter
+                ))
+            } else if counter == previous {
+                Some(format!(
+                    "File change counter stayed at {} although page 1 was rewritten",
+                    counter
+                ))
+            } else {
+                None
+            };
+
+            if let Some(message) = message {
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Warning,
+                    message,
+                    IssueLocation::Page { page_number: 1 },
+                    commit_index,
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.check_change_counter
+    }
+
+    fn compares_states(&self) -> bool {
+        // The counter is compared with the last one seen
+        true
+    }
+}
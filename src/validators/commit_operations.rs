@@ -0,0 +1,109 @@
+//! Validator for describing what each WAL commit changed.
+//!
+//! Maps the pages written by a commit back to the tables and indexes that own
+//! them, turning raw page churn into a schema-level summary such as
+//! "commit #42 modified table 'orders' and index 'idx_orders_date'".
+
+use std::collections::HashMap;
+
+use crate::error::Result;
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that reports the tables and indexes modified by each commit.
+///
+/// This is informational only and is enabled by `describe_commits`.
+pub struct CommitOperationValidator;
+
+impl CommitOperationValidator {
+    /// Create a new commit operation validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CommitOperationValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for CommitOperationValidator {
+    fn name(&self) -> &'static str {
+        "commit-operations"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        // Only WAL commits have written pages to describe
+        let Some(commit) = ctx.commit else {
+            return Ok(Vec::new());
+        };
+        let commit_index = ctx.commit_index;
+
+        // Discover all B-trees
+        let btrees = ctx.discover_btrees()?;
+        let mut scanner = ctx.scanner();
+
+        // Build a map of page number -> owning object, starting with sqlite_master.
+        // A walk stops at the first page it can't parse, so pages past the damage
+        // count as outside any B-tree; page-layout reports the damage itself.
+        let mut owners: Vec<String> = vec!["table 'sqlite_master'".to_string()];
+        let mut page_owner: HashMap<u32, usize> = HashMap::new();
+        for (page, _) in scanner.pages(1).map_while(Result::ok) {
+            page_owner.insert(page, 0);
+        }
+
+        for btree in &btrees {
+            let kind = if btree.is_table { "table" } else { "index" };
+            let name = btree.name.as_deref().unwrap_or("<unknown>");
+            owners.push(format!("{} '{}'", kind, name));
+
+            let owner = owners.len() - 1;
+            for (page, _) in scanner.pages(btree.root_page).map_while(Result::ok) {
+                page_owner.insert(page, owner);
+            }
+        }
+
+        // Intersect with the pages written by this commit
+        let mut modified: Vec<usize> = Vec::new();
+        let mut unowned_pages = 0;
+        for frame in &commit.frames {
+            match page_owner.get(&frame.header.page_number) {
+                Some(&owner) => {
+                    if !modified.contains(&owner) {
+                        modified.push(owner);
+                    }
+                }
+                None => unowned_pages += 1,
+            }
+        }
+
+        let mut parts: Vec<String> = modified.iter().map(|&o| owners[o].clone()).collect();
+        if unowned_pages > 0 {
+            parts.push(format!(
+                "{} page(s) outside any B-tree (overflow or freelist)",
+                unowned_pages
+            ));
+        }
+
+        let description = match parts.split_last() {
+            None => "no pages".to_string(),
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        };
+
+        Ok(vec![ValidationIssue::new(
+            self.name(),
+            Severity::Info,
+            format!("Commit modified {}", description),
+            IssueLocation::Database,
+            commit_index,
+        )])
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.describe_commits
+    }
+}
@@ -0,0 +1,127 @@
+//! Validator for the ordering of WAL commits.
+//!
+//! SQLite allows a single writer, so commits follow each other strictly:
+//! commit indices increase and each commit's frames pick up where the
+//! previous commit's ended. A commit that shrinks the database also rewrites
+//! page 1 with the new page count. A WAL that breaks these rules was spliced
+//! together, corrupted, or written by something other than SQLite.
+
+use crate::db::DbHeader;
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// The most recent commit seen by the validator
+struct SeenCommit {
+    index: u64,
+    db_size: u32,
+    last_frame: u64,
+}
+
+/// Validator that checks commits form a single-writer sequence.
+pub struct CommitSequenceValidator {
+    previous: Option<SeenCommit>,
+}
+
+impl CommitSequenceValidator {
+    /// Create a new commit sequence validator.
+    pub fn new() -> Self {
+        Self { previous: None }
+    }
+}
+
+impl Default for CommitSequenceValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for CommitSequenceValidator {
+    fn name(&self) -> &'static str {
+        "commit-sequence"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        // Only WAL commits form the sequence
+        let Some(commit) = ctx.commit else {
+            return Ok(issues);
+        };
+        let (Some(first), Some(last)) = (commit.frames.first(), commit.frames.last()) else {
+            return Ok(issues);
+        };
+
+        let mut warn = |message: String, location: IssueLocation| {
+            issues.push(ValidationIssue::new(
+                self.name(),
+                Severity::Warning,
+                message,
+                location,
+                commit_index,
+            ));
+        };
+
+        // Skipped commits (--since-commit) leave gaps, so only an immediate
+        // predecessor is compared frame by frame
+        if let Some(previous) = &self.previous {
+            if commit.index <= previous.index {
+                warn(
+                    format!(
+                        "Commit index {} does not follow commit {}",
+                        commit.index, previous.index
+                    ),
+                    IssueLocation::Database,
+                );
+            } else if commit.index == previous.index + 1 {
+                if first.frame_index != previous.last_frame + 1 {
+                    warn(
+                        format!(
+                            "Commit starts at frame {} but the previous commit ended at frame {}",
+                            first.frame_index, previous.last_frame
+                        ),
+                        IssueLocation::Database,
+                    );
+                }
+
+                if commit.db_size < previous.db_size {
+                    let rewrites_page_count = commit
+                        .frames
+                        .iter()
+                        .rev()
+                        .find(|f| f.header.page_number == 1)
+                        .and_then(|f| DbHeader::parse(&f.page_data).ok())
+                        .is_some_and(|header| header.page_count == commit.db_size);
+                    if !rewrites_page_count {
+                        warn(
+                            format!(
+                                "Database shrank from {} to {} page(s) without page 1 \
+                                 recording the new size",
+                                previous.db_size, commit.db_size
+                            ),
+                            IssueLocation::Page { page_number: 1 },
+                        );
+                    }
+                }
+            }
+        }
+
+        self.previous = Some(SeenCommit {
+            index: commit.index,
+            db_size: commit.db_size,
+            last_frame: last.frame_index,
+        });
+
+        Ok(issues)
+    }
+
+    fn compares_states(&self) -> bool {
+        // Each commit is compared with the one before it
+        true
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
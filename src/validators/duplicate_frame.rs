@@ -0,0 +1,67 @@
+//! Validator for back-to-back duplicate WAL frames.
+//!
+//! A transaction may write the same page more than once, but each write
+//! normally carries different content. Two consecutive frames in one commit
+//! with the same page number and byte-identical data are more likely the
+//! result of a WAL being copied or truncated incorrectly.
+
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that flags consecutive identical frames within a commit.
+pub struct DuplicateFrameValidator;
+
+impl DuplicateFrameValidator {
+    /// Create a new duplicate frame validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for DuplicateFrameValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for DuplicateFrameValidator {
+    fn name(&self) -> &'static str {
+        "duplicate-frame"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+
+        // Only WAL commits have frames
+        let Some(commit) = ctx.commit else {
+            return Ok(issues);
+        };
+
+        for pair in commit.frames.windows(2) {
+            let (previous, frame) = (&pair[0], &pair[1]);
+            if frame.header.page_number == previous.header.page_number
+                && frame.page_data == previous.page_data
+            {
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Warning,
+                    format!(
+                        "Frames {} and {} write identical data to the same page back-to-back",
+                        previous.frame_index, frame.frame_index
+                    ),
+                    IssueLocation::Page {
+                        page_number: frame.header.page_number,
+                    },
+                    ctx.commit_index,
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
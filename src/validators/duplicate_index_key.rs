@@ -1,5 +1,7 @@
 //! Validator for detecting duplicate keys in index B-trees.
 
+use std::collections::HashMap;
+
 use crate::error::Result;
 
 use super::duplicate_rowid::find_duplicates;
@@ -35,10 +37,12 @@ impl Validator for DuplicateIndexKeyValidator {
     fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
         let mut issues = Vec::new();
         let commit_index = ctx.commit_index;
+        let comparison = ctx.config.key_comparison;
 
         // Discover all B-trees
+        let btrees = ctx.discover_btrees()?;
         let mut scanner = ctx.scanner();
-        let btrees = scanner.discover_btrees()?;
+        let encoding = scanner.encoding();
 
         // Check each index B-tree
         for btree in btrees {
@@ -52,8 +56,29 @@ impl Validator for DuplicateIndexKeyValidator {
                 continue;
             }
 
-            let keys = scanner.collect_index_keys(btree.root_page)?;
-            let duplicates = find_duplicates(keys);
+            // Group keys under the configured comparison (which may fold text
+            // under the index's collations); keys that can't be normalized are
+            // compared by their raw bytes
+            let mut rowids = HashMap::new();
+            let keys: Vec<_> = scanner
+                .collect_index_keys_including_interior(btree.root_page)?
+                .into_iter()
+                .map(|(key, rowid, location)| {
+                    rowids.insert((location.page_number, location.cell_index), rowid);
+                    let comparable = key.comparable(comparison, &btree.collations, encoding);
+                    (comparable.unwrap_or(key), location)
+                })
+                .collect();
+            let mut duplicates = find_duplicates(keys);
+
+            // Name the rows each conflicting entry points to
+            for duplicate in &mut duplicates {
+                duplicate.rowids = duplicate
+                    .locations
+                    .iter()
+                    .map(|loc| rowids[&(loc.page_number, loc.cell_index)])
+                    .collect();
+            }
 
             if !duplicates.is_empty() {
                 issues.push(ValidationIssue::duplicate_index_keys(
@@ -68,4 +93,17 @@ impl Validator for DuplicateIndexKeyValidator {
 
         Ok(issues)
     }
+
+    fn scans_indexes(&self) -> bool {
+        true
+    }
+
+    fn depends_on(&self) -> &[&'static str] {
+        // Report structural damage before the duplicates it may explain
+        &["page-layout"]
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
 }
@@ -37,8 +37,8 @@ impl Validator for DuplicateRowidValidator {
         let commit_index = ctx.commit_index;
 
         // Discover all B-trees
+        let btrees = ctx.discover_btrees()?;
         let mut scanner = ctx.scanner();
-        let btrees = scanner.discover_btrees()?;
 
         // Check each table B-tree
         for btree in btrees {
@@ -62,6 +62,15 @@ impl Validator for DuplicateRowidValidator {
 
         Ok(issues)
     }
+
+    fn depends_on(&self) -> &[&'static str] {
+        // Report structural damage before the duplicates it may explain
+        &["page-layout"]
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
 }
 
 /// Find duplicate entries in a list of (key, location) pairs.
@@ -79,6 +88,10 @@ pub fn find_duplicates<K: Eq + Hash + Clone>(
     groups
         .into_iter()
         .filter(|(_, locations)| locations.len() > 1)
-        .map(|(key, locations)| DuplicateEntry { key, locations })
+        .map(|(key, locations)| DuplicateEntry {
+            key,
+            locations,
+            rowids: Vec::new(),
+        })
         .collect()
 }
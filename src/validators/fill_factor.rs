@@ -0,0 +1,94 @@
+//! Validator for the fill factor of B-tree leaf pages.
+//!
+//! The headers, cell pointers and cells of a leaf page must fit in its usable
+//! space. A B-tree whose leaf pages claim to use more bytes than they have can
+//! only come from corrupt cell pointers or payload sizes.
+//!
+//! The fill factors themselves are an operational metric, so the validator
+//! only runs alongside `--stats`. A B-tree that can't be walked is skipped
+//! with a warning.
+
+use crate::error::Result;
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that flags B-trees whose leaf pages are more than full.
+pub struct FillFactorValidator;
+
+impl FillFactorValidator {
+    /// Create a new fill factor validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FillFactorValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for FillFactorValidator {
+    fn name(&self) -> &'static str {
+        "fill-factor"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let btrees = ctx.discover_btrees()?;
+        let mut scanner = ctx.scanner();
+
+        for btree in &btrees {
+            let location = if btree.is_table {
+                IssueLocation::Table {
+                    name: btree.name.clone(),
+                    root_page: btree.root_page,
+                }
+            } else {
+                IssueLocation::Index {
+                    name: btree.name.clone(),
+                    root_page: btree.root_page,
+                }
+            };
+
+            // A tree that can't be walked is skipped; page-layout reports the damage
+            let Ok(fill) = scanner.fill_factor(btree) else {
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Warning,
+                    "Fill factor not measured: the B-tree can't be walked".to_string(),
+                    location,
+                    commit_index,
+                ));
+                continue;
+            };
+            if fill.ratio() <= 1.0 {
+                continue;
+            }
+
+            issues.push(ValidationIssue::new(
+                self.name(),
+                Severity::Warning,
+                format!(
+                    "Leaf pages are {:.1}% full ({} of {} usable bytes across {} page(s))",
+                    fill.ratio() * 100.0,
+                    fill.used_bytes,
+                    fill.usable_bytes,
+                    fill.leaf_pages
+                ),
+                location,
+                commit_index,
+            ));
+        }
+
+        Ok(issues)
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.stats
+    }
+}
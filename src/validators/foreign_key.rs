@@ -0,0 +1,257 @@
+//! Validator for foreign keys that reference missing parent rows.
+//!
+//! Mirrors `PRAGMA foreign_key_check` against the replayed state: for each
+//! FOREIGN KEY of a table, every child row whose key columns are all non-NULL
+//! must match a row of the parent table. SQLite only enforces this while
+//! `PRAGMA foreign_keys` is on, so a violation can be legitimate data rather
+//! than corruption, but a row pointing at nothing is worth a look either way.
+//!
+//! Values are compared as stored, without the type affinity conversions
+//! SQLite applies when comparing a child value to the parent key, and
+//! without collations. WITHOUT ROWID tables are skipped, as children and as
+//! parents, since their records hold the columns in primary key order.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::btree::cell::Value;
+use crate::btree::BTreeScanner;
+use crate::error::Result;
+use crate::schema::SchemaObject;
+use crate::sql::{ForeignKey, TableColumn};
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that checks child rows against the parent rows they reference.
+pub struct ForeignKeyValidator;
+
+impl ForeignKeyValidator {
+    /// Create a new foreign key validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ForeignKeyValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for ForeignKeyValidator {
+    fn name(&self) -> &'static str {
+        "foreign-key"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let schema = ctx.schema()?;
+        let mut scanner = ctx.scanner();
+
+        // Keys of each parent table, by parent name and key columns
+        let mut parent_keys: HashMap<(String, Vec<String>), HashSet<Vec<String>>> =
+            HashMap::new();
+
+        for child in schema.tables() {
+            let Some(child_columns) = rowid_table_columns(child) else {
+                continue;
+            };
+            let name = child.name().unwrap_or("<unknown>");
+
+            for foreign_key in &child.foreign_keys {
+                let Some(child_key) = column_positions(child_columns, &foreign_key.columns) else {
+                    continue;
+                };
+                let Some(parent) = schema.table(&foreign_key.parent_table) else {
+                    continue;
+                };
+                let Some(parent_table_columns) = rowid_table_columns(parent) else {
+                    continue;
+                };
+                let Some(parent_columns) = parent_key_columns(parent_table_columns, foreign_key)
+                else {
+                    issues.push(ValidationIssue::new(
+                        self.name(),
+                        Severity::Warning,
+                        format!(
+                            "Foreign key on {} of table '{}' references table '{}', which has \
+                             no PRIMARY KEY to match",
+                            describe_columns(&foreign_key.columns),
+                            name,
+                            foreign_key.parent_table
+                        ),
+                        IssueLocation::Table {
+                            name: child.name().map(str::to_string),
+                            root_page: child.btree.root_page,
+                        },
+                        commit_index,
+                    ));
+                    continue;
+                };
+                if parent_columns.len() != child_key.len() {
+                    continue;
+                }
+
+                let cache_key = (foreign_key.parent_table.to_lowercase(), parent_columns.clone());
+                if !parent_keys.contains_key(&cache_key) {
+                    let Some(keys) = collect_parent_keys(&mut scanner, parent, &parent_columns)?
+                    else {
+                        continue;
+                    };
+                    parent_keys.insert(cache_key.clone(), keys);
+                }
+                let keys = &parent_keys[&cache_key];
+
+                let root_page = child.btree.root_page;
+                for (rowid, record, location) in scanner.collect_table_records(root_page)? {
+                    // A NULL in any key column means the row references nothing
+                    let Some(values) = key_values(rowid, &record, &child_key, child_columns)
+                    else {
+                        continue;
+                    };
+                    let key: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+                    if keys.contains(&key) {
+                        continue;
+                    }
+                    issues.push(ValidationIssue::new(
+                        self.name(),
+                        Severity::Error,
+                        format!(
+                            "Row {} of table '{}' has {} = {}, but table '{}' has no row with \
+                             {} = {}",
+                            rowid,
+                            name,
+                            describe_columns(&foreign_key.columns),
+                            describe_key(&key),
+                            foreign_key.parent_table,
+                            describe_columns(&parent_columns),
+                            describe_key(&key)
+                        ),
+                        IssueLocation::Page {
+                            page_number: location.page_number,
+                        },
+                        commit_index,
+                    ));
+                }
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.check_foreign_keys
+    }
+
+    fn depends_on(&self) -> &[&'static str] {
+        // Report structural damage before the missing rows it may explain
+        &["page-layout"]
+    }
+}
+
+/// Columns of a rowid table, or None for a WITHOUT ROWID table or one whose
+/// statement can't be parsed
+fn rowid_table_columns(table: &SchemaObject) -> Option<&[TableColumn]> {
+    if table.without_rowid {
+        return None;
+    }
+    table.table_columns.as_deref()
+}
+
+/// Referenced columns of the parent table: those the foreign key names, or
+/// else the parent's PRIMARY KEY. None if it names none and the parent
+/// declares no PRIMARY KEY, which SQLite reports as a foreign key mismatch
+/// rather than falling back to the rowid.
+fn parent_key_columns(columns: &[TableColumn], foreign_key: &ForeignKey) -> Option<Vec<String>> {
+    if !foreign_key.parent_columns.is_empty() {
+        return Some(foreign_key.parent_columns.clone());
+    }
+    let primary_key: Vec<String> = columns
+        .iter()
+        .filter(|c| c.primary_key)
+        .map(|c| c.name.clone())
+        .collect();
+    (!primary_key.is_empty()).then_some(primary_key)
+}
+
+/// Record positions of the named columns, or None if one doesn't exist
+fn column_positions(columns: &[TableColumn], names: &[String]) -> Option<Vec<usize>> {
+    names
+        .iter()
+        .map(|name| columns.iter().position(|c| c.name.eq_ignore_ascii_case(name)))
+        .collect()
+}
+
+/// Values of the key columns of a row, or None if any of them is NULL.
+/// A rowid alias column holds the rowid; a column past the end of a short
+/// record (added by ALTER TABLE later) is treated as NULL.
+fn key_values(
+    rowid: i64,
+    record: &[Value],
+    positions: &[usize],
+    columns: &[TableColumn],
+) -> Option<Vec<Value>> {
+    positions
+        .iter()
+        .map(|&i| {
+            if columns[i].is_rowid_alias {
+                return Some(Value::Int(rowid));
+            }
+            record.get(i).filter(|v| **v != Value::Null).cloned()
+        })
+        .collect()
+}
+
+/// Formatted keys of every row of the parent table. A key on the rowid
+/// alone is read from the cells without decoding the records.
+fn collect_parent_keys(
+    scanner: &mut BTreeScanner,
+    parent: &SchemaObject,
+    key_columns: &[String],
+) -> Result<Option<HashSet<Vec<String>>>> {
+    let Some(columns) = rowid_table_columns(parent) else {
+        return Ok(None);
+    };
+    let Some(positions) = column_positions(columns, key_columns) else {
+        return Ok(None);
+    };
+
+    if let [position] = positions[..]
+        && columns[position].is_rowid_alias
+    {
+        let rowids = scanner.collect_table_rowids(parent.btree.root_page)?;
+        return Ok(Some(
+            rowids
+                .into_iter()
+                .map(|(rowid, _)| vec![Value::Int(rowid).to_string()])
+                .collect(),
+        ));
+    }
+
+    let mut keys = HashSet::new();
+    for (rowid, record, _) in scanner.collect_table_records(parent.btree.root_page)? {
+        if let Some(values) = key_values(rowid, &record, &positions, columns) {
+            keys.insert(values.iter().map(|v| v.to_string()).collect());
+        }
+    }
+    Ok(Some(keys))
+}
+
+/// Column names for messages: `a` or `(a, b)`
+fn describe_columns(names: &[String]) -> String {
+    match names {
+        [name] => name.clone(),
+        _ => format!("({})", names.join(", ")),
+    }
+}
+
+/// Key values for messages: `1` or `(1, 'x')`
+fn describe_key(values: &[String]) -> String {
+    match values {
+        [value] => value.clone(),
+        _ => format!("({})", values.join(", ")),
+    }
+}
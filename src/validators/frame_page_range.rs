@@ -0,0 +1,66 @@
+//! Validator for pages written past a commit's declared database size.
+//!
+//! The commit frame records the database size in pages after the commit.
+//! A frame of that commit writing a higher page number describes a page the
+//! committed database doesn't have. Frames of earlier commits may write
+//! such pages legitimately: a later commit that shrinks the database (for
+//! example a VACUUM) drops them.
+
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that checks each commit's frames against its database size.
+pub struct FramePageRangeValidator;
+
+impl FramePageRangeValidator {
+    /// Create a new frame page range validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FramePageRangeValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for FramePageRangeValidator {
+    fn name(&self) -> &'static str {
+        "frame-page-range"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        // Only WAL commits declare a database size
+        let Some(commit) = ctx.commit else {
+            return Ok(Vec::new());
+        };
+
+        let issues = commit
+            .frames
+            .iter()
+            .filter(|frame| frame.header.page_number > commit.db_size)
+            .map(|frame| {
+                ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    format!(
+                        "Frame {} writes page {} past the {} page(s) its commit declares",
+                        frame.frame_index, frame.header.page_number, commit.db_size
+                    ),
+                    IssueLocation::Page {
+                        page_number: frame.header.page_number,
+                    },
+                    ctx.commit_index,
+                )
+            })
+            .collect();
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,170 @@
+//! Validator for the freelist of unused database pages.
+//!
+//! The database header names the first freelist trunk page and the total
+//! number of free pages. Each trunk page holds the number of the next trunk,
+//! a count of leaf pages and that many leaf page numbers. SQLite reuses
+//! these pages for new content, so a page that is both free and part of a
+//! live B-tree will be overwritten while still in use, and a count that
+//! disagrees with the list makes `PRAGMA integrity_check` fail.
+
+use std::collections::{HashMap, HashSet};
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::db::DbHeader;
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that walks the freelist and checks it against the header and
+/// the live B-trees.
+pub struct FreelistValidator;
+
+impl FreelistValidator {
+    /// Create a new freelist validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for FreelistValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for FreelistValidator {
+    fn name(&self) -> &'static str {
+        "freelist"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut problems = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let header = DbHeader::parse(ctx.page_cache.get_page_ref(1)?)?;
+        let page_count = ctx.page_cache.effective_page_count();
+        // Trunk pages hold the next trunk and leaf count ahead of the leaves
+        let max_leaves = header.usable_size() as usize / 4 - 2;
+
+        // Free pages, each with the trunk page that lists it
+        let mut free_pages: HashMap<u32, u32> = HashMap::new();
+        let mut trunk = header.freelist_head;
+
+        while trunk != 0 {
+            if trunk > page_count {
+                problems.push(format!(
+                    "Freelist trunk page {} is past the end of the database ({} pages)",
+                    trunk, page_count
+                ));
+                break;
+            }
+            if free_pages.insert(trunk, trunk).is_some() {
+                problems.push(format!(
+                    "Freelist trunk page {} is listed more than once; the freelist loops",
+                    trunk
+                ));
+                break;
+            }
+
+            let data = ctx.page_cache.get_page_ref(trunk)?;
+            let next_trunk = BigEndian::read_u32(&data[0..4]);
+            let mut leaf_count = BigEndian::read_u32(&data[4..8]) as usize;
+            if leaf_count > max_leaves {
+                problems.push(format!(
+                    "Freelist trunk page {} claims {} leaf pages, more than the {} that fit",
+                    trunk, leaf_count, max_leaves
+                ));
+                leaf_count = max_leaves;
+            }
+
+            for entry in data[8..8 + leaf_count * 4].chunks_exact(4) {
+                let leaf = BigEndian::read_u32(entry);
+                if leaf == 0 || leaf > page_count {
+                    problems.push(format!(
+                        "Freelist trunk page {} lists leaf page {}, which is not in the \
+                         database ({} pages)",
+                        trunk, leaf, page_count
+                    ));
+                } else if let Some(previous) = free_pages.insert(leaf, trunk) {
+                    problems.push(format!(
+                        "Freelist page {} is listed by trunk page {} and again by trunk \
+                         page {}",
+                        leaf, previous, trunk
+                    ));
+                }
+            }
+
+            trunk = next_trunk;
+        }
+
+        if free_pages.len() as u64 != header.freelist_count as u64 {
+            problems.push(format!(
+                "Header declares {} freelist page(s), but the freelist holds {}",
+                header.freelist_count,
+                free_pages.len()
+            ));
+        }
+
+        // A free page must not also be in use by a B-tree
+        if !free_pages.is_empty() {
+            let btrees = ctx.discover_btrees()?;
+            let mut scanner = ctx.scanner();
+            let mut roots = vec![(1, "sqlite_master".to_string())];
+            roots.extend(btrees.iter().map(|b| {
+                let name = b.name.clone().unwrap_or_else(|| format!("page {}", b.root_page));
+                (b.root_page, name)
+            }));
+
+            let mut reported = HashSet::new();
+            for (root, name) in roots {
+                for page in scanner.collect_btree_pages(root)? {
+                    if free_pages.contains_key(&page) && reported.insert(page) {
+                        problems.push(format!(
+                            "Freelist page {} is also a page of the B-tree '{}'",
+                            page, name
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(problems
+            .into_iter()
+            .map(|message| {
+                ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    message,
+                    IssueLocation::Database,
+                    commit_index,
+                )
+            })
+            .collect())
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
+
+/// Every page on the freelist: trunk pages and the leaves they list.
+///
+/// Unlike the validator, this stops quietly where the list loops or leaves
+/// the database, keeping the pages found up to that point.
+pub(crate) fn freelist_pages(ctx: &mut ValidationContext) -> Result<HashSet<u32>> {
+    let header = DbHeader::parse(ctx.page_cache.get_page_ref(1)?)?;
+    let page_count = ctx.page_cache.effective_page_count();
+    let max_leaves = header.usable_size() as usize / 4 - 2;
+
+    let mut pages = HashSet::new();
+    let mut trunk = header.freelist_head;
+    while trunk != 0 && trunk <= page_count && pages.insert(trunk) {
+        let data = ctx.page_cache.get_page_ref(trunk)?;
+        let leaf_count = (BigEndian::read_u32(&data[4..8]) as usize).min(max_leaves);
+        let leaves = data[8..8 + leaf_count * 4].chunks_exact(4).map(BigEndian::read_u32);
+        pages.extend(leaves.filter(|&leaf| leaf != 0 && leaf <= page_count));
+        trunk = BigEndian::read_u32(&data[0..4]);
+    }
+    Ok(pages)
+}
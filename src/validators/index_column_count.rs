@@ -0,0 +1,104 @@
+//! Validator for the number of columns in index entries.
+//!
+//! Every entry of an index on a rowid table is a record holding the indexed
+//! columns followed by the rowid, so its column count is fixed by the
+//! CREATE INDEX statement. An entry with any other count, or whose record
+//! header can't be parsed, is corrupt.
+
+use std::collections::HashMap;
+
+use crate::error::Result;
+use crate::sql::{is_without_rowid, parse_index_columns};
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that checks index entries have the declared number of columns.
+///
+/// Autoindexes have no SQL and indexes on WITHOUT ROWID tables append the
+/// table's primary key instead of a rowid, so both are skipped.
+pub struct IndexColumnCountValidator;
+
+impl IndexColumnCountValidator {
+    /// Create a new index column count validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for IndexColumnCountValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for IndexColumnCountValidator {
+    fn name(&self) -> &'static str {
+        "index-column-count"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let btrees = ctx.discover_btrees()?;
+        let mut scanner = ctx.scanner();
+
+        // Tables whose indexes end with the rowid
+        let rowid_tables: HashMap<&str, bool> = btrees
+            .iter()
+            .filter(|b| b.is_table)
+            .filter_map(|b| {
+                let name = b.name.as_deref()?;
+                let without_rowid = b.sql.as_deref().is_some_and(is_without_rowid);
+                Some((name, !without_rowid))
+            })
+            .collect();
+
+        for index in btrees.iter().filter(|b| !b.is_table) {
+            let Some(columns) = index.sql.as_deref().and_then(parse_index_columns) else {
+                continue;
+            };
+            let Some(&true) = index.tbl_name.as_deref().and_then(|t| rowid_tables.get(t)) else {
+                continue;
+            };
+
+            // Indexed columns plus the trailing rowid
+            let expected = columns.len() + 1;
+
+            // An index that can't be walked is skipped; page-layout reports the damage
+            let Ok(counts) = scanner.collect_index_column_counts(index.root_page) else {
+                continue;
+            };
+            for (count, location) in counts {
+                let problem = match count {
+                    Some(count) if count == expected => continue,
+                    Some(count) => format!("has {} column(s), expected {}", count, expected),
+                    None => "has a record header that can't be parsed".to_string(),
+                };
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    format!(
+                        "Entry at page {}, cell {} {}",
+                        location.page_number, location.cell_index, problem
+                    ),
+                    IssueLocation::Index {
+                        name: index.name.clone(),
+                        root_page: index.root_page,
+                    },
+                    commit_index,
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn scans_indexes(&self) -> bool {
+        true
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
@@ -9,7 +9,7 @@
 
 use std::collections::HashSet;
 
-use crate::btree::BTreeInfo;
+use crate::btree::BTreeClassification;
 use crate::error::Result;
 
 use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
@@ -22,43 +22,6 @@ impl IndexIntegrityValidator {
     pub fn new() -> Self {
         Self
     }
-
-    /// Check if an index should be skipped (partial or expression index).
-    fn should_skip_index(index: &BTreeInfo) -> bool {
-        if let Some(ref sql) = index.sql {
-            let sql_upper = sql.to_uppercase();
-
-            // Skip partial indexes (have WHERE clause)
-            if sql_upper.contains(" WHERE ") {
-                return true;
-            }
-
-            // Skip expression indexes (have parentheses in column list after ON table_name)
-            // Expression indexes look like: CREATE INDEX idx ON table ((expr))
-            // We detect this by looking for (( pattern or function calls in the index definition
-            if sql_upper.contains("((") {
-                return true;
-            }
-
-            // Also check for common SQL functions that indicate expression indexes
-            let expr_patterns = [
-                "LOWER(", "UPPER(", "SUBSTR(", "LENGTH(", "ABS(",
-                "COALESCE(", "IFNULL(", "NULLIF(", "TYPEOF(",
-                "CAST(", "DATE(", "TIME(", "DATETIME(", "JULIANDAY(",
-                "JSON_EXTRACT(", "JSON(",
-            ];
-            for pattern in expr_patterns {
-                if sql_upper.contains(pattern) {
-                    return true;
-                }
-            }
-        } else {
-            // Autoindexes (no SQL) are created by SQLite for PRIMARY KEY / UNIQUE constraints
-            // These should be checked, so don't skip
-        }
-
-        false
-    }
 }
 
 impl Default for IndexIntegrityValidator {
@@ -75,27 +38,17 @@ impl Validator for IndexIntegrityValidator {
     fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
         let mut issues = Vec::new();
         let commit_index = ctx.commit_index;
+        let config = ctx.config;
 
-        // Discover all B-trees
+        let schema = ctx.schema()?;
         let mut scanner = ctx.scanner();
-        let btrees = scanner.discover_btrees()?;
-
-        // Build a map of table name -> root page for quick lookup
-        let table_map: std::collections::HashMap<String, u32> = btrees
-            .iter()
-            .filter(|b| b.is_table)
-            .filter_map(|b| b.name.clone().map(|name| (name, b.root_page)))
-            .collect();
 
         // Check each index
-        for index in &btrees {
-            // Skip tables
-            if index.is_table {
-                continue;
-            }
+        for index in schema.indexes() {
+            let index = &index.btree;
 
             // Skip partial and expression indexes
-            if Self::should_skip_index(index) {
+            if index.classification() != BTreeClassification::Checked {
                 continue;
             }
 
@@ -106,8 +59,8 @@ impl Validator for IndexIntegrityValidator {
             };
 
             // Find the table's root page
-            let table_root = match table_map.get(tbl_name) {
-                Some(&root) => root,
+            let table_root = match schema.table(tbl_name) {
+                Some(table) => table.btree.root_page,
                 None => continue, // Table not found (shouldn't happen in valid DB)
             };
 
@@ -125,16 +78,18 @@ impl Validator for IndexIntegrityValidator {
                 .collect();
 
             // Find missing entries (in table but not in index)
-            let missing: Vec<i64> = table_rowids
+            let mut missing: Vec<i64> = table_rowids
                 .difference(&index_rowids)
                 .copied()
                 .collect();
+            missing.sort_unstable();
 
             // Find dangling entries (in index but not in table)
-            let dangling: Vec<i64> = index_rowids
+            let mut dangling: Vec<i64> = index_rowids
                 .difference(&table_rowids)
                 .copied()
                 .collect();
+            dangling.sort_unstable();
 
             // Report missing entries
             if !missing.is_empty() {
@@ -142,16 +97,10 @@ impl Validator for IndexIntegrityValidator {
                     self.name(),
                     Severity::Error,
                     format!(
-                        "Index is missing {} row(s) that exist in table '{}': {:?}",
+                        "Index is missing {} row(s) that exist in table '{}': {}",
                         missing.len(),
                         tbl_name,
-                        if missing.len() <= 10 {
-                            missing.clone()
-                        } else {
-                            let mut preview = missing[..10].to_vec();
-                            preview.push(-1); // Sentinel to indicate truncation
-                            preview
-                        }
+                        config.format_listed_values(&missing)
                     ),
                     IssueLocation::Index {
                         name: index.name.clone(),
@@ -167,15 +116,9 @@ impl Validator for IndexIntegrityValidator {
                     self.name(),
                     Severity::Error,
                     format!(
-                        "Index has {} dangling entry(ies) referencing non-existent rows: {:?}",
+                        "Index has {} dangling entry(ies) referencing non-existent rows: {}",
                         dangling.len(),
-                        if dangling.len() <= 10 {
-                            dangling.clone()
-                        } else {
-                            let mut preview = dangling[..10].to_vec();
-                            preview.push(-1); // Sentinel to indicate truncation
-                            preview
-                        }
+                        config.format_listed_values(&dangling)
                     ),
                     IssueLocation::Index {
                         name: index.name.clone(),
@@ -188,4 +131,8 @@ impl Validator for IndexIntegrityValidator {
 
         Ok(issues)
     }
+
+    fn scans_indexes(&self) -> bool {
+        true
+    }
 }
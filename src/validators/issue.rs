@@ -2,10 +2,13 @@
 
 use std::fmt;
 
+use serde::Serialize;
+
 use crate::btree::{IndexKey, RowidLocation};
 
 /// Severity of a validation issue.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Severity {
     /// Critical issue that indicates corruption
     Error,
@@ -15,8 +18,20 @@ pub enum Severity {
     Info,
 }
 
+impl Severity {
+    /// Position when listing severities, most severe first
+    pub(crate) fn rank(self) -> u8 {
+        match self {
+            Severity::Error => 0,
+            Severity::Warning => 1,
+            Severity::Info => 2,
+        }
+    }
+}
+
 /// Location where an issue was found.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
 pub enum IssueLocation {
     /// Issue in a table B-tree
     Table {
@@ -56,12 +71,16 @@ impl fmt::Display for IssueLocation {
 }
 
 /// A single duplicate entry (one rowid/key that appears multiple times).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DuplicateEntry<K> {
     /// The duplicated key or rowid
     pub key: K,
     /// All locations where this key appears
     pub locations: Vec<RowidLocation>,
+    /// For index keys: the rowid each entry points to, in the same order as
+    /// `locations` (empty for rowid duplicates)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub rowids: Vec<Option<i64>>,
 }
 
 impl<K> DuplicateEntry<K> {
@@ -76,7 +95,8 @@ impl<K> DuplicateEntry<K> {
 }
 
 /// Details about a duplicate issue.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "entries", rename_all = "snake_case")]
 pub enum DuplicateDetails {
     /// Duplicate rowids in a table
     Rowid(Vec<DuplicateEntry<i64>>),
@@ -85,7 +105,7 @@ pub enum DuplicateDetails {
 }
 
 /// A validation issue found by a validator.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ValidationIssue {
     /// Name of the validator that found this issue
     pub validator: &'static str,
@@ -10,6 +10,9 @@
 //! 3. Add the module to this file
 //! 4. Register it in `default_validators()`
 //!
+//! Library users can run validators of their own without changing this crate
+//! by passing them to [`crate::validate_with_validators`].
+//!
 //! # Example
 //!
 //! ```ignore
@@ -27,24 +30,412 @@
 //! }
 //! ```
 
+pub mod change_counter;
+pub mod commit_operations;
+pub mod commit_sequence;
+pub mod duplicate_frame;
 pub mod duplicate_index_key;
 pub mod duplicate_rowid;
+pub mod fill_factor;
+pub mod foreign_key;
+pub mod frame_page_range;
+pub mod freelist;
+pub mod index_column_count;
 pub mod index_integrity;
 pub mod issue;
+pub mod new_pages;
+pub mod not_null;
+pub mod ordering;
+pub mod orphan_page;
+pub mod page_layout;
+pub mod page_link;
+pub mod page_reference;
+pub mod page_size;
+pub mod ptrmap;
+pub mod repeated_page;
+pub mod reserved_bytes;
+pub mod root_page;
+pub mod rowid_resurrection;
+pub mod schema_cookie;
 
+pub use change_counter::ChangeCounterValidator;
+pub use commit_operations::CommitOperationValidator;
+pub use commit_sequence::CommitSequenceValidator;
+pub use duplicate_frame::DuplicateFrameValidator;
 pub use duplicate_index_key::DuplicateIndexKeyValidator;
 pub use duplicate_rowid::DuplicateRowidValidator;
+pub use fill_factor::FillFactorValidator;
+pub use foreign_key::ForeignKeyValidator;
+pub use frame_page_range::FramePageRangeValidator;
+pub use freelist::FreelistValidator;
+pub use index_column_count::IndexColumnCountValidator;
 pub use index_integrity::IndexIntegrityValidator;
 pub use issue::{DuplicateDetails, DuplicateEntry, IssueLocation, Severity, ValidationIssue};
+pub use new_pages::NewPageValidator;
+pub use not_null::NotNullValidator;
+pub use ordering::OrderingValidator;
+pub use orphan_page::OrphanPageValidator;
+pub use page_layout::PageLayoutValidator;
+pub use page_link::PageLinkValidator;
+pub use page_reference::PageReferenceValidator;
+pub use page_size::PageSizeValidator;
+pub use ptrmap::PtrmapValidator;
+pub use repeated_page::RepeatedPageValidator;
+pub use reserved_bytes::ReservedBytesValidator;
+pub use root_page::RootPageValidator;
+pub use rowid_resurrection::RowidResurrectionValidator;
+pub use schema_cookie::SchemaCookieValidator;
+
+use std::any::{Any, TypeId};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 
-use crate::btree::BTreeScanner;
+use crate::btree::{BTreeInfo, BTreeScanner, KeyComparison};
+use crate::schema::{Schema, SchemaCache};
 use crate::error::Result;
 use crate::validator::PageCache;
+use crate::wal::Commit;
 
 /// Configuration for validators.
+///
+/// Build one with [`ValidatorConfig::builder`], or refine a preset with
+/// [`ValidatorConfig::into_builder`]. The fields stay public so existing
+/// struct literals keep compiling, but the builder is the recommended path
+/// as options are added.
 #[derive(Debug, Clone, Default)]
 pub struct ValidatorConfig {
-    // Reserved for future configuration options
+    /// Skip validating the base database state and start at the first WAL commit
+    pub skip_base: bool,
+    /// Count WAL frames that rewrite a page with byte-identical content
+    pub detect_noop_frames: bool,
+    /// Skip validating commits whose frames all rewrite their page with
+    /// byte-identical content, noting each one as an Info issue
+    pub skip_noop_commits: bool,
+    /// Replay only the first N WAL frames, such as the mxFrame of a `-shm`
+    /// WAL-index; later frames are reported as not part of the database
+    pub max_frame: Option<u64>,
+    /// Report which tables and indexes each commit modified, and which
+    /// commits changed the schema
+    pub describe_commits: bool,
+    /// How index keys are compared when looking for duplicates
+    pub key_comparison: KeyComparison,
+    /// Skip the validators that scan index B-trees (overridden for those
+    /// selected by name in `only`)
+    pub skip_indexes: bool,
+    /// Check the B-tree headers of pages newly allocated by each commit
+    pub check_new_pages: bool,
+    /// Check the file change counter increases across commits that rewrite page 1
+    pub check_change_counter: bool,
+    /// Check the reserved bytes at the end of each page are zero
+    pub check_reserved_bytes: bool,
+    /// Check that keys are in order across each table and index B-tree
+    pub check_ordering: bool,
+    /// Check that rows hold no NULL in columns declared NOT NULL
+    pub check_constraints: bool,
+    /// Check that foreign keys reference existing parent rows
+    pub check_foreign_keys: bool,
+    /// Report rowids that reappear after an earlier commit deleted them
+    pub check_resurrected_rows: bool,
+    /// Report the fill factor of every B-tree and flag impossible ones
+    pub stats: bool,
+    /// Measure the wall time spent in each validator
+    pub time_validators: bool,
+    /// Record the frames and byte range each WAL commit occupies in the file
+    pub commit_positions: bool,
+    /// Only validate commits after this index; earlier commits are applied
+    /// without validation, and the base database is not checked
+    pub since_commit: Option<u64>,
+    /// Run only the validators with these names (empty = all enabled validators)
+    pub only: Vec<String>,
+    /// Never run the validators with these names, even if selected by `only`
+    pub skip: Vec<String>,
+    /// Show at most this many locations of each duplicated key in reports
+    /// (None = all of them)
+    pub max_locations_per_entry: Option<usize>,
+    /// Show at most this many duplicated keys per issue in reports, and list
+    /// at most this many values in issue messages (None = all duplicated
+    /// keys, and 10 listed values)
+    pub max_entries_per_issue: Option<usize>,
+}
+
+impl ValidatorConfig {
+    /// Start building a configuration from the defaults.
+    pub fn builder() -> ValidatorConfigBuilder {
+        ValidatorConfigBuilder::default()
+    }
+
+    /// Start building a configuration from this one, such as a preset.
+    pub fn into_builder(self) -> ValidatorConfigBuilder {
+        ValidatorConfigBuilder { config: self }
+    }
+
+    /// Returns a copy with settings implied by the selected validators applied.
+    ///
+    /// Selecting a validator by name in `only` turns on the setting it needs
+    /// (clearing `skip_indexes` for an index validator), so asking for it
+    /// explicitly never silently produces nothing.
+    pub fn with_implied_settings(&self) -> Self {
+        let mut config = self.clone();
+        if !self.only.is_empty() {
+            config.skip_indexes &= !default_validators()
+                .iter()
+                .any(|v| v.scans_indexes() && self.only.iter().any(|name| name == v.name()));
+        }
+        for name in &self.only {
+            match name.as_str() {
+                "new-pages" => config.check_new_pages = true,
+                "change-counter" => config.check_change_counter = true,
+                "reserved-bytes" => config.check_reserved_bytes = true,
+                "key-order" => config.check_ordering = true,
+                "not-null" => config.check_constraints = true,
+                "foreign-key" => config.check_foreign_keys = true,
+                "rowid-resurrection" => config.check_resurrected_rows = true,
+                "schema-cookie" | "commit-operations" => config.describe_commits = true,
+                "fill-factor" => config.stats = true,
+                _ => {}
+            }
+        }
+        config
+    }
+
+    /// Returns whether the base database state should be validated.
+    pub fn validates_base(&self) -> bool {
+        !self.skip_base && self.since_commit.is_none()
+    }
+
+    /// Returns whether the state after the given commit should be validated.
+    pub fn validates_commit(&self, commit_index: u64) -> bool {
+        self.since_commit.is_none_or(|since| commit_index > since)
+    }
+
+    /// Split the entries of an issue into those to show and the number left
+    /// out, following `max_entries_per_issue`.
+    pub fn truncate_entries<'a, T>(&self, entries: &'a [T]) -> (&'a [T], usize) {
+        truncate(entries, self.max_entries_per_issue)
+    }
+
+    /// Split the locations of a duplicated key into those to show and the
+    /// number left out, following `max_locations_per_entry`.
+    pub fn truncate_locations<'a, T>(&self, locations: &'a [T]) -> (&'a [T], usize) {
+        truncate(locations, self.max_locations_per_entry)
+    }
+
+    /// List values in an issue message, such as rowids, ending with
+    /// "... and N more" past `max_entries_per_issue` (10 when unset).
+    pub fn format_listed_values<T: std::fmt::Display>(&self, values: &[T]) -> String {
+        let limit = self.max_entries_per_issue.unwrap_or(DEFAULT_LISTED_VALUES);
+        let (shown, omitted) = truncate(values, Some(limit));
+        let mut list: Vec<String> = shown.iter().map(|v| v.to_string()).collect();
+        if omitted > 0 {
+            list.push(format!("... and {} more", omitted));
+        }
+        list.join(", ")
+    }
+
+    /// Only detect duplicate rowids, the cheapest corruption check.
+    pub fn fast() -> Self {
+        Self {
+            only: vec!["duplicate-rowid".to_string()],
+            ..Self::default()
+        }
+    }
+
+    /// Structural corruption checks (the validators whose
+    /// `checks_structure` is true), without the expensive index-to-table
+    /// integrity comparison.
+    pub fn corruption_only() -> Self {
+        Self {
+            check_new_pages: true,
+            only: default_validators()
+                .iter()
+                .filter(|v| v.checks_structure())
+                .map(|v| v.name().to_string())
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Every available check, with collation-aware index key comparison.
+    pub fn thorough() -> Self {
+        Self {
+            describe_commits: true,
+            check_new_pages: true,
+            check_change_counter: true,
+            check_reserved_bytes: true,
+            check_ordering: true,
+            check_constraints: true,
+            check_foreign_keys: true,
+            check_resurrected_rows: true,
+            stats: true,
+            key_comparison: KeyComparison::Collated,
+            ..Self::default()
+        }
+    }
+}
+
+/// Values listed in an issue message when `max_entries_per_issue` is unset
+const DEFAULT_LISTED_VALUES: usize = 10;
+
+/// Split a list into its first `limit` items and the number left out
+fn truncate<T>(items: &[T], limit: Option<usize>) -> (&[T], usize) {
+    match limit {
+        Some(limit) if items.len() > limit => (&items[..limit], items.len() - limit),
+        _ => (items, 0),
+    }
+}
+
+/// Builder for [`ValidatorConfig`], with one chainable method per setting,
+/// such as `ValidatorConfig::builder().check_ordering(true).build()`.
+#[derive(Debug, Clone, Default)]
+pub struct ValidatorConfigBuilder {
+    config: ValidatorConfig,
+}
+
+impl ValidatorConfigBuilder {
+    /// Skip validating the base database state
+    pub fn skip_base(mut self, skip_base: bool) -> Self {
+        self.config.skip_base = skip_base;
+        self
+    }
+
+    /// Count WAL frames that rewrite a page with byte-identical content
+    pub fn detect_noop_frames(mut self, detect_noop_frames: bool) -> Self {
+        self.config.detect_noop_frames = detect_noop_frames;
+        self
+    }
+
+    /// Skip validating commits that only rewrite pages with identical content
+    pub fn skip_noop_commits(mut self, skip_noop_commits: bool) -> Self {
+        self.config.skip_noop_commits = skip_noop_commits;
+        self
+    }
+
+    /// Replay only the first `max_frame` WAL frames
+    pub fn max_frame(mut self, max_frame: u64) -> Self {
+        self.config.max_frame = Some(max_frame);
+        self
+    }
+
+    /// Report which tables and indexes each commit modified
+    pub fn describe_commits(mut self, describe_commits: bool) -> Self {
+        self.config.describe_commits = describe_commits;
+        self
+    }
+
+    /// How index keys are compared when looking for duplicates
+    pub fn key_comparison(mut self, key_comparison: KeyComparison) -> Self {
+        self.config.key_comparison = key_comparison;
+        self
+    }
+
+    /// Skip the validators that scan index B-trees
+    pub fn skip_indexes(mut self, skip_indexes: bool) -> Self {
+        self.config.skip_indexes = skip_indexes;
+        self
+    }
+
+    /// Check the B-tree headers of pages newly allocated by each commit
+    pub fn check_new_pages(mut self, check_new_pages: bool) -> Self {
+        self.config.check_new_pages = check_new_pages;
+        self
+    }
+
+    /// Check the file change counter increases across commits
+    pub fn check_change_counter(mut self, check_change_counter: bool) -> Self {
+        self.config.check_change_counter = check_change_counter;
+        self
+    }
+
+    /// Check the reserved bytes at the end of each page are zero
+    pub fn check_reserved_bytes(mut self, check_reserved_bytes: bool) -> Self {
+        self.config.check_reserved_bytes = check_reserved_bytes;
+        self
+    }
+
+    /// Check that keys are in order across each B-tree
+    pub fn check_ordering(mut self, check_ordering: bool) -> Self {
+        self.config.check_ordering = check_ordering;
+        self
+    }
+
+    /// Check that rows hold no NULL in columns declared NOT NULL
+    pub fn check_constraints(mut self, check_constraints: bool) -> Self {
+        self.config.check_constraints = check_constraints;
+        self
+    }
+
+    /// Check that foreign keys reference existing parent rows
+    pub fn check_foreign_keys(mut self, check_foreign_keys: bool) -> Self {
+        self.config.check_foreign_keys = check_foreign_keys;
+        self
+    }
+
+    /// Report rowids that reappear after an earlier commit deleted them
+    pub fn check_resurrected_rows(mut self, check_resurrected_rows: bool) -> Self {
+        self.config.check_resurrected_rows = check_resurrected_rows;
+        self
+    }
+
+    /// Report the fill factor of every B-tree
+    pub fn stats(mut self, stats: bool) -> Self {
+        self.config.stats = stats;
+        self
+    }
+
+    /// Measure the wall time spent in each validator
+    pub fn time_validators(mut self, time_validators: bool) -> Self {
+        self.config.time_validators = time_validators;
+        self
+    }
+
+    /// Record the frames and byte range each WAL commit occupies in the file
+    pub fn commit_positions(mut self, commit_positions: bool) -> Self {
+        self.config.commit_positions = commit_positions;
+        self
+    }
+
+    /// Only validate commits after this index
+    pub fn since_commit(mut self, since_commit: u64) -> Self {
+        self.config.since_commit = Some(since_commit);
+        self
+    }
+
+    /// Run only the validators with these names, replacing any selected before
+    pub fn only<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.only = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Never run the validators with these names, replacing any skipped before
+    pub fn skip<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.skip = names.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Show at most this many locations of each duplicated key in reports
+    pub fn max_locations_per_entry(mut self, max_locations_per_entry: usize) -> Self {
+        self.config.max_locations_per_entry = Some(max_locations_per_entry);
+        self
+    }
+
+    /// Show at most this many duplicated keys or listed values per issue
+    pub fn max_entries_per_issue(mut self, max_entries_per_issue: usize) -> Self {
+        self.config.max_entries_per_issue = Some(max_entries_per_issue);
+        self
+    }
+
+    /// Finish building the configuration
+    pub fn build(self) -> ValidatorConfig {
+        self.config
+    }
 }
 
 /// Context provided to validators during validation.
@@ -55,28 +446,129 @@ pub struct ValidationContext<'a> {
     pub page_cache: &'a mut PageCache,
     /// Current commit index (None = base database state)
     pub commit_index: Option<u64>,
+    /// The commit that produced the current state (None = base database state)
+    pub commit: Option<&'a Commit>,
     /// Validator configuration
     pub config: &'a ValidatorConfig,
+    /// Results cached by earlier validators for the current state
+    pub scratch: Scratch,
+    /// Schema carried over from earlier states, if any
+    pub schema_cache: Option<&'a mut SchemaCache>,
 }
 
+/// Typed storage shared by the validators that run against one state.
+///
+/// Holds at most one value per type; a validator can stash an expensive
+/// result (such as a reachability set) for validators that depend on it.
+#[derive(Default)]
+pub struct Scratch {
+    entries: HashMap<TypeId, Box<dyn Any>>,
+}
+
+impl Scratch {
+    /// Get the cached value of type `T`, if any.
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.entries
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    /// Cache a value, replacing any previous value of the same type.
+    pub fn insert<T: 'static>(&mut self, value: T) {
+        self.entries.insert(TypeId::of::<T>(), Box::new(value));
+    }
+}
+
+/// Schema discovered in the current state, cached in the scratch area.
+struct DiscoveredSchema(Rc<Schema>);
+
+/// Pages reachable in the current state, cached in the scratch area.
+struct UsedPages(Rc<HashSet<u32>>);
+
 impl<'a> ValidationContext<'a> {
     /// Create a new validation context.
     pub fn new(
         page_cache: &'a mut PageCache,
-        commit_index: Option<u64>,
+        commit: Option<&'a Commit>,
         config: &'a ValidatorConfig,
     ) -> Self {
         Self {
             page_cache,
-            commit_index,
+            commit_index: commit.map(|c| c.index),
+            commit,
             config,
+            scratch: Scratch::default(),
+            schema_cache: None,
         }
     }
 
+    /// Reuse the schema of earlier states while it is unchanged, instead of
+    /// discovering it again for this state.
+    pub fn with_schema_cache(mut self, schema_cache: &'a mut SchemaCache) -> Self {
+        self.schema_cache = Some(schema_cache);
+        self
+    }
+
     /// Create a B-tree scanner for this context.
     pub fn scanner(&mut self) -> BTreeScanner<'_> {
         BTreeScanner::new(self.page_cache)
     }
+
+    /// Discover the schema of the current state.
+    ///
+    /// Discovery runs once per state; later calls reuse the cached result.
+    /// Objects whose root page lies past the end of the database are left
+    /// out, since traversing them would only read zero-filled pages; the
+    /// root page validator reports them instead.
+    pub fn schema(&mut self) -> Result<Rc<Schema>> {
+        if let Some(DiscoveredSchema(schema)) = self.scratch.get() {
+            return Ok(Rc::clone(schema));
+        }
+
+        let mut schema = match &mut self.schema_cache {
+            Some(cache) => cache.schema(self.page_cache)?,
+            None => Rc::new(Schema::from_page_cache(self.page_cache)?),
+        };
+        let page_count = self.page_cache.effective_page_count();
+        if schema.btrees().any(|b| b.root_page > page_count) {
+            let objects = schema.objects.iter().filter(|o| o.btree.root_page <= page_count);
+            schema = Rc::new(Schema {
+                objects: objects.cloned().collect(),
+            });
+        }
+        self.scratch.insert(DiscoveredSchema(Rc::clone(&schema)));
+        Ok(schema)
+    }
+
+    /// Discover all B-trees in the current state, as listed by `schema`.
+    pub fn discover_btrees(&mut self) -> Result<Vec<BTreeInfo>> {
+        Ok(self.schema()?.btrees().cloned().collect())
+    }
+
+    /// Pages in use in the current state: page 1, the pages of every B-tree
+    /// and their overflow chains, and the pages of the freelist.
+    ///
+    /// Found once per state, like `schema`. Fails if a B-tree can't be
+    /// walked, since the pages below the damage can't be told apart from
+    /// unused ones.
+    pub fn used_pages(&mut self) -> Result<Rc<HashSet<u32>>> {
+        if let Some(UsedPages(pages)) = self.scratch.get() {
+            return Ok(Rc::clone(pages));
+        }
+
+        let schema = self.schema()?;
+        let mut pages = freelist::freelist_pages(self)?;
+        let mut scanner = self.scanner();
+        let roots = std::iter::once(1).chain(schema.btrees().map(|b| b.root_page));
+        for root in roots {
+            pages.extend(scanner.collect_btree_pages(root)?);
+            pages.extend(scanner.collect_overflow_pages(root)?);
+        }
+
+        let pages = Rc::new(pages);
+        self.scratch.insert(UsedPages(Rc::clone(&pages)));
+        Ok(pages)
+    }
 }
 
 /// Trait for implementing validators.
@@ -92,14 +584,64 @@ pub trait Validator: Send + Sync {
     /// The context provides access to:
     /// - `page_cache`: Read pages from base DB + WAL overlay
     /// - `commit_index`: Current state (None = base DB, Some = after commit)
+    /// - `commit`: The frames written by the current commit, if any
     /// - `config`: Validator configuration
     fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>>;
 
+    /// Validate the current database state, passing each issue to `on_issue`
+    /// as it is found.
+    ///
+    /// The default implementation forwards the issues returned by
+    /// `validate`; a validator that can find many issues in one state may
+    /// override it to avoid holding them all at once.
+    fn validate_streaming(
+        &mut self,
+        ctx: &mut ValidationContext,
+        on_issue: &mut dyn FnMut(ValidationIssue),
+    ) -> Result<()> {
+        self.validate(ctx)?.into_iter().for_each(on_issue);
+        Ok(())
+    }
+
     /// Returns whether this validator is enabled for the given configuration.
     ///
-    /// Default implementation always returns true.
-    fn is_enabled(&self, _config: &ValidatorConfig) -> bool {
-        true
+    /// Default implementation disables validators that scan indexes when
+    /// `skip_indexes` is set, and enables all other validators unconditionally.
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        !self.scans_indexes() || !config.skip_indexes
+    }
+
+    /// Returns whether this validator scans index B-trees.
+    ///
+    /// Such validators are turned off by `skip_indexes`, unless one of them
+    /// is selected by name.
+    fn scans_indexes(&self) -> bool {
+        false
+    }
+
+    /// Returns whether this validator looks for structural corruption of the
+    /// database or WAL file, as opposed to checking the data it holds.
+    ///
+    /// The `corruption_only` preset runs exactly these validators.
+    fn checks_structure(&self) -> bool {
+        false
+    }
+
+    /// Names of validators that must run before this one.
+    ///
+    /// Dependencies on validators that aren't enabled are ignored.
+    fn depends_on(&self) -> &[&'static str] {
+        &[]
+    }
+
+    /// Returns whether this validator compares each state with the ones
+    /// validated before it.
+    ///
+    /// Such validators keep what they saw between calls, so they must see
+    /// every state in order; parallel validation runs them on one thread
+    /// and splits only the other validators across workers.
+    fn compares_states(&self) -> bool {
+        false
     }
 }
 
@@ -109,13 +651,88 @@ pub fn default_validators() -> Vec<Box<dyn Validator>> {
         Box::new(DuplicateRowidValidator::new()),
         Box::new(DuplicateIndexKeyValidator::new()),
         Box::new(IndexIntegrityValidator::new()),
+        Box::new(RootPageValidator::new()),
+        Box::new(CommitOperationValidator::new()),
+        Box::new(NewPageValidator::new()),
+        Box::new(PageLayoutValidator::new()),
+        Box::new(PageLinkValidator::new()),
+        Box::new(ChangeCounterValidator::new()),
+        Box::new(IndexColumnCountValidator::new()),
+        Box::new(DuplicateFrameValidator::new()),
+        Box::new(FillFactorValidator::new()),
+        Box::new(ReservedBytesValidator::new()),
+        Box::new(CommitSequenceValidator::new()),
+        Box::new(FramePageRangeValidator::new()),
+        Box::new(OrderingValidator::new()),
+        Box::new(PtrmapValidator::new()),
+        Box::new(FreelistValidator::new()),
+        Box::new(PageReferenceValidator::new()),
+        Box::new(RepeatedPageValidator::new()),
+        Box::new(SchemaCookieValidator::new()),
+        Box::new(NotNullValidator::new()),
+        Box::new(ForeignKeyValidator::new()),
+        Box::new(OrphanPageValidator::new()),
+        Box::new(RowidResurrectionValidator::new()),
+        Box::new(PageSizeValidator::new()),
     ]
 }
 
+/// Names of all available validators, in `default_validators()` order.
+pub fn validator_names() -> Vec<&'static str> {
+    default_validators().iter().map(|v| v.name()).collect()
+}
+
 /// Returns all enabled validators for the given configuration.
+///
+/// Validators are ordered so that each runs after the validators it depends
+/// on; otherwise the order of `default_validators()` is kept.
 pub fn enabled_validators(config: &ValidatorConfig) -> Vec<Box<dyn Validator>> {
-    default_validators()
+    select_validators(default_validators(), config)
+}
+
+/// Returns the validators of a set that are enabled for the given
+/// configuration, ordered by their dependencies.
+///
+/// Custom validators are selected by `only`, `skip` and `is_enabled` just
+/// like the built-in ones, and otherwise keep the order they are given in.
+pub fn select_validators(
+    validators: Vec<Box<dyn Validator>>,
+    config: &ValidatorConfig,
+) -> Vec<Box<dyn Validator>> {
+    let config = &config.with_implied_settings();
+    let enabled: Vec<Box<dyn Validator>> = validators
         .into_iter()
         .filter(|v| v.is_enabled(config))
-        .collect()
+        .filter(|v| config.only.is_empty() || config.only.iter().any(|name| name == v.name()))
+        .filter(|v| !config.skip.iter().any(|name| name == v.name()))
+        .collect();
+    order_by_dependencies(enabled)
+}
+
+/// Stable topological sort of validators by `depends_on`.
+///
+/// Validators caught in a dependency cycle keep their relative order and run last.
+fn order_by_dependencies(validators: Vec<Box<dyn Validator>>) -> Vec<Box<dyn Validator>> {
+    let mut remaining: Vec<Option<Box<dyn Validator>>> =
+        validators.into_iter().map(Some).collect();
+    let mut ordered: Vec<Box<dyn Validator>> = Vec::with_capacity(remaining.len());
+
+    loop {
+        // Pick the first validator whose enabled dependencies have all run
+        let ready = remaining.iter().position(|slot| {
+            slot.as_ref().is_some_and(|v| {
+                v.depends_on()
+                    .iter()
+                    .all(|dep| !remaining.iter().flatten().any(|other| other.name() == *dep))
+            })
+        });
+
+        match ready {
+            Some(idx) => ordered.extend(remaining[idx].take()),
+            None => break,
+        }
+    }
+
+    ordered.extend(remaining.into_iter().flatten());
+    ordered
 }
@@ -0,0 +1,142 @@
+//! Validator for pages newly allocated by a WAL commit.
+//!
+//! When a commit grows the database, the pages beyond the previous size are
+//! brand new and are the riskiest pages in the file. This validator parses the
+//! B-tree header of every newly allocated page that the commit links into a
+//! B-tree, without traversing the full trees.
+
+use std::collections::BTreeSet;
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::btree::BTreePageHeader;
+use crate::error::Result;
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that checks newly allocated B-tree pages have valid headers.
+///
+/// A new page can only become part of a B-tree if the same commit rewrites its
+/// parent (or sqlite_master, for a new root), so child pointers are gathered
+/// from the interior pages written by the commit. New pages that aren't linked
+/// this way (overflow or freelist pages) are not B-tree pages and are skipped.
+pub struct NewPageValidator {
+    /// Database size after the previous state
+    previous_db_size: Option<u32>,
+}
+
+impl NewPageValidator {
+    /// Create a new newly-allocated page validator.
+    pub fn new() -> Self {
+        Self {
+            previous_db_size: None,
+        }
+    }
+}
+
+impl Default for NewPageValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for NewPageValidator {
+    fn name(&self) -> &'static str {
+        "new-pages"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let Some(commit) = ctx.commit else {
+            // Base state: remember the starting size
+            self.previous_db_size = Some(ctx.page_cache.base_page_count());
+            return Ok(issues);
+        };
+
+        let previous_db_size = self
+            .previous_db_size
+            .unwrap_or_else(|| ctx.page_cache.base_page_count());
+        self.previous_db_size = Some(commit.db_size);
+
+        // Pages beyond the previous size that this commit wrote
+        let new_pages: BTreeSet<u32> = commit
+            .frames
+            .iter()
+            .map(|f| f.header.page_number)
+            .filter(|&p| p > previous_db_size && p <= commit.db_size)
+            .collect();
+
+        if new_pages.is_empty() {
+            return Ok(issues);
+        }
+
+        // Collect pages the commit links into a B-tree
+        let mut linked: BTreeSet<u32> = BTreeSet::new();
+        for frame in &commit.frames {
+            let page_num = frame.header.page_number;
+            let page_data = ctx.page_cache.get_page_ref(page_num)?;
+            let Ok((header, _)) = BTreePageHeader::parse(page_data, page_num) else {
+                continue;
+            };
+            if !header.page_type.is_interior() {
+                continue;
+            }
+
+            let Ok(cell_pointers) = header.get_cell_pointers(page_data, page_num) else {
+                continue;
+            };
+            for cell_ptr in cell_pointers {
+                let cell_offset = cell_ptr as usize;
+                if cell_offset + 4 <= page_data.len() {
+                    linked.insert(BigEndian::read_u32(&page_data[cell_offset..cell_offset + 4]));
+                }
+            }
+            if let Some(right_child) = header.right_child {
+                linked.insert(right_child);
+            }
+        }
+
+        // New roots are linked from sqlite_master, which lives on page 1
+        if commit.frames.iter().any(|f| f.header.page_number == 1) {
+            let btrees = ctx.discover_btrees()?;
+            linked.extend(btrees.iter().map(|b| b.root_page));
+        }
+
+        for &page_num in new_pages.intersection(&linked) {
+            let page_data = ctx.page_cache.get_page_ref(page_num)?;
+            if let Err(e) = BTreePageHeader::parse(page_data, page_num) {
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    format!(
+                        "Newly allocated page {} has an invalid B-tree header: {}",
+                        page_num, e
+                    ),
+                    IssueLocation::Page {
+                        page_number: page_num,
+                    },
+                    commit_index,
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.check_new_pages
+    }
+
+    fn compares_states(&self) -> bool {
+        // New pages are those past the previous state's size
+        true
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
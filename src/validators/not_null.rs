@@ -0,0 +1,101 @@
+//! Validator for NULLs in columns declared NOT NULL.
+//!
+//! SQLite refuses to store NULL in a NOT NULL column, so a NULL found there
+//! was written by something other than SQLite, most often a page write that
+//! zeroed part of a record: serial type 0 is NULL. Each row is decoded and
+//! checked against the column constraints of its CREATE TABLE statement.
+//!
+//! In rowid tables, PRIMARY KEY doesn't imply NOT NULL (a quirk SQLite keeps
+//! for backwards compatibility), and an INTEGER PRIMARY KEY is stored as the
+//! rowid with NULL in its place in the record, so only NOT NULL is checked.
+//! WITHOUT ROWID tables store their columns in primary key order and are
+//! skipped. A record shorter than the column list is a row written before
+//! ALTER TABLE ADD COLUMN, whose missing columns take their default.
+
+use crate::btree::cell::Value;
+use crate::error::Result;
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that looks for NULLs in NOT NULL columns.
+pub struct NotNullValidator;
+
+impl NotNullValidator {
+    /// Create a new NOT NULL validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for NotNullValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for NotNullValidator {
+    fn name(&self) -> &'static str {
+        "not-null"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let schema = ctx.schema()?;
+        let mut scanner = ctx.scanner();
+
+        for table in schema.tables() {
+            if table.without_rowid {
+                continue;
+            }
+            let Some(columns) = &table.table_columns else {
+                continue;
+            };
+            let not_null: Vec<usize> = columns
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| c.not_null && !c.is_rowid_alias)
+                .map(|(i, _)| i)
+                .collect();
+            if not_null.is_empty() {
+                continue;
+            }
+
+            let name = table.name().unwrap_or("<unknown>");
+            let root_page = table.btree.root_page;
+            for (rowid, record, location) in scanner.collect_table_records(root_page)? {
+                for &column in &not_null {
+                    if record.get(column) != Some(&Value::Null) {
+                        continue;
+                    }
+                    issues.push(ValidationIssue::new(
+                        self.name(),
+                        Severity::Error,
+                        format!(
+                            "Row {} of table '{}' (cell {}) holds NULL in NOT NULL column '{}'",
+                            rowid, name, location.cell_index, columns[column].name
+                        ),
+                        IssueLocation::Page {
+                            page_number: location.page_number,
+                        },
+                        commit_index,
+                    ));
+                }
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.check_constraints
+    }
+
+    fn depends_on(&self) -> &[&'static str] {
+        // Report structural damage before the NULLs it may explain
+        &["page-layout"]
+    }
+}
@@ -0,0 +1,157 @@
+//! Validator for the order of keys within each B-tree.
+//!
+//! A B-tree is only searchable if its keys appear in order when the tree is
+//! walked left to right. A page spliced in from elsewhere or a child pointer
+//! swapped to the wrong subtree can leave every key unique yet out of order,
+//! which the duplicate validators won't notice.
+//!
+//! Table rowids must strictly increase. Index keys must not decrease; they
+//! are compared column by column as SQLite does under the BINARY collation,
+//! so indexes that sort a column DESC or under another collation are skipped.
+
+use std::cmp::Ordering;
+
+use crate::btree::cell::compare_records;
+use crate::btree::BTreeInfo;
+use crate::error::Result;
+use crate::sql::{self, Token};
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that checks keys are in order across each B-tree.
+pub struct OrderingValidator;
+
+impl OrderingValidator {
+    /// Create a new ordering validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for OrderingValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for OrderingValidator {
+    fn name(&self) -> &'static str {
+        "key-order"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let btrees = ctx.discover_btrees()?;
+        let mut scanner = ctx.scanner();
+
+        for btree in &btrees {
+            if btree.is_table {
+                let rowids = scanner.collect_table_rowids_in_order(btree.root_page)?;
+                let out_of_order = rowids.windows(2).find(|pair| pair[1].0 <= pair[0].0);
+                if let Some([(previous, _), (rowid, location)]) = out_of_order {
+                    issues.push(ValidationIssue::new(
+                        self.name(),
+                        Severity::Error,
+                        format!(
+                            "Rowid {} at cell {} of table {} follows rowid {}; rowids must \
+                             increase",
+                            rowid,
+                            location.cell_index,
+                            describe(btree),
+                            previous
+                        ),
+                        IssueLocation::Page {
+                            page_number: location.page_number,
+                        },
+                        commit_index,
+                    ));
+                }
+                continue;
+            }
+
+            if !sorts_by_binary_ascending(btree, &btrees) {
+                continue;
+            }
+
+            let keys = scanner.collect_index_keys_in_order(btree.root_page)?;
+            let encoding = scanner.encoding();
+            let out_of_order = keys.windows(2).find(|pair| {
+                compare_records(&pair[1].0.raw, &pair[0].0.raw)
+                    .is_ok_and(|ordering| ordering == Ordering::Less)
+            });
+            if let Some([(previous, _), (key, location)]) = out_of_order {
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    format!(
+                        "Key {} at cell {} of index {} sorts before the preceding key {}",
+                        key.display(encoding),
+                        location.cell_index,
+                        describe(btree),
+                        previous.display(encoding)
+                    ),
+                    IssueLocation::Page {
+                        page_number: location.page_number,
+                    },
+                    commit_index,
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.check_ordering
+    }
+
+    fn depends_on(&self) -> &[&'static str] {
+        // Report structural damage before the disorder it may explain
+        &["page-layout"]
+    }
+}
+
+/// Name of a B-tree for messages, falling back to its root page
+fn describe(btree: &BTreeInfo) -> String {
+    match &btree.name {
+        Some(name) => format!("'{}'", name),
+        None => format!("at root page {}", btree.root_page),
+    }
+}
+
+/// Returns true if every column of an index sorts ascending under the BINARY
+/// collation, the only order `compare_records` reproduces.
+///
+/// A column's collation may come from the CREATE INDEX statement or from the
+/// table's column definition (which is also the only source for the
+/// automatic indexes of UNIQUE and PRIMARY KEY constraints), so both are
+/// checked. This is conservative: any DESC or non-BINARY COLLATE in either
+/// statement skips the index.
+fn sorts_by_binary_ascending(index: &BTreeInfo, btrees: &[BTreeInfo]) -> bool {
+    let table_sql = btrees
+        .iter()
+        .find(|b| b.is_table && b.name.is_some() && b.name == index.tbl_name)
+        .and_then(|b| b.sql.as_deref());
+
+    [index.sql.as_deref(), table_sql]
+        .into_iter()
+        .flatten()
+        .all(|statement| {
+            let tokens = sql::tokenize(statement);
+            !tokens.iter().any(|t| t.is_keyword("DESC"))
+                && !tokens.windows(2).any(|pair| {
+                    pair[0].is_keyword("COLLATE") && !is_binary_collation(&pair[1])
+                })
+        })
+}
+
+/// Returns true if a COLLATE operand names the BINARY collation
+fn is_binary_collation(token: &Token) -> bool {
+    token
+        .identifier()
+        .is_some_and(|name| name.eq_ignore_ascii_case("BINARY"))
+}
@@ -0,0 +1,84 @@
+//! Validator for pages that nothing in the database refers to.
+//!
+//! Every page of a healthy database belongs to a B-tree (as a tree page or
+//! an overflow page), is on the freelist, or is a pointer-map page or the
+//! lock-byte page, which SQLite sets aside. A page that is none of these is
+//! lost: `PRAGMA integrity_check` reports it as "never used". It usually
+//! means a lost write to a parent page or the freelist, so the page leaks
+//! until a VACUUM, though what it holds may show what the lost write was.
+
+use crate::btree::{is_lock_byte_page, PtrmapLayout};
+use crate::db::DbHeader;
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that reports pages unreachable from any B-tree or the freelist.
+pub struct OrphanPageValidator;
+
+impl OrphanPageValidator {
+    /// Create a new orphan page validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for OrphanPageValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for OrphanPageValidator {
+    fn name(&self) -> &'static str {
+        "orphan-page"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        // A B-tree that can't be walked hides the pages below the damage,
+        // which would all look orphaned; page-layout reports the damage
+        let Ok(used) = ctx.used_pages() else {
+            return Ok(issues);
+        };
+        let header = DbHeader::parse(ctx.page_cache.get_page_ref(1)?)?;
+        let page_count = ctx.page_cache.effective_page_count();
+        let ptrmap_pages = PtrmapLayout::from_header(&header)
+            .map(|layout| layout.ptrmap_pages(page_count))
+            .unwrap_or_default();
+
+        let orphans: Vec<u32> = (1..=page_count)
+            .filter(|page| {
+                !used.contains(page)
+                    && !ptrmap_pages.contains(page)
+                    && !is_lock_byte_page(*page, header.page_size)
+            })
+            .collect();
+        if !orphans.is_empty() {
+            issues.push(ValidationIssue::new(
+                self.name(),
+                Severity::Warning,
+                format!(
+                    "{} page(s) are not reachable from any B-tree or the freelist: {}",
+                    orphans.len(),
+                    ctx.config.format_listed_values(&orphans)
+                ),
+                IssueLocation::Database,
+                commit_index,
+            ));
+        }
+
+        Ok(issues)
+    }
+
+    fn depends_on(&self) -> &[&'static str] {
+        // Report structural damage before the pages it cut off
+        &["page-layout"]
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
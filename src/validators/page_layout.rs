@@ -0,0 +1,247 @@
+//! Validator for the physical layout of B-tree pages.
+//!
+//! Checks that each page's cells live where its header says they should:
+//! the cell content area starts at `cell_content_offset`, so every cell
+//! pointer must reference an offset at or beyond it. A pointer below that
+//! start means a cell was written into the header or cell pointer array.
+//!
+//! Each cell must also end within the page's usable space. A payload size
+//! that implies more local content than the page has left is corrupt, and
+//! would otherwise be silently skipped when rowids and keys are collected.
+//!
+//! Within the content area, no two cells may overlap, and the freeblock chain
+//! must run in ascending order without touching a cell. Every byte of the
+//! area then belongs to exactly one cell, freeblock or fragment, so their
+//! sizes must add up to the size of the area, as `PRAGMA integrity_check`
+//! also requires.
+//!
+//! A page that can't be read or parsed is reported as an issue of its own,
+//! and the remaining pages are still checked.
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::btree::BTreePageHeader;
+use crate::db::DbHeader;
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that checks the cell layout of every B-tree page.
+pub struct PageLayoutValidator;
+
+impl PageLayoutValidator {
+    /// Create a new page layout validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PageLayoutValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for PageLayoutValidator {
+    fn name(&self) -> &'static str {
+        "page-layout"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+        let name = self.name();
+        let mut report = |page_number, message| {
+            issues.push(ValidationIssue::new(
+                name,
+                Severity::Error,
+                message,
+                IssueLocation::Page { page_number },
+                commit_index,
+            ));
+        };
+
+        // Without a readable header, assume no bytes are reserved
+        let usable_size = match ctx.page_cache.get_page_ref(1).and_then(DbHeader::parse) {
+            Ok(header) => header.usable_size() as usize,
+            Err(e) => {
+                report(1, format!("Database header can't be read: {}", e));
+                ctx.page_cache.page_size() as usize
+            }
+        };
+
+        // Discover all B-trees, including sqlite_master itself
+        let mut roots = vec![1];
+        match ctx.discover_btrees() {
+            Ok(btrees) => roots.extend(btrees.iter().map(|b| b.root_page)),
+            Err(e) => report(1, format!("Schema can't be read: {}", e)),
+        }
+        let mut scanner = ctx.scanner();
+
+        // A walk stops at the first page it can't parse; the pages reached
+        // until then are still checked
+        let mut pages = Vec::new();
+        for root in roots {
+            for page in scanner.pages(root) {
+                match page {
+                    Ok((page_num, _)) => pages.push(page_num),
+                    Err(e) => report(
+                        root,
+                        format!("B-tree with root page {} can't be walked: {}", root, e),
+                    ),
+                }
+            }
+        }
+        pages.sort_unstable();
+        pages.dedup();
+
+        for page_num in pages {
+            let page_data = match ctx.page_cache.get_page_ref(page_num) {
+                Ok(page_data) => page_data,
+                Err(e) => {
+                    report(page_num, format!("Page can't be read: {}", e));
+                    continue;
+                }
+            };
+            for problem in page_problems(page_data, page_num, usable_size) {
+                report(page_num, problem);
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
+
+/// Describe what is wrong with the layout of one B-tree page
+fn page_problems(page_data: &[u8], page_num: u32, usable_size: usize) -> Vec<String> {
+    let mut problems = Vec::new();
+    let header = match BTreePageHeader::parse(page_data, page_num) {
+        Ok((header, _)) => header,
+        Err(e) => return vec![format!("Page header can't be parsed: {}", e)],
+    };
+    let cell_pointers = match header.get_cell_pointers(page_data, page_num) {
+        Ok(cell_pointers) => cell_pointers,
+        Err(e) => return vec![format!("Cell pointer array can't be read: {}", e)],
+    };
+
+    // A stored value of 0 means the content area starts at 65536
+    let content_start = if header.cell_content_offset == 0 {
+        65536
+    } else {
+        header.cell_content_offset as usize
+    };
+
+    // Byte range of each cell whose size could be read, with its index
+    let mut extents = Vec::with_capacity(cell_pointers.len());
+    for (cell_idx, &cell_ptr) in cell_pointers.iter().enumerate() {
+        let cell_ptr = cell_ptr as usize;
+        if cell_ptr < content_start {
+            problems.push(format!(
+                "Cell {} starts at offset {} below the cell content area start {}",
+                cell_idx, cell_ptr, content_start
+            ));
+        }
+
+        let cell = page_data.get(cell_ptr..).unwrap_or_default();
+        let cell_end = header
+            .page_type
+            .cell_size(cell, usable_size)
+            .map(|size| cell_ptr + size);
+        if let Ok(end) = cell_end
+            && end <= usable_size
+        {
+            extents.push((cell_ptr, end, cell_idx));
+            continue;
+        }
+
+        let extent = match cell_end {
+            Ok(end) => format!("ends at offset {}", end),
+            Err(_) => "has a truncated header".to_string(),
+        };
+        problems.push(format!(
+            "Cell {} at offset {} {}, past the usable page size {}",
+            cell_idx, cell_ptr, extent, usable_size
+        ));
+    }
+
+    extents.sort_unstable();
+    for pair in extents.windows(2) {
+        let ((start, end, first), (next_start, _, second)) = (pair[0], pair[1]);
+        if next_start < end {
+            problems.push(format!(
+                "Cell {} at offset {} overlaps cell {}, which spans offsets {}-{}",
+                second,
+                next_start,
+                first,
+                start,
+                end - 1
+            ));
+        }
+    }
+
+    // Freeblocks form a chain in ascending offset order through the content area
+    let mut free_bytes = 0;
+    let mut previous_end = content_start;
+    let mut offset = header.first_freeblock as usize;
+    while offset != 0 {
+        if offset < previous_end || offset + 4 > usable_size {
+            problems.push(format!(
+                "Freeblock at offset {} is out of order or outside the cell content area \
+                 ({}-{})",
+                offset,
+                content_start,
+                usable_size - 1
+            ));
+            break;
+        }
+        let next = BigEndian::read_u16(&page_data[offset..offset + 2]) as usize;
+        let size = BigEndian::read_u16(&page_data[offset + 2..offset + 4]) as usize;
+        let end = offset + size;
+        if size < 4 || end > usable_size {
+            problems.push(format!(
+                "Freeblock at offset {} has size {}, which doesn't fit between 4 bytes and the \
+                 usable page size {}",
+                offset, size, usable_size
+            ));
+            break;
+        }
+        if let Some(&(_, _, cell_idx)) = extents
+            .iter()
+            .find(|&&(start, cell_end, _)| start < end && offset < cell_end)
+        {
+            problems.push(format!(
+                "Freeblock at offset {} of size {} overlaps cell {}",
+                offset, size, cell_idx
+            ));
+        }
+
+        free_bytes += size;
+        previous_end = end;
+        offset = next;
+    }
+
+    // Every byte of the content area is a cell, a freeblock or a fragment;
+    // once anything else is wrong the totals can't be trusted
+    if problems.is_empty() && content_start <= usable_size {
+        let used: usize = extents.iter().map(|&(start, end, _)| end - start).sum();
+        let fragmented = header.fragmented_bytes as usize;
+        let content_size = usable_size - content_start;
+        if used + free_bytes + fragmented != content_size {
+            problems.push(format!(
+                "Cell content area holds {} bytes, but cells use {}, freeblocks {} and \
+                 fragments {} ({} in total)",
+                content_size,
+                used,
+                free_bytes,
+                fragmented,
+                used + free_bytes + fragmented
+            ));
+        }
+    }
+
+    problems
+}
@@ -0,0 +1,92 @@
+//! Validator for child pointers of interior pages that lead nowhere.
+//!
+//! Every cell of an interior page starts with the page number of its left
+//! child, and the page header holds the right-most child. Each of these must
+//! name a page of the database: a zeroed pointer, or one past the end of the
+//! database, cuts off the whole subtree below it. Traversals skip such
+//! pointers, so without this check the rows below them would just go
+//! missing from every other report.
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that checks every child pointer of interior pages is in range.
+pub struct PageLinkValidator;
+
+impl PageLinkValidator {
+    /// Create a new page link validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PageLinkValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for PageLinkValidator {
+    fn name(&self) -> &'static str {
+        "page-link"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let page_count = ctx.page_cache.effective_page_count();
+        let btrees = ctx.discover_btrees()?;
+        let mut roots = vec![(1, "sqlite_master".to_string())];
+        roots.extend(btrees.iter().map(|b| {
+            let name = b.name.clone().unwrap_or_else(|| format!("page {}", b.root_page));
+            (b.root_page, name)
+        }));
+
+        let mut scanner = ctx.scanner();
+        for (root, name) in roots {
+            scanner.walk_pages(root, |visit| {
+                let Some(right_child) = visit.header.right_child else {
+                    return Ok(());
+                };
+
+                // Cell pointers past the end of the page are page-layout's to report
+                let cell_pointers = visit.header.get_cell_pointers(visit.data, visit.page_number)?;
+                let left_children = cell_pointers.iter().enumerate().filter_map(|(i, &ptr)| {
+                    let child = visit.data.get(ptr as usize..ptr as usize + 4)?;
+                    Some((format!("left child of cell {}", i), BigEndian::read_u32(child)))
+                });
+                let children = left_children.chain([("right child".to_string(), right_child)]);
+
+                for (pointer, child) in children {
+                    if child != 0 && child <= page_count {
+                        continue;
+                    }
+                    issues.push(ValidationIssue::new(
+                        self.name(),
+                        Severity::Error,
+                        format!(
+                            "Interior page {} of B-tree '{}': the {} is page {}, outside the \
+                             database ({} pages)",
+                            visit.page_number, name, pointer, child, page_count
+                        ),
+                        IssueLocation::Page {
+                            page_number: visit.page_number,
+                        },
+                        commit_index,
+                    ));
+                }
+                Ok(())
+            })?;
+        }
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,127 @@
+//! Validator for B-tree pages reachable through more than one pointer.
+//!
+//! Every page of a B-tree has exactly one parent, and a root page has none.
+//! A page listed as the child of two interior pages (in the same tree or in
+//! two different ones), or a root page that some interior page also lists as
+//! a child, is the classic signature of a pointer overwritten with the
+//! number of a page already in use. Whichever tree writes to the page next
+//! corrupts the other.
+//!
+//! Only the first page reached through two parents is reported; the pages
+//! below it share a parent in both walks and so are explained by it.
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that checks every B-tree page is reached through one pointer.
+pub struct PageReferenceValidator;
+
+impl PageReferenceValidator {
+    /// Create a new page reference validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PageReferenceValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for PageReferenceValidator {
+    fn name(&self) -> &'static str {
+        "page-reference"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        // Every B-tree, including sqlite_master itself
+        let mut trees = vec![(1, "sqlite_master".to_string())];
+        trees.extend(ctx.discover_btrees()?.iter().map(|b| {
+            let name = b.name.clone().unwrap_or_else(|| format!("root page {}", b.root_page));
+            (b.root_page, name)
+        }));
+        let roots: HashMap<u32, &str> =
+            trees.iter().map(|(root, name)| (*root, name.as_str())).collect();
+        let mut scanner = ctx.scanner();
+
+        // The first parent each page was reached through, and its tree
+        let mut parents: HashMap<u32, (u32, &str)> = HashMap::new();
+        let mut problems = Vec::new();
+
+        for (root, tree) in &trees {
+            let mut reached = Vec::new();
+            let repeated = scanner.walk_pages(*root, |visit| {
+                if let Some(parent) = visit.parent {
+                    reached.push((visit.page_number, parent));
+                }
+                Ok(())
+            })?;
+
+            for (page, parent) in reached.into_iter().chain(repeated) {
+                if let Some(owner) = roots.get(&page) {
+                    problems.push((
+                        page,
+                        format!(
+                            "Page {} is the root page of '{}' but is also a child of page {} \
+                             in '{}'",
+                            page, owner, parent, tree
+                        ),
+                    ));
+                    continue;
+                }
+                match parents.entry(page) {
+                    Entry::Vacant(vacant) => {
+                        vacant.insert((parent, tree));
+                    }
+                    // Reached through the same parent again: a subtree shared
+                    // higher up, already reported at the page where it forks
+                    Entry::Occupied(occupied) if occupied.get().0 == parent => {
+                        if occupied.get().1 == tree {
+                            problems.push((
+                                page,
+                                format!(
+                                    "Page {} is listed twice as a child of page {} in '{}'",
+                                    page, parent, tree
+                                ),
+                            ));
+                        }
+                    }
+                    Entry::Occupied(occupied) => {
+                        let (first_parent, first_tree) = *occupied.get();
+                        problems.push((
+                            page,
+                            format!(
+                                "Page {} is a child of page {} in '{}' and of page {} in '{}'",
+                                page, first_parent, first_tree, parent, tree
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for (page_number, message) in problems {
+            issues.push(ValidationIssue::new(
+                self.name(),
+                Severity::Error,
+                message,
+                IssueLocation::Page { page_number },
+                commit_index,
+            ));
+        }
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
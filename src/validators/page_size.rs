@@ -0,0 +1,72 @@
+//! Validator for the page size declared by page 1 after WAL commits.
+//!
+//! The page size at offset 16 of page 1 is fixed once the database exists,
+//! and in WAL mode it must match the page size in the WAL header, which the
+//! base database is checked against before replay. A commit that rewrites
+//! page 1 can still declare another size in it; SQLite would then read every
+//! page at the wrong offsets, so the database is unreadable from that commit
+//! on. The field is read raw, since an invalid value fails header parsing.
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that checks commits rewriting page 1 keep its page size.
+pub struct PageSizeValidator;
+
+impl PageSizeValidator {
+    /// Create a new page size validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PageSizeValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for PageSizeValidator {
+    fn name(&self) -> &'static str {
+        "page-size"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+
+        // The base state was checked against the WAL header before replay
+        let Some(commit) = ctx.commit else {
+            return Ok(issues);
+        };
+        if !commit.frames.iter().any(|f| f.header.page_number == 1) {
+            return Ok(issues);
+        }
+
+        let page_size = ctx.page_cache.page_size();
+        let raw = BigEndian::read_u16(&ctx.page_cache.get_page_ref(1)?[16..18]);
+        // A value of 1 means 65536
+        let declared = if raw == 1 { 65536 } else { raw as u32 };
+        if declared != page_size {
+            issues.push(ValidationIssue::new(
+                self.name(),
+                Severity::Error,
+                format!(
+                    "Page 1 declares a page size of {}, but the database and WAL use {}-byte \
+                     pages",
+                    declared, page_size
+                ),
+                IssueLocation::Page { page_number: 1 },
+                ctx.commit_index,
+            ));
+        }
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
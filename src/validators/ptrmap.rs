@@ -0,0 +1,187 @@
+//! Validator for the pointer-map pages of auto-vacuum databases.
+//!
+//! Each pointer-map entry names the page that links to a page: the parent
+//! B-tree page of a child, the B-tree page whose cell starts an overflow
+//! chain, or the previous page of the chain. Auto-vacuum trusts these entries
+//! when it relocates pages, so an entry whose parent doesn't actually link to
+//! the page would make SQLite rewrite the wrong pointer.
+//!
+//! Databases without auto-vacuum have no pointer-map pages and are skipped.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use byteorder::{BigEndian, ByteOrder};
+
+use crate::btree::{BTreePageHeader, PtrmapEntry, PtrmapLayout, PtrmapPage, PtrmapType};
+use crate::db::DbHeader;
+use crate::error::Result;
+use crate::validator::PageCache;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that checks pointer-map entries against the pages they name.
+pub struct PtrmapValidator;
+
+impl PtrmapValidator {
+    /// Create a new pointer-map validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PtrmapValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for PtrmapValidator {
+    fn name(&self) -> &'static str {
+        "ptrmap"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        let header = DbHeader::parse(ctx.page_cache.get_page_ref(1)?)?;
+        let Some(layout) = PtrmapLayout::from_header(&header) else {
+            return Ok(issues);
+        };
+        let usable_size = header.usable_size() as usize;
+        let page_count = ctx.page_cache.effective_page_count();
+
+        // Links out of each parent page, gathered once per page
+        let mut links: HashMap<u32, PageLinks> = HashMap::new();
+
+        for ptrmap_page in layout.ptrmap_pages(page_count) {
+            let data = ctx.page_cache.get_page_ref(ptrmap_page)?;
+            let ptrmap = PtrmapPage::parse(data, ptrmap_page, &layout, page_count);
+
+            for entry in &ptrmap.entries {
+                let Some(problem) = check_entry(entry, ctx.page_cache, &mut links, usable_size)?
+                else {
+                    continue;
+                };
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    format!(
+                        "Pointer-map page {} entry for page {} {}",
+                        ptrmap_page, entry.page_number, problem
+                    ),
+                    IssueLocation::Page {
+                        page_number: entry.page_number,
+                    },
+                    commit_index,
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
+
+/// Pages a page links to, as far as pointer-map entries are concerned
+#[derive(Default)]
+struct PageLinks {
+    /// Child pages, if it is an interior B-tree page
+    children: HashSet<u32>,
+    /// First overflow pages of its cells, if it is a B-tree page
+    overflows: HashSet<u32>,
+    /// Next page of the chain, if it is an overflow page
+    next_overflow: u32,
+}
+
+impl PageLinks {
+    /// Gather the links out of a page, reading it as both a B-tree page and
+    /// an overflow page since the entry type says which one it should be
+    fn read(page_cache: &mut PageCache, page_num: u32, usable_size: usize) -> Result<Self> {
+        let data = page_cache.get_page_ref(page_num)?;
+        let mut links = PageLinks {
+            next_overflow: data.get(..4).map_or(0, BigEndian::read_u32),
+            ..PageLinks::default()
+        };
+
+        let Ok((header, _)) = BTreePageHeader::parse(data, page_num) else {
+            return Ok(links);
+        };
+        let Ok(cell_pointers) = header.get_cell_pointers(data, page_num) else {
+            return Ok(links);
+        };
+        for cell_ptr in cell_pointers {
+            let cell = data.get(cell_ptr as usize..).unwrap_or_default();
+            if header.page_type.is_interior() && cell.len() >= 4 {
+                links.children.insert(BigEndian::read_u32(cell));
+            }
+            if let Ok(Some(overflow)) = header.page_type.overflow_page(cell, usable_size) {
+                links.overflows.insert(overflow);
+            }
+        }
+        links.children.extend(header.right_child);
+
+        Ok(links)
+    }
+}
+
+/// Describe what is wrong with a pointer-map entry, if anything
+fn check_entry(
+    entry: &PtrmapEntry,
+    page_cache: &mut PageCache,
+    links: &mut HashMap<u32, PageLinks>,
+    usable_size: usize,
+) -> Result<Option<String>> {
+    let Some(kind) = entry.kind() else {
+        return Ok(Some(format!("has invalid type {}", entry.entry_type)));
+    };
+
+    let role = match kind {
+        PtrmapType::RootPage | PtrmapType::FreePage => {
+            if entry.parent == 0 {
+                return Ok(None);
+            }
+            let what = if kind == PtrmapType::RootPage {
+                "a root page"
+            } else {
+                "a free page"
+            };
+            return Ok(Some(format!(
+                "marks it as {} but names parent page {} (expected 0)",
+                what, entry.parent
+            )));
+        }
+        PtrmapType::Overflow1 => "the first overflow page of a cell on",
+        PtrmapType::Overflow2 => "the overflow page following",
+        PtrmapType::BTree => "a child of",
+    };
+
+    if entry.parent == 0 || entry.parent == entry.page_number {
+        return Ok(Some(format!("marks it as {} page {}", role, entry.parent)));
+    }
+
+    let parent = match links.entry(entry.parent) {
+        Entry::Occupied(occupied) => occupied.into_mut(),
+        Entry::Vacant(vacant) => {
+            vacant.insert(PageLinks::read(page_cache, entry.parent, usable_size)?)
+        }
+    };
+
+    let linked = match kind {
+        PtrmapType::Overflow1 => parent.overflows.contains(&entry.page_number),
+        PtrmapType::Overflow2 => parent.next_overflow == entry.page_number,
+        _ => parent.children.contains(&entry.page_number),
+    };
+    if linked {
+        return Ok(None);
+    }
+
+    Ok(Some(format!(
+        "marks it as {} page {}, but page {} doesn't link to it",
+        role, entry.parent, entry.parent
+    )))
+}
@@ -0,0 +1,77 @@
+//! Validator for pages written more than once by one WAL commit.
+//!
+//! Since version 3.11, SQLite rewrites a frame in place when a transaction
+//! changes a page it already spilled to the WAL, so each page appears at most
+//! once per commit. A commit with two frames for the same page points to a
+//! writer that doesn't follow that protocol. Readers use the last frame, which
+//! is also what the page overlay does.
+//!
+//! Back-to-back frames with identical data are left to `duplicate-frame`, so
+//! that one defect is reported once.
+
+use std::collections::HashMap;
+
+use crate::error::Result;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that flags commits holding several frames for one page.
+pub struct RepeatedPageValidator;
+
+impl RepeatedPageValidator {
+    /// Create a new repeated page validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RepeatedPageValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for RepeatedPageValidator {
+    fn name(&self) -> &'static str {
+        "repeated-page"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+
+        // Only WAL commits have frames
+        let Some(commit) = ctx.commit else {
+            return Ok(issues);
+        };
+
+        // Position in the commit of the latest frame seen for each page
+        let mut latest: HashMap<u32, usize> = HashMap::new();
+        for (position, frame) in commit.frames.iter().enumerate() {
+            let page_number = frame.header.page_number;
+            let Some(previous) = latest.insert(page_number, position) else {
+                continue;
+            };
+            if previous + 1 == position && commit.frames[previous].page_data == frame.page_data {
+                continue;
+            }
+            let previous = commit.frames[previous].frame_index;
+            issues.push(ValidationIssue::new(
+                self.name(),
+                Severity::Warning,
+                format!(
+                    "Page {} is written by frames {} and {} of the same commit; the later \
+                     frame wins",
+                    page_number, previous, frame.frame_index
+                ),
+                IssueLocation::Page { page_number },
+                ctx.commit_index,
+            ));
+        }
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,151 @@
+//! Validator for checking root page assignments in sqlite_master.
+//!
+//! Every table and index owns its own B-tree, so no two schema objects may
+//! claim the same root page. When they do, per-table checks become
+//! meaningless because both objects are reading the same tree.
+//!
+//! The root page must also be the kind of B-tree its object declares: a table
+//! root is a table page and an index root is an index page. WITHOUT ROWID
+//! tables are stored as index B-trees, so their roots are index pages.
+//!
+//! A root page past the end of the database is reported here rather than
+//! surfacing later as a parse failure of a zero-filled page.
+
+use std::collections::BTreeMap;
+
+use crate::btree::{BTreeInfo, BTreePageHeader};
+use crate::error::Result;
+use crate::sql::is_without_rowid;
+
+use super::{IssueLocation, Severity, ValidationContext, ValidationIssue, Validator};
+
+/// Validator that checks the root pages declared in sqlite_master.
+pub struct RootPageValidator;
+
+impl RootPageValidator {
+    /// Create a new root page validator.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RootPageValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for RootPageValidator {
+    fn name(&self) -> &'static str {
+        "root-page"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+
+        // Discover all B-trees, including the out-of-range ones the cached
+        // discovery leaves out
+        let btrees = ctx.scanner().discover_btrees()?;
+        let page_count = ctx.page_cache.effective_page_count();
+
+        // Group objects by the root page they claim
+        let mut by_root: BTreeMap<u32, Vec<&BTreeInfo>> = BTreeMap::new();
+        for btree in &btrees {
+            by_root.entry(btree.root_page).or_default().push(btree);
+        }
+
+        for (root_page, objects) in by_root {
+            let Some((first, others)) = objects.split_first() else {
+                continue;
+            };
+
+            for other in others {
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    format!(
+                        "objects '{}' and '{}' both claim root page {}",
+                        first.name.as_deref().unwrap_or("<unknown>"),
+                        other.name.as_deref().unwrap_or("<unknown>"),
+                        root_page
+                    ),
+                    IssueLocation::Page {
+                        page_number: root_page,
+                    },
+                    commit_index,
+                ));
+            }
+        }
+
+        // Check each root page exists and holds the declared kind of B-tree
+        for btree in &btrees {
+            if btree.root_page > page_count {
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    format!(
+                        "object '{}' has out-of-range root page {} (database has {} page(s))",
+                        btree.name.as_deref().unwrap_or("<unknown>"),
+                        btree.root_page,
+                        page_count
+                    ),
+                    IssueLocation::Page {
+                        page_number: btree.root_page,
+                    },
+                    commit_index,
+                ));
+                continue;
+            }
+
+            let Ok(page_data) = ctx.page_cache.get_page_ref(btree.root_page) else {
+                continue;
+            };
+            let Ok((header, _)) = BTreePageHeader::parse(page_data, btree.root_page) else {
+                continue;
+            };
+
+            let without_rowid =
+                btree.is_table && btree.sql.as_deref().is_some_and(is_without_rowid);
+            let expects_table = btree.is_table && !without_rowid;
+            if header.page_type.is_table() != expects_table {
+                let name = btree.name.as_deref().unwrap_or("<unknown>");
+                // A WITHOUT ROWID table is stored as an index B-tree
+                let message = if without_rowid {
+                    format!(
+                        "'{}' is a WITHOUT ROWID table but its root page {} is a table (rowid) \
+                         page, not an index page",
+                        name, btree.root_page
+                    )
+                } else {
+                    format!(
+                        "'{}' is declared as {} but its root page {} is {}",
+                        name,
+                        if btree.is_table { "a table" } else { "an index" },
+                        btree.root_page,
+                        if header.page_type.is_table() {
+                            "a table page"
+                        } else {
+                            "an index page"
+                        }
+                    )
+                };
+                issues.push(ValidationIssue::new(
+                    self.name(),
+                    Severity::Error,
+                    message,
+                    IssueLocation::Page {
+                        page_number: btree.root_page,
+                    },
+                    commit_index,
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+
+    fn checks_structure(&self) -> bool {
+        true
+    }
+}
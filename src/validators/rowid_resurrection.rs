@@ -0,0 +1,151 @@
+//! Validator for rowids that come back after being deleted.
+//!
+//! The rowids of each table are compared with those of the previous state
+//! checked: a rowid that is gone is remembered as deleted by that commit,
+//! and reported if a later commit brings it back. A lost delete, a stale
+//! page written back by a faulty replication or merge, or a WAL replayed
+//! out of order all show up this way, as "ghost" rows. So does an
+//! application re-inserting a row under an explicit rowid, or SQLite
+//! reusing the largest rowid after it was deleted, which is why this is
+//! informational and opt-in.
+//!
+//! Tables are tracked by name and forgotten when dropped; a table whose
+//! root page moves (DROP and CREATE, or VACUUM) starts over, since its
+//! rowids may belong to different rows. WITHOUT ROWID tables are skipped.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::error::Result;
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that reports rowids reappearing after an earlier commit
+/// deleted them.
+pub struct RowidResurrectionValidator {
+    /// Rowids of each table in the most recent state checked, by lowercase
+    /// table name
+    tables: HashMap<String, TableRowids>,
+}
+
+/// What is known of one table's rowids
+struct TableRowids {
+    /// Root page when the rowids were collected
+    root_page: u32,
+    /// Rowids present in the most recent state checked
+    live: HashSet<i64>,
+    /// Rowids gone since, with the commit they disappeared in
+    deleted: HashMap<i64, u64>,
+}
+
+impl RowidResurrectionValidator {
+    /// Create a new rowid resurrection validator.
+    pub fn new() -> Self {
+        Self {
+            tables: HashMap::new(),
+        }
+    }
+}
+
+impl Default for RowidResurrectionValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for RowidResurrectionValidator {
+    fn name(&self) -> &'static str {
+        "rowid-resurrection"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+        let commit_index = ctx.commit_index;
+        let config = ctx.config;
+
+        let schema = ctx.schema()?;
+        let mut scanner = ctx.scanner();
+        let mut tables = HashMap::new();
+
+        for table in schema.tables() {
+            if table.without_rowid {
+                continue;
+            }
+            let Some(name) = table.name() else {
+                continue;
+            };
+            let root_page = table.btree.root_page;
+            let live: HashSet<i64> = scanner
+                .collect_table_rowids(root_page)?
+                .into_iter()
+                .map(|(rowid, _)| rowid)
+                .collect();
+
+            let previous = self
+                .tables
+                .remove(&name.to_lowercase())
+                .filter(|previous| previous.root_page == root_page);
+            let mut deleted = HashMap::new();
+            if let Some(previous) = previous
+                && let Some(commit) = commit_index
+            {
+                deleted = previous.deleted;
+                let mut resurrected: Vec<(i64, u64)> = live
+                    .iter()
+                    .filter_map(|rowid| Some((*rowid, deleted.remove(rowid)?)))
+                    .collect();
+                resurrected.sort_unstable();
+                deleted.extend(previous.live.difference(&live).map(|&r| (r, commit)));
+
+                if !resurrected.is_empty() {
+                    let listed: Vec<String> = resurrected
+                        .iter()
+                        .map(|(rowid, deleted_in)| {
+                            format!("{} (deleted in commit {})", rowid, deleted_in)
+                        })
+                        .collect();
+                    issues.push(ValidationIssue::new(
+                        self.name(),
+                        Severity::Info,
+                        format!(
+                            "{} rowid(s) deleted by an earlier commit reappeared: {}",
+                            resurrected.len(),
+                            config.format_listed_values(&listed)
+                        ),
+                        IssueLocation::Table {
+                            name: Some(name.to_string()),
+                            root_page,
+                        },
+                        commit_index,
+                    ));
+                }
+            }
+
+            let rowids = TableRowids {
+                root_page,
+                live,
+                deleted,
+            };
+            tables.insert(name.to_lowercase(), rowids);
+        }
+
+        // Tables missing from this state were dropped
+        self.tables = tables;
+        Ok(issues)
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.check_resurrected_rows
+    }
+
+    fn depends_on(&self) -> &[&'static str] {
+        // Report structural damage before the rows it may hide or reveal
+        &["page-layout"]
+    }
+
+    fn compares_states(&self) -> bool {
+        // Rowids are compared with those of earlier states
+        true
+    }
+}
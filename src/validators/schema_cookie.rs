@@ -0,0 +1,78 @@
+//! Validator for tracking schema changes across WAL commits.
+//!
+//! The schema cookie at offset 40 of page 1 is incremented whenever a
+//! transaction changes the schema (CREATE, DROP or ALTER). Reporting the
+//! commits that change it shows where DDL happened in the WAL, which helps
+//! correlate later issues with migrations run by the application.
+
+use crate::db::DbHeader;
+use crate::error::Result;
+
+use super::{
+    IssueLocation, Severity, ValidationContext, ValidationIssue, Validator, ValidatorConfig,
+};
+
+/// Validator that reports the commits that change the schema cookie.
+///
+/// This is informational only and is enabled by `describe_commits`.
+pub struct SchemaCookieValidator {
+    /// Schema cookie of the most recent state checked
+    previous_cookie: Option<u32>,
+}
+
+impl SchemaCookieValidator {
+    /// Create a new schema cookie validator.
+    pub fn new() -> Self {
+        Self {
+            previous_cookie: None,
+        }
+    }
+}
+
+impl Default for SchemaCookieValidator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Validator for SchemaCookieValidator {
+    fn name(&self) -> &'static str {
+        "schema-cookie"
+    }
+
+    fn validate(&mut self, ctx: &mut ValidationContext) -> Result<Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+
+        // The cookie lives on page 1, so only commits that rewrite it can change it
+        if let Some(commit) = ctx.commit
+            && self.previous_cookie.is_some()
+            && !commit.frames.iter().any(|f| f.header.page_number == 1)
+        {
+            return Ok(issues);
+        }
+
+        let cookie = DbHeader::parse(ctx.page_cache.get_page_ref(1)?)?.schema_cookie;
+        if let Some(previous) = self.previous_cookie.replace(cookie)
+            && previous != cookie
+        {
+            issues.push(ValidationIssue::new(
+                self.name(),
+                Severity::Info,
+                format!("Schema cookie changed from {} to {}", previous, cookie),
+                IssueLocation::Database,
+                ctx.commit_index,
+            ));
+        }
+
+        Ok(issues)
+    }
+
+    fn is_enabled(&self, config: &ValidatorConfig) -> bool {
+        config.describe_commits
+    }
+
+    fn compares_states(&self) -> bool {
+        // The cookie is compared with the last one seen
+        true
+    }
+}
@@ -0,0 +1,155 @@
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+use crate::btree::BTreePageType;
+use crate::db::DbHeader;
+use crate::error::{Result, WalValidatorError};
+use crate::validator::PageCache;
+use crate::wal::CommitIterator;
+
+/// The pages one WAL commit changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitDiff {
+    /// Index of the commit
+    pub commit_index: u64,
+    /// Pages written by the commit, in ascending order and each listed once
+    pub changed_pages: Vec<u32>,
+    /// Database size in pages before the commit
+    pub old_page_count: u32,
+    /// Database size in pages after the commit
+    pub new_page_count: u32,
+    /// B-tree page type of each changed page before and after the commit,
+    /// in `changed_pages` order (empty unless requested)
+    pub page_types: Vec<PageTypeChange>,
+}
+
+/// B-tree page type of a page before and after a commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageTypeChange {
+    /// Page number
+    pub page_number: u32,
+    /// Type before the commit, or None if the page wasn't a B-tree page
+    /// (including when it was past the end of the database)
+    pub before: Option<BTreePageType>,
+    /// Type after the commit, or None if the page isn't a B-tree page
+    pub after: Option<BTreePageType>,
+}
+
+/// Iterator over the changes each WAL commit makes to a database, without
+/// validating them
+pub struct CommitDiffIterator<R = File> {
+    commits: CommitIterator<R>,
+    page_cache: PageCache,
+    page_types: bool,
+}
+
+impl CommitDiffIterator {
+    /// Create a diff iterator replaying a WAL over its database file
+    /// Returns None if the WAL file is empty (no commits)
+    pub fn new(db_path: &Path, wal_path: &Path) -> Result<Option<Self>> {
+        if !db_path.exists() {
+            return Err(WalValidatorError::DatabaseNotFound(db_path.to_path_buf()));
+        }
+
+        let db_header = DbHeader::from_file(db_path)?;
+        let Some(commits) = CommitIterator::new(wal_path)? else {
+            return Ok(None);
+        };
+        if commits.wal_header().page_size != db_header.page_size {
+            return Err(WalValidatorError::PageSizeMismatch {
+                db_size: db_header.page_size,
+                wal_size: commits.wal_header().page_size,
+            });
+        }
+
+        let page_cache = PageCache::new(db_path, db_header.page_size, db_header.page_count);
+        Ok(Some(Self::from_parts(commits, page_cache)))
+    }
+}
+
+impl<R: Read + Seek> CommitDiffIterator<R> {
+    /// Create a diff iterator applying commits over an existing page cache
+    pub fn from_parts(commits: CommitIterator<R>, page_cache: PageCache) -> Self {
+        CommitDiffIterator {
+            commits,
+            page_cache,
+            page_types: false,
+        }
+    }
+
+    /// Also classify each changed page by its B-tree page type.
+    ///
+    /// The type is read from the page's first header byte, so an overflow or
+    /// free page whose first bytes happen to match a page type is reported as
+    /// a B-tree page.
+    pub fn with_page_types(mut self) -> Self {
+        self.page_types = true;
+        self
+    }
+
+    /// The page state as of the last commit yielded
+    pub fn page_cache(&mut self) -> &mut PageCache {
+        &mut self.page_cache
+    }
+
+    /// B-tree page type of a page in the current state
+    fn page_type(&mut self, page_num: u32) -> Result<Option<BTreePageType>> {
+        if page_num > self.page_cache.effective_page_count() {
+            return Ok(None);
+        }
+        // Page 1 has the 100-byte database header before its B-tree header
+        let offset = if page_num == 1 { 100 } else { 0 };
+        let page = self.page_cache.get_page_ref(page_num)?;
+        Ok(page.get(offset).copied().and_then(BTreePageType::from_byte))
+    }
+}
+
+impl<R: Read + Seek> Iterator for CommitDiffIterator<R> {
+    type Item = Result<CommitDiff>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let commit = match self.commits.next()? {
+            Ok(commit) => commit,
+            Err(e) => return Some(Err(e)),
+        };
+
+        let mut changed_pages: Vec<u32> =
+            commit.frames.iter().map(|f| f.header.page_number).collect();
+        changed_pages.sort_unstable();
+        changed_pages.dedup();
+
+        let old_page_count = self.page_cache.effective_page_count();
+        let mut before = Vec::new();
+        if self.page_types {
+            for &page_num in &changed_pages {
+                match self.page_type(page_num) {
+                    Ok(page_type) => before.push(page_type),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+        }
+
+        self.page_cache.apply_commit(&commit);
+
+        let mut page_types = Vec::with_capacity(before.len());
+        for (&page_number, before) in changed_pages.iter().zip(before) {
+            match self.page_type(page_number) {
+                Ok(after) => page_types.push(PageTypeChange {
+                    page_number,
+                    before,
+                    after,
+                }),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        Some(Ok(CommitDiff {
+            commit_index: commit.index,
+            changed_pages,
+            old_page_count,
+            new_page_count: commit.db_size,
+            page_types,
+        }))
+    }
+}
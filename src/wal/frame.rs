@@ -1,8 +1,9 @@
 use byteorder::{BigEndian, ByteOrder};
 
 use crate::error::{Result, WalValidatorError};
+use crate::wal::WalHeader;
 
-/// WAL frame header (24 bytes)
+/// WAL frame header (24 bytes, as laid out by WAL format version 3007000)
 #[derive(Debug, Clone)]
 pub struct FrameHeader {
     /// Page number (1-indexed)
@@ -20,8 +21,12 @@ pub struct FrameHeader {
 }
 
 impl FrameHeader {
-    /// Parse a frame header from bytes
-    pub fn parse(data: &[u8]) -> Result<Self> {
+    /// Parse a frame header from bytes of a WAL with the given header.
+    ///
+    /// Fails with `UnsupportedWalVersion` unless the WAL's format version is
+    /// one whose frame layout is known.
+    pub fn parse(data: &[u8], wal_header: &WalHeader) -> Result<Self> {
+        wal_header.ensure_supported_version()?;
         if data.len() < 24 {
             return Err(WalValidatorError::UnexpectedEof);
         }
@@ -42,6 +47,17 @@ impl FrameHeader {
     }
 }
 
+/// Size of the WAL header that precedes the first frame
+const WAL_HEADER_SIZE: u64 = 32;
+
+/// Size of each frame's header, ahead of its page data
+const FRAME_HEADER_SIZE: u64 = 24;
+
+/// Byte offset of a frame within a WAL file of the given page size
+pub fn frame_offset(frame_index: u64, page_size: u32) -> u64 {
+    WAL_HEADER_SIZE + frame_index * (FRAME_HEADER_SIZE + page_size as u64)
+}
+
 /// A complete WAL frame with header and page data
 #[derive(Debug, Clone)]
 pub struct Frame {
@@ -51,4 +67,16 @@ pub struct Frame {
     pub page_data: Vec<u8>,
     /// Frame index in the WAL file (0-indexed)
     pub frame_index: u64,
+    /// Whether the frame's checksum was verified against the running chain.
+    /// False when verification is turned off, for the first frame of an older
+    /// generation, and for every frame after a skipped bad frame.
+    pub checksum_verified: bool,
+}
+
+impl Frame {
+    /// Byte offset of the frame, header first, within a WAL file of the
+    /// given page size
+    pub fn file_offset(&self, page_size: u32) -> u64 {
+        frame_offset(self.frame_index, page_size)
+    }
 }
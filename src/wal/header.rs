@@ -9,6 +9,8 @@ use crate::error::{Result, WalValidatorError};
 pub const WAL_MAGIC_BE: u32 = 0x377f0682;
 /// WAL magic number for little-endian checksums
 pub const WAL_MAGIC_LE: u32 = 0x377f0683;
+/// The only WAL format version, whose frame layout this crate parses
+pub const WAL_FORMAT_VERSION: u32 = 3007000;
 
 /// SQLite WAL file header (32 bytes)
 #[derive(Debug, Clone)]
@@ -80,6 +82,22 @@ impl WalHeader {
         })
     }
 
+    /// Fail unless the frames that follow use the layout we know how to parse
+    pub fn ensure_supported_version(&self) -> Result<()> {
+        if self.format_version != WAL_FORMAT_VERSION {
+            return Err(WalValidatorError::UnsupportedWalVersion(self.format_version));
+        }
+        Ok(())
+    }
+
+    /// A copy of this header that computes checksums with the other byte order
+    pub fn with_opposite_endianness(&self) -> Self {
+        WalHeader {
+            big_endian_checksums: !self.big_endian_checksums,
+            ..self.clone()
+        }
+    }
+
     /// Calculate the WAL checksum for a block of data
     /// Returns (checksum1, checksum2)
     pub fn checksum(&self, data: &[u8], initial: (u32, u32)) -> (u32, u32) {
@@ -0,0 +1,42 @@
+use std::path::Path;
+
+use crate::error::Result;
+use crate::wal::CommitIterator;
+
+/// One write of a page by a WAL frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageWrite {
+    /// Index of the commit the frame belongs to
+    pub commit_index: u64,
+    /// Frame index in the WAL file (0-indexed)
+    pub frame_index: u64,
+    /// True if the frame is the one that ends its commit
+    pub is_commit_frame: bool,
+}
+
+/// List every committed frame in a WAL that writes the given page, in order.
+///
+/// Frames of an incomplete trailing transaction are not part of any commit
+/// and are left out.
+pub fn page_history(wal_path: &Path, page_number: u32) -> Result<Vec<PageWrite>> {
+    let mut writes = Vec::new();
+
+    let Some(commit_iter) = CommitIterator::new(wal_path)? else {
+        return Ok(writes);
+    };
+
+    for commit in commit_iter {
+        let commit = commit?;
+        for frame in &commit.frames {
+            if frame.header.page_number == page_number {
+                writes.push(PageWrite {
+                    commit_index: commit.index,
+                    frame_index: frame.frame_index,
+                    is_commit_frame: frame.header.is_commit(),
+                });
+            }
+        }
+    }
+
+    Ok(writes)
+}
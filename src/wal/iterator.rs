@@ -1,12 +1,12 @@
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 
 use crate::error::{Result, WalValidatorError};
-use crate::wal::{Frame, FrameHeader, WalHeader};
+use crate::wal::{frame, Frame, FrameHeader, WalHeader};
 
 /// A commit consisting of one or more frames
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Commit {
     /// Commit index (0-indexed sequence number)
     pub index: u64,
@@ -14,56 +14,176 @@ pub struct Commit {
     pub frames: Vec<Frame>,
     /// Database size after this commit
     pub db_size: u32,
+    /// Byte offset in the WAL file where this commit's first frame starts
+    pub start_offset: u64,
+    /// Byte offset in the WAL file just past this commit's final frame
+    pub end_offset: u64,
+    /// WAL generation the commit belongs to: 0 for the generation named by
+    /// the WAL header, then 1, 2, ... for older generations found past its
+    /// end (only yielded when `follow_generations` is set, and never for the
+    /// partly overwritten first commit of an older generation)
+    pub generation: u64,
 }
 
-/// Iterator that yields commits from a WAL file
-pub struct CommitIterator {
-    file: File,
+impl Commit {
+    /// Returns true if the checksum of every frame in the commit was verified
+    pub fn checksums_verified(&self) -> bool {
+        self.frames.iter().all(|f| f.checksum_verified)
+    }
+}
+
+/// What to do with a frame whose checksum doesn't verify
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BadFrameAction {
+    /// End the log at the frame, or fail with `ChecksumMismatch` if it is
+    /// part of a transaction
+    #[default]
+    Stop,
+    /// Leave the frame out and keep reading. The checksum chain is broken
+    /// from then on, so later frames are yielded unverified.
+    Skip,
+}
+
+/// Options for iterating over a WAL
+#[derive(Debug, Clone)]
+pub struct CommitIteratorOptions {
+    /// Keep iterating past a salt change instead of ending the log there.
+    ///
+    /// When a checkpoint restarts the WAL, new frames overwrite it from the
+    /// start under new salts, and frames of the previous generation remain
+    /// past the end of the new ones. Their chain can't be verified from the
+    /// first surviving frame (its predecessor was overwritten), so the
+    /// running checksum is re-seeded from that frame's own checksum and only
+    /// the frames after it are verified.
+    ///
+    /// The commit that frame belongs to may have lost its first frames to
+    /// the overwrite, so it is dropped rather than yielded as a whole commit.
+    pub follow_generations: bool,
+    /// Report frames of a transaction left unfinished at the end of the WAL
+    /// as an `IncompleteCommit` error instead of ignoring them
+    pub strict: bool,
+    /// Verify each frame's checksum against the running chain.
+    ///
+    /// Turning this off yields every frame unverified. The log then only ends
+    /// at a salt change or the end of the file, so stale frames that reuse
+    /// the current salts are yielded as well.
+    pub verify_checksums: bool,
+    /// What to do with a frame whose checksum doesn't verify
+    pub on_bad_frame: BadFrameAction,
+}
+
+impl Default for CommitIteratorOptions {
+    fn default() -> Self {
+        Self {
+            follow_generations: false,
+            strict: false,
+            verify_checksums: true,
+            on_bad_frame: BadFrameAction::Stop,
+        }
+    }
+}
+
+/// State of the checksum chain before a frame, from which reading can resume
+#[derive(Debug, Clone, Copy)]
+struct ResumePoint {
+    frame_index: u64,
+    checksum: (u32, u32),
+    chain_broken: bool,
+    generation: u64,
+    salts: (u32, u32),
+}
+
+/// Size of the read buffer; frames are read in order, so a buffer several
+/// frames long turns most of them into a single read
+const READ_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Iterator that yields verified frames from a WAL file one at a time
+pub struct FrameIterator<R = File> {
+    reader: BufReader<R>,
+    /// Offset the reader is positioned at, or None after a failed read
+    position: Option<u64>,
     wal_header: WalHeader,
     page_size: u32,
     current_frame_index: u64,
-    current_commit_index: u64,
-    pending_frames: Vec<Frame>,
     current_checksum: (u32, u32),
     finished: bool,
+    /// Size of the WAL in bytes
+    wal_size: u64,
+    /// Frames past this count are treated as absent
+    max_frame: Option<u64>,
+    /// Keep reading past a salt change (see `CommitIteratorOptions`)
+    follow_generations: bool,
+    /// Generation of the frames being read
+    generation: u64,
+    /// Salts of the current generation
+    salts: (u32, u32),
+    /// True while the last frame read was not a commit frame
+    in_transaction: bool,
+    /// Verify frame checksums (see `CommitIteratorOptions`)
+    verify_checksums: bool,
+    /// Skip frames that fail verification instead of stopping
+    skip_bad_frames: bool,
+    /// True once a frame was skipped, leaving nothing to verify against
+    chain_broken: bool,
+    /// Frames skipped because their checksum didn't verify
+    skipped_frames: Vec<u64>,
 }
 
-impl CommitIterator {
-    /// Create a new commit iterator for a WAL file
-    /// Returns None if the WAL file is empty (no commits)
+impl FrameIterator {
+    /// Create a new frame iterator for a WAL file
+    /// Returns None if the WAL file is empty (no frames)
     pub fn new(path: &Path) -> Result<Option<Self>> {
-        let file_size = std::fs::metadata(path)?.len();
+        Self::from_reader(File::open(path)?)
+    }
+}
 
-        // Empty WAL file - no commits to process
-        if file_size == 0 {
+impl<R: Read + Seek> FrameIterator<R> {
+    /// Create a frame iterator over WAL bytes from any seekable reader
+    /// Returns None if the WAL is empty (no frames)
+    pub fn from_reader(mut reader: R) -> Result<Option<Self>> {
+        let wal_size = reader.seek(SeekFrom::End(0))?;
+
+        // Empty WAL file - no frames to process
+        if wal_size == 0 {
             return Ok(None);
         }
 
         // WAL header is 32 bytes minimum
-        if file_size < 32 {
+        if wal_size < 32 {
             return Err(crate::error::WalValidatorError::UnexpectedEof);
         }
 
-        let mut file = File::open(path)?;
-
         // Read and parse WAL header
         let mut header_bytes = [0u8; 32];
-        file.read_exact(&mut header_bytes)?;
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut header_bytes)?;
         let wal_header = WalHeader::parse(&header_bytes)?;
 
+        // Frames of an unknown format version can't be parsed reliably
+        wal_header.ensure_supported_version()?;
+
         // Initial checksum for frames uses the header checksums (bytes 24-31),
         // NOT the salt values
         let initial_checksum = (wal_header.checksum1, wal_header.checksum2);
 
-        Ok(Some(CommitIterator {
-            file,
+        Ok(Some(FrameIterator {
+            reader: BufReader::with_capacity(READ_BUFFER_SIZE, reader),
+            position: Some(32),
             page_size: wal_header.page_size,
+            salts: (wal_header.salt1, wal_header.salt2),
             wal_header,
             current_frame_index: 0,
-            current_commit_index: 0,
-            pending_frames: Vec::new(),
             current_checksum: initial_checksum,
             finished: false,
+            wal_size,
+            max_frame: None,
+            follow_generations: false,
+            generation: 0,
+            in_transaction: false,
+            verify_checksums: true,
+            skip_bad_frames: false,
+            chain_broken: false,
+            skipped_frames: Vec::new(),
         }))
     }
 
@@ -72,69 +192,452 @@ impl CommitIterator {
         &self.wal_header
     }
 
-    /// Try to read the next frame from the WAL file
+    /// Stop after the first `max_frame` frames, as if the rest of the WAL
+    /// were absent. Used to honor the mxFrame of a `-shm` WAL-index.
+    pub fn with_max_frame(mut self, max_frame: u64) -> Self {
+        self.max_frame = Some(max_frame);
+        self
+    }
+
+    /// Drop the limit set by `with_max_frame`, returning true if iteration
+    /// had stopped there and now continues past it
+    fn lift_max_frame(&mut self) -> bool {
+        let at_limit = self
+            .max_frame
+            .take()
+            .is_some_and(|max| self.finished && self.current_frame_index >= max);
+        if at_limit {
+            self.finished = false;
+        }
+        at_limit
+    }
+
+    /// Keep reading frames of older WAL generations past a salt change
+    pub fn following_generations(mut self) -> Self {
+        self.follow_generations = true;
+        self
+    }
+
+    /// Yield frames without verifying their checksums
+    pub fn without_checksum_verification(mut self) -> Self {
+        self.verify_checksums = false;
+        self
+    }
+
+    /// Skip frames whose checksum doesn't verify instead of stopping there
+    pub fn skipping_bad_frames(mut self) -> Self {
+        self.skip_bad_frames = true;
+        self
+    }
+
+    /// Frames skipped so far because their checksum didn't verify
+    pub fn skipped_frames(&self) -> &[u64] {
+        &self.skipped_frames
+    }
+
+    /// Number of whole frames in the WAL, whether or not they are valid
+    pub fn frames_in_file(&self) -> u64 {
+        self.wal_size.saturating_sub(32) / (24 + self.page_size as u64)
+    }
+
+    /// Running checksum the next frame must chain from: the checksum of the
+    /// last frame read, or of the WAL header before the first frame. When a
+    /// frame fails verification, this is the value it was checked against.
+    pub fn running_checksum(&self) -> (u32, u32) {
+        self.current_checksum
+    }
+
+    /// Generation of the last frame read (see `Commit::generation`)
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Index of the next frame to be read
+    pub fn next_frame_index(&self) -> u64 {
+        self.current_frame_index
+    }
+
+    /// Where reading would resume from now
+    fn resume_point(&self) -> ResumePoint {
+        ResumePoint {
+            frame_index: self.current_frame_index,
+            checksum: self.current_checksum,
+            chain_broken: self.chain_broken,
+            generation: self.generation,
+            salts: self.salts,
+        }
+    }
+
+    /// Where reading starts: the first frame, chaining from the header
+    fn start_point(&self) -> ResumePoint {
+        ResumePoint {
+            frame_index: 0,
+            checksum: (self.wal_header.checksum1, self.wal_header.checksum2),
+            chain_broken: false,
+            generation: 0,
+            salts: (self.wal_header.salt1, self.wal_header.salt2),
+        }
+    }
+
+    /// Continue reading from an earlier resume point, outside any transaction
+    fn resume_at(&mut self, point: ResumePoint) {
+        self.current_frame_index = point.frame_index;
+        self.current_checksum = point.checksum;
+        self.chain_broken = point.chain_broken;
+        self.generation = point.generation;
+        self.salts = point.salts;
+        self.in_transaction = false;
+        // Frames past the point will be read, and skipped, again
+        self.skipped_frames.retain(|&index| index < point.frame_index);
+        self.finished = false;
+    }
+
+    /// Byte offset of a frame within the WAL file
+    fn frame_offset(&self, frame_index: u64) -> u64 {
+        frame::frame_offset(frame_index, self.page_size)
+    }
+
+    /// Move the reader to a byte offset, seeking only if it isn't there
+    /// already. Frames are read in order, so this is normally a no-op and
+    /// the buffered reader can serve them sequentially.
+    fn position_at(&mut self, offset: u64) -> std::io::Result<()> {
+        if self.position != Some(offset) {
+            self.position = None;
+            self.reader.seek(SeekFrom::Start(offset))?;
+            self.position = Some(offset);
+        }
+        Ok(())
+    }
+
+    /// Fill a buffer from the current position, keeping track of where the
+    /// reader ends up
+    fn read_bytes(&mut self, buffer: &mut [u8]) -> std::io::Result<()> {
+        let start = self.position.take();
+        self.reader.read_exact(buffer)?;
+        self.position = start.map(|start| start + buffer.len() as u64);
+        Ok(())
+    }
+
+    /// Try to read the next frame from the WAL file, skipping bad frames
+    /// when asked to
     fn read_frame(&mut self) -> Result<Option<Frame>> {
-        // Calculate frame offset: header (32 bytes) + frame_index * (24 + page_size)
-        let frame_size = 24 + self.page_size as u64;
-        let offset = 32 + self.current_frame_index * frame_size;
+        loop {
+            match self.read_frame_at_index()? {
+                FrameRead::Frame(frame) => return Ok(Some(frame)),
+                FrameRead::End => return Ok(None),
+                FrameRead::Skipped => {
+                    self.skipped_frames.push(self.current_frame_index);
+                    self.chain_broken = true;
+                    self.current_frame_index += 1;
+                }
+            }
+        }
+    }
 
-        // Seek to frame position
-        if self.file.seek(SeekFrom::Start(offset)).is_err() {
-            return Ok(None);
+    /// Try to read the frame at the current index
+    fn read_frame_at_index(&mut self) -> Result<FrameRead> {
+        if self.max_frame.is_some_and(|max| self.current_frame_index >= max) {
+            return Ok(FrameRead::End);
         }
 
+        let offset = self.frame_offset(self.current_frame_index);
+
+        // Move to the frame position; seeking past the end is allowed, and
+        // the read below reports it
+        self.position_at(offset)?;
+
         // Read frame header (24 bytes)
         let mut header_bytes = [0u8; 24];
-        match self.file.read_exact(&mut header_bytes) {
+        match self.read_bytes(&mut header_bytes) {
             Ok(_) => {}
-            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(FrameRead::End),
             Err(e) => return Err(e.into()),
         }
 
-        let frame_header = FrameHeader::parse(&header_bytes)?;
+        let frame_header = FrameHeader::parse(&header_bytes, &self.wal_header)?;
 
-        // Validate salt values match WAL header
-        if frame_header.salt1 != self.wal_header.salt1
-            || frame_header.salt2 != self.wal_header.salt2
-        {
-            // Salt mismatch indicates end of valid frames or corruption
-            return Ok(None);
+        // Validate salt values match the current generation
+        let mut new_generation = false;
+        if (frame_header.salt1, frame_header.salt2) != self.salts {
+            // Salt mismatch indicates end of valid frames or corruption, unless
+            // older generations are followed. Page 0 doesn't exist, so a frame
+            // naming it is unused space rather than an older generation.
+            if !self.follow_generations || frame_header.page_number == 0 {
+                return Ok(FrameRead::End);
+            }
+            new_generation = true;
         }
 
         // Read page data
         let mut page_data = vec![0u8; self.page_size as usize];
-        match self.file.read_exact(&mut page_data) {
+        match self.read_bytes(&mut page_data) {
             Ok(_) => {}
-            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(FrameRead::End),
             Err(e) => return Err(e.into()),
         }
 
-        // Verify checksum
-        let checksum = self.wal_header.checksum(&header_bytes[0..8], self.current_checksum);
-        let checksum = self.wal_header.checksum(&page_data, checksum);
+        // The first surviving frame of an older generation can't be verified:
+        // the frames it chained from were overwritten. Its own checksum seeds
+        // the chain for the frames after it.
+        let mut checksum_verified = false;
+        if new_generation {
+            self.generation += 1;
+            self.salts = (frame_header.salt1, frame_header.salt2);
+            self.current_checksum = (frame_header.checksum1, frame_header.checksum2);
+        } else if self.verify_checksums && !self.chain_broken {
+            // Verify checksum
+            let checksum = self.wal_header.checksum(&header_bytes[0..8], self.current_checksum);
+            let checksum = self.wal_header.checksum(&page_data, checksum);
 
-        if checksum.0 != frame_header.checksum1 || checksum.1 != frame_header.checksum2 {
-            return Err(WalValidatorError::ChecksumMismatch {
-                frame_index: self.current_frame_index,
-            });
-        }
+            if checksum.0 != frame_header.checksum1 || checksum.1 != frame_header.checksum2 {
+                // A frame that chains under the other byte order points at a WAL
+                // whose magic number doesn't match the writer's checksums
+                let opposite = self.wal_header.with_opposite_endianness();
+                let checksum = opposite.checksum(&header_bytes[0..8], self.current_checksum);
+                let checksum = opposite.checksum(&page_data, checksum);
+                if checksum.0 == frame_header.checksum1 && checksum.1 == frame_header.checksum2 {
+                    return Err(WalValidatorError::OppositeEndianChecksum {
+                        frame_index: self.current_frame_index,
+                    });
+                }
+
+                if self.skip_bad_frames {
+                    return Ok(FrameRead::Skipped);
+                }
+
+                // Matching salts don't prove a frame belongs to this generation: a
+                // stale frame from an earlier generation that reused the salts won't
+                // chain from the last commit, which is where the valid log ends
+                if !self.in_transaction {
+                    return Ok(FrameRead::End);
+                }
 
-        // Update running checksum
-        self.current_checksum = checksum;
+                // Inside a transaction, a broken chain is corruption
+                return Err(WalValidatorError::ChecksumMismatch {
+                    frame_index: self.current_frame_index,
+                });
+            }
+
+            // Update running checksum
+            self.current_checksum = checksum;
+            checksum_verified = true;
+        }
 
+        self.in_transaction = !frame_header.is_commit();
         let frame = Frame {
             header: frame_header,
             page_data,
             frame_index: self.current_frame_index,
+            checksum_verified,
         };
 
         self.current_frame_index += 1;
 
-        Ok(Some(frame))
+        Ok(FrameRead::Frame(frame))
     }
 }
 
-impl Iterator for CommitIterator {
+/// Outcome of reading the frame at one index
+enum FrameRead {
+    /// A frame to yield
+    Frame(Frame),
+    /// A frame whose checksum didn't verify, to be skipped
+    Skipped,
+    /// The end of the valid log
+    End,
+}
+
+impl<R: Read + Seek> Iterator for FrameIterator<R> {
+    type Item = Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        let result = self.read_frame().transpose();
+        if !matches!(result, Some(Ok(_))) {
+            self.finished = true;
+        }
+        result
+    }
+}
+
+/// Iterator that yields commits from a WAL file, or any seekable source of WAL bytes
+pub struct CommitIterator<R = File> {
+    frames: FrameIterator<R>,
+    current_commit_index: u64,
+    pending_frames: Vec<Frame>,
+    finished: bool,
+    options: CommitIteratorOptions,
+    /// Generation of the frames in `pending_frames`
+    pending_generation: u64,
+    /// Set while reading the first commit of an older generation, which is
+    /// dropped since its earlier frames may have been overwritten
+    in_fragment: bool,
+    /// Frames after the last commit frame, once the end of the WAL is reached
+    incomplete_tail: Vec<Frame>,
+    /// First frame of each commit, and where to resume reading to yield it,
+    /// once `build_commit_index` has run
+    commit_index: Option<Vec<(u64, ResumePoint)>>,
+}
+
+impl CommitIterator {
+    /// Create a new commit iterator for a WAL file
+    /// Returns None if the WAL file is empty (no commits)
+    pub fn new(path: &Path) -> Result<Option<Self>> {
+        Self::from_reader(File::open(path)?)
+    }
+
+    /// Create a commit iterator for a WAL file with non-default options
+    /// Returns None if the WAL file is empty (no commits)
+    pub fn new_with_options(path: &Path, options: CommitIteratorOptions) -> Result<Option<Self>> {
+        Self::from_reader_with_options(File::open(path)?, options)
+    }
+}
+
+impl<R: Read + Seek> CommitIterator<R> {
+    /// Create a commit iterator over WAL bytes from any seekable reader
+    /// Returns None if the WAL is empty (no commits)
+    pub fn from_reader(reader: R) -> Result<Option<Self>> {
+        Self::from_reader_with_options(reader, CommitIteratorOptions::default())
+    }
+
+    /// Create a commit iterator over WAL bytes with non-default options
+    /// Returns None if the WAL is empty (no commits)
+    pub fn from_reader_with_options(
+        reader: R,
+        options: CommitIteratorOptions,
+    ) -> Result<Option<Self>> {
+        let Some(mut frames) = FrameIterator::from_reader(reader)? else {
+            return Ok(None);
+        };
+        if options.follow_generations {
+            frames = frames.following_generations();
+        }
+        if !options.verify_checksums {
+            frames = frames.without_checksum_verification();
+        }
+        if options.on_bad_frame == BadFrameAction::Skip {
+            frames = frames.skipping_bad_frames();
+        }
+
+        Ok(Some(CommitIterator {
+            frames,
+            current_commit_index: 0,
+            pending_frames: Vec::new(),
+            finished: false,
+            options,
+            pending_generation: 0,
+            in_fragment: false,
+            incomplete_tail: Vec::new(),
+            commit_index: None,
+        }))
+    }
+
+    /// Get a reference to the WAL header
+    pub fn wal_header(&self) -> &WalHeader {
+        self.frames.wal_header()
+    }
+
+    /// Stop after the first `max_frame` frames, as if the rest of the WAL
+    /// were absent. Used to honor the mxFrame of a `-shm` WAL-index.
+    pub fn with_max_frame(mut self, max_frame: u64) -> Self {
+        self.frames = self.frames.with_max_frame(max_frame);
+        self
+    }
+
+    /// Drop the limit set by `with_max_frame`. If iteration stopped at the
+    /// limit, it continues with the frames past it, so the rest of the WAL
+    /// can be inspected without having been applied.
+    pub fn lift_max_frame(&mut self) {
+        if self.frames.lift_max_frame() && self.finished {
+            self.finished = false;
+            self.pending_frames = std::mem::take(&mut self.incomplete_tail);
+        }
+    }
+
+    /// Frames left out because their checksum didn't verify, when
+    /// `on_bad_frame` is `BadFrameAction::Skip`
+    pub fn skipped_frames(&self) -> &[u64] {
+        self.frames.skipped_frames()
+    }
+
+    /// Frames of a transaction left unfinished at the end of the WAL.
+    ///
+    /// These frames were never committed, so iteration doesn't yield them.
+    /// Empty until the iterator has reached the end of the WAL.
+    pub fn incomplete_tail(&self) -> &[Frame] {
+        &self.incomplete_tail
+    }
+
+    /// Number of whole frames in the WAL, whether or not they are valid
+    pub fn frames_in_file(&self) -> u64 {
+        self.frames.frames_in_file()
+    }
+
+    /// Index every commit in the WAL, returning the frame index of each
+    /// commit's first frame, and go back to the first commit.
+    ///
+    /// This reads and verifies every frame once, so it is O(n) in the size of
+    /// the WAL. The index is cached for `seek_to_commit`. Indexing ends at the
+    /// first corrupt frame, past which iteration can't go either.
+    pub fn build_commit_index(&mut self) -> Result<Vec<u64>> {
+        self.resume_at(0, self.frames.start_point());
+
+        let mut index = Vec::new();
+        loop {
+            let start = self.frames.resume_point();
+            match self.next() {
+                Some(Ok(commit)) => index.push((commit.frames[0].frame_index, start)),
+                None | Some(Err(WalValidatorError::IncompleteCommit { .. })) => break,
+                Some(Err(e @ WalValidatorError::Io(_))) => return Err(e),
+                Some(Err(_)) => break,
+            }
+        }
+
+        self.resume_at(0, self.frames.start_point());
+        let first_frames = index.iter().map(|&(first_frame, _)| first_frame).collect();
+        self.commit_index = Some(index);
+        Ok(first_frames)
+    }
+
+    /// Position the iterator so that the next commit it yields is commit `n`.
+    ///
+    /// The first seek builds the commit index (see `build_commit_index`),
+    /// which is O(n). Later seeks are O(1): frame checksums chain from one
+    /// frame to the next, and reading resumes from the checksum stored in the
+    /// frame before the commit, which was verified while indexing.
+    pub fn seek_to_commit(&mut self, n: u64) -> Result<()> {
+        if self.commit_index.is_none() {
+            self.build_commit_index()?;
+        }
+        let index = self.commit_index.as_deref().unwrap_or_default();
+        let Some(&(_, start)) = index.get(n as usize) else {
+            return Err(WalValidatorError::CommitNotFound {
+                commit_index: n,
+                commit_count: index.len() as u64,
+            });
+        };
+
+        self.resume_at(n, start);
+        Ok(())
+    }
+
+    /// Continue iterating from a resume point, numbering the next commit
+    /// `commit_index`
+    fn resume_at(&mut self, commit_index: u64, point: ResumePoint) {
+        self.frames.resume_at(point);
+        self.current_commit_index = commit_index;
+        self.pending_frames.clear();
+        self.pending_generation = point.generation;
+        self.in_fragment = false;
+        self.incomplete_tail.clear();
+        self.finished = false;
+    }
+}
+
+impl<R: Read + Seek> Iterator for CommitIterator<R> {
     type Item = Result<Commit>;
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -143,32 +646,62 @@ impl Iterator for CommitIterator {
         }
 
         loop {
-            match self.read_frame() {
-                Ok(Some(frame)) => {
+            match self.frames.next() {
+                Some(Ok(frame)) => {
+                    // A transaction left unfinished when the generation ended
+                    // is not part of either generation's log
+                    let generation = self.frames.generation();
+                    if generation != self.pending_generation {
+                        self.pending_frames.clear();
+                        self.pending_generation = generation;
+                        self.in_fragment = true;
+                    }
+
                     let is_commit = frame.header.is_commit();
                     let db_size = frame.header.db_size_after_commit;
                     self.pending_frames.push(frame);
 
+                    // What survives of the commit cut by the overwrite is not replayable
+                    if is_commit && self.in_fragment {
+                        self.pending_frames.clear();
+                        self.in_fragment = false;
+                        continue;
+                    }
+
                     if is_commit {
+                        let frames = std::mem::take(&mut self.pending_frames);
+                        let first_frame = frames[0].frame_index;
                         let commit = Commit {
                             index: self.current_commit_index,
-                            frames: std::mem::take(&mut self.pending_frames),
+                            start_offset: self.frames.frame_offset(first_frame),
+                            end_offset: self.frames.frame_offset(self.frames.next_frame_index()),
+                            frames,
                             db_size,
+                            generation,
                         };
                         self.current_commit_index += 1;
                         return Some(Ok(commit));
                     }
                 }
-                Ok(None) => {
-                    // EOF reached
+                None => {
+                    // EOF reached. Frames after the last commit frame belong to
+                    // a transaction that was in progress or aborted; they are
+                    // kept for incomplete_tail() rather than yielded
                     self.finished = true;
-                    if !self.pending_frames.is_empty() {
-                        // Incomplete commit - could be in-progress transaction
-                        // We'll silently ignore it rather than error
+                    if self.in_fragment {
+                        self.pending_frames.clear();
+                    }
+                    self.incomplete_tail = std::mem::take(&mut self.pending_frames);
+                    if self.options.strict
+                        && let Some(first) = self.incomplete_tail.first()
+                    {
+                        return Some(Err(WalValidatorError::IncompleteCommit {
+                            start_frame: first.frame_index,
+                        }));
                     }
                     return None;
                 }
-                Err(e) => {
+                Some(Err(e)) => {
                     self.finished = true;
                     return Some(Err(e));
                 }
@@ -1,7 +1,17 @@
+pub mod diff;
 pub mod frame;
 pub mod header;
+pub mod history;
 pub mod iterator;
+pub mod repair;
+pub mod shm;
 
-pub use frame::{Frame, FrameHeader};
+pub use diff::{CommitDiff, CommitDiffIterator, PageTypeChange};
+pub use frame::{frame_offset, Frame, FrameHeader};
 pub use header::WalHeader;
-pub use iterator::{Commit, CommitIterator};
+pub use history::{page_history, PageWrite};
+pub use iterator::{
+    BadFrameAction, Commit, CommitIterator, CommitIteratorOptions, FrameIterator,
+};
+pub use repair::{repair_checksums, RepairReport};
+pub use shm::ShmHeader;
@@ -0,0 +1,132 @@
+//! Parsing of the WAL-index header at the start of a `-shm` file.
+//!
+//! The WAL-index is shared memory, so its fields are in the byte order of
+//! the machine that wrote it. It starts with two copies of a 48-byte header
+//! (a writer updates one, then the other) followed by the checkpoint info.
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use crate::error::{Result, WalValidatorError};
+use crate::wal::header::WAL_FORMAT_VERSION;
+use crate::wal::WalHeader;
+
+/// Size of one copy of the WAL-index header
+const INDEX_HEADER_SIZE: usize = 48;
+
+/// Bytes needed to read both header copies and the backfill count
+const SHM_HEADER_SIZE: usize = 2 * INDEX_HEADER_SIZE + 4;
+
+/// WAL-index header of a `-shm` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShmHeader {
+    /// Number of valid, committed frames in the WAL
+    pub max_frame: u32,
+    /// Database size in pages after the last committed frame
+    pub page_count: u32,
+    /// Database page size
+    pub page_size: u32,
+    /// First salt of the WAL generation this index describes
+    pub salt1: u32,
+    /// Second salt of the WAL generation this index describes
+    pub salt2: u32,
+    /// Number of frames already copied back into the database
+    pub backfilled: u32,
+    /// Whether the header's checksum matches its contents
+    pub checksum_matches: bool,
+}
+
+impl ShmHeader {
+    /// Parse the WAL-index header from a `-shm` file
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+        let mut data = [0u8; SHM_HEADER_SIZE];
+        file.read_exact(&mut data).map_err(|e| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => WalValidatorError::InvalidShmHeader(
+                "file is too short to hold a WAL-index header".to_string(),
+            ),
+            _ => e.into(),
+        })?;
+        Self::parse(&data)
+    }
+
+    /// Parse the WAL-index header from bytes
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < SHM_HEADER_SIZE {
+            return Err(WalValidatorError::UnexpectedEof);
+        }
+
+        // The two copies only differ while a writer is updating them
+        let (first, second) = (&data[..INDEX_HEADER_SIZE], &data[INDEX_HEADER_SIZE..96]);
+        if first != second {
+            return Err(WalValidatorError::InvalidShmHeader(
+                "header copies differ; a writer may be updating it".to_string(),
+            ));
+        }
+
+        // The version field tells the byte order the header was written in
+        let little_endian = if LittleEndian::read_u32(first) == WAL_FORMAT_VERSION {
+            true
+        } else if BigEndian::read_u32(first) == WAL_FORMAT_VERSION {
+            false
+        } else {
+            return Err(WalValidatorError::InvalidShmHeader(format!(
+                "unknown WAL-index version {}",
+                LittleEndian::read_u32(first)
+            )));
+        };
+        let read_u32 = |bytes: &[u8]| {
+            if little_endian {
+                LittleEndian::read_u32(bytes)
+            } else {
+                BigEndian::read_u32(bytes)
+            }
+        };
+        let read_u16 = |bytes: &[u8]| {
+            if little_endian {
+                LittleEndian::read_u16(bytes)
+            } else {
+                BigEndian::read_u16(bytes)
+            }
+        };
+
+        if first[12] == 0 {
+            return Err(WalValidatorError::InvalidShmHeader(
+                "header is not initialized".to_string(),
+            ));
+        }
+
+        // Page sizes of 65536 are stored as 1, since they don't fit in 16 bits
+        let page_size = match read_u16(&first[14..16]) {
+            1 => 65536,
+            size => size as u32,
+        };
+
+        // The checksum covers the first 40 bytes, read as native-order words
+        let (mut s0, mut s1) = (0u32, 0u32);
+        for words in first[..40].chunks_exact(8) {
+            s0 = s0.wrapping_add(read_u32(&words[0..4])).wrapping_add(s1);
+            s1 = s1.wrapping_add(read_u32(&words[4..8])).wrapping_add(s0);
+        }
+        let checksum_matches = (s0, s1) == (read_u32(&first[40..44]), read_u32(&first[44..48]));
+
+        Ok(ShmHeader {
+            max_frame: read_u32(&first[16..20]),
+            page_count: read_u32(&first[20..24]),
+            page_size,
+            // Salts are copied verbatim from the WAL header, which is big-endian
+            salt1: BigEndian::read_u32(&first[32..36]),
+            salt2: BigEndian::read_u32(&first[36..40]),
+            backfilled: read_u32(&data[96..100]),
+            checksum_matches,
+        })
+    }
+
+    /// Returns true if this index describes the current generation of the
+    /// given WAL (the salts change every time the WAL restarts)
+    pub fn describes(&self, wal_header: &WalHeader) -> bool {
+        self.salt1 == wal_header.salt1 && self.salt2 == wal_header.salt2
+    }
+}
@@ -51,12 +51,12 @@ fn test_validate_no_duplicates() {
     let config = ValidatorConfig::default();
     let result = wal_validator::validate(&db_path, &wal_path, &config);
     match result {
-        Ok((reports, commits)) => {
-            println!("Processed {} commits", commits);
+        Ok(summary) => {
+            println!("Processed {} commits", summary.total_commits);
             assert!(
-                reports.is_empty(),
+                summary.issues.is_empty(),
                 "Expected no duplicates, found: {:?}",
-                reports
+                summary.issues
             );
         }
         Err(e) => {
@@ -66,6 +66,99 @@ fn test_validate_no_duplicates() {
     }
 }
 
+#[test]
+fn test_validate_readers_matches_paths() {
+    use std::io::Cursor;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+    let config = ValidatorConfig::default();
+
+    let from_paths = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let db = Cursor::new(std::fs::read(&db_path).unwrap());
+    let wal = Cursor::new(std::fs::read(&wal_path).unwrap());
+    let from_readers = wal_validator::validate_readers(db, wal, &config).unwrap();
+
+    assert!(from_paths.total_commits > 0);
+    assert_eq!(from_readers.total_commits, from_paths.total_commits);
+    assert_eq!(from_readers.final_db_size, from_paths.final_db_size);
+    assert_eq!(from_readers.issues.len(), from_paths.issues.len());
+
+    // A database too short for its header is reported, not read past
+    let result = wal_validator::validate_readers(
+        Cursor::new(vec![0u8; 10]),
+        Cursor::new(Vec::new()),
+        &config,
+    );
+    assert!(matches!(
+        result,
+        Err(wal_validator::error::WalValidatorError::TruncatedDatabaseHeader { size: 10 })
+    ));
+}
+
+#[test]
+fn test_custom_validator_issues_reported() {
+    use wal_validator::validators::{
+        default_validators, IssueLocation, Severity, ValidationContext, ValidationIssue, Validator,
+    };
+
+    /// Reports the page count of every state it sees
+    struct PageCountValidator;
+
+    impl Validator for PageCountValidator {
+        fn name(&self) -> &'static str {
+            "page-count"
+        }
+
+        fn validate(
+            &mut self,
+            ctx: &mut ValidationContext,
+        ) -> wal_validator::error::Result<Vec<ValidationIssue>> {
+            Ok(vec![ValidationIssue::new(
+                self.name(),
+                Severity::Info,
+                format!("{} pages", ctx.page_cache.effective_page_count()),
+                IssueLocation::Database,
+                ctx.commit_index,
+            )])
+        }
+    }
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+    let config = ValidatorConfig::default();
+
+    let mut validators = default_validators();
+    validators.push(Box::new(PageCountValidator));
+    let summary =
+        wal_validator::validate_with_validators(&db_path, &wal_path, &config, validators)
+            .unwrap();
+
+    let custom: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|issue| issue.validator == "page-count")
+        .collect();
+    assert!(summary.total_commits > 0);
+    assert_eq!(custom.len() as u64, summary.total_commits + 1);
+    assert_eq!(custom[0].commit_index, None);
+    assert!(custom.iter().all(|issue| issue.message.ends_with(" pages")));
+
+    // Custom validators are selected by name like the built-in ones
+    let config = ValidatorConfig {
+        skip: vec!["page-count".to_string()],
+        ..Default::default()
+    };
+    let summary = wal_validator::validate_with_validators(
+        &db_path,
+        &wal_path,
+        &config,
+        vec![Box::new(PageCountValidator)],
+    )
+    .unwrap();
+    assert!(summary.issues.is_empty());
+}
+
 #[test]
 fn test_db_header_parsing() {
     let dir = TempDir::new().unwrap();
@@ -119,3 +212,4198 @@ fn test_wal_header_parsing() {
         eprintln!("WAL file not found, skipping test");
     }
 }
+
+#[test]
+fn test_shared_root_page_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+        CREATE TABLE b (id INTEGER PRIMARY KEY);
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET rootpage = (SELECT rootpage FROM sqlite_master WHERE name = 'a')
+            WHERE name = 'b';
+    ",
+    );
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+
+    let root_issues: Vec<_> = summary.issues.iter().filter(|i| i.validator == "root-page").collect();
+    assert!(!root_issues.is_empty(), "Expected a shared root page issue");
+    assert!(root_issues[0].message.contains("'a' and 'b'"));
+}
+
+#[test]
+fn test_repair_checksums() {
+    let dir = TempDir::new().unwrap();
+    let (_db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    // Corrupt the stored checksum of the first frame
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    wal[32 + 16] ^= 0xFF;
+    let corrupt_path = dir.path().join("corrupt.db-wal");
+    std::fs::write(&corrupt_path, &wal).unwrap();
+
+    let repaired_path = dir.path().join("repaired.db-wal");
+    let report = wal_validator::wal::repair_checksums(&corrupt_path, &repaired_path).unwrap();
+    assert!(report.repaired_frames.contains(&0));
+    assert!(!report.header_repaired);
+
+    let commits = wal_validator::wal::CommitIterator::new(&repaired_path)
+        .unwrap()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(!commits.is_empty());
+}
+
+#[test]
+fn test_truncated_database_header() {
+    use wal_validator::error::WalValidatorError;
+
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+
+    std::fs::write(&db_path, b"").unwrap();
+    assert!(matches!(
+        wal_validator::db::DbHeader::from_file(&db_path),
+        Err(WalValidatorError::EmptyDatabase(_))
+    ));
+    assert!(matches!(
+        wal_validator::db::DbHeader::from_reader(&mut std::io::Cursor::new(Vec::new())),
+        Err(WalValidatorError::EmptyDatabaseImage)
+    ));
+    assert!(matches!(
+        wal_validator::db::DbHeader::from_bytes(&[]),
+        Err(WalValidatorError::EmptyDatabaseImage)
+    ));
+
+    std::fs::write(&db_path, b"SQLite format 3\0").unwrap();
+    assert!(matches!(
+        wal_validator::db::DbHeader::from_file(&db_path),
+        Err(WalValidatorError::TruncatedDatabaseHeader { size: 16 })
+    ));
+}
+
+#[test]
+fn test_numeric_key_comparison() {
+    use wal_validator::btree::KeyComparison;
+
+    let dir = TempDir::new().unwrap();
+
+    // A column without affinity keeps 1 as INTEGER and 1.0 as REAL; relabeling the
+    // index as UNIQUE afterwards makes them a duplicate SQLite itself would reject
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        CREATE INDEX idx_x ON t (x);
+        INSERT INTO t VALUES (1);
+        INSERT INTO t VALUES (1.0);
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET sql = 'CREATE UNIQUE INDEX idx_x ON t (x)' WHERE name = 'idx_x';
+    ",
+    );
+
+    let key_issues = |comparison| {
+        let config = ValidatorConfig {
+            key_comparison: comparison,
+            ..Default::default()
+        };
+        wal_validator::validate(&db_path, &wal_path, &config)
+            .unwrap()
+            .issues
+            .into_iter()
+            .filter(|i| i.validator == "duplicate-index-key")
+            .count()
+    };
+
+    assert_eq!(key_issues(KeyComparison::Binary), 0);
+    assert!(key_issues(KeyComparison::Numeric) > 0);
+}
+
+#[test]
+fn test_parse_record_serial_type_boundaries() {
+    use wal_validator::btree::cell::{encode_varint, extract_index_key};
+    use wal_validator::btree::{parse_record, TextEncoding, Value};
+
+    let record = |columns: &[(u64, Vec<u8>)]| {
+        let mut header = Vec::new();
+        for (serial_type, _) in columns {
+            encode_varint(*serial_type, &mut header);
+        }
+        let mut record = vec![header.len() as u8 + 1];
+        record.extend(header);
+        for (_, data) in columns {
+            record.extend(data);
+        }
+        record
+    };
+
+    // Each integer size at both ends of its range, then the other classes
+    let columns = vec![
+        (1, vec![0x80]),
+        (1, vec![0x7f]),
+        (2, (i16::MIN).to_be_bytes().to_vec()),
+        (2, (i16::MAX).to_be_bytes().to_vec()),
+        (3, vec![0x80, 0x00, 0x00]),
+        (3, vec![0x7f, 0xff, 0xff]),
+        (4, (i32::MIN).to_be_bytes().to_vec()),
+        (4, (i32::MAX).to_be_bytes().to_vec()),
+        (5, vec![0x80, 0, 0, 0, 0, 0]),
+        (5, vec![0x7f, 0xff, 0xff, 0xff, 0xff, 0xff]),
+        (6, i64::MIN.to_be_bytes().to_vec()),
+        (6, i64::MAX.to_be_bytes().to_vec()),
+        (7, (-1.5f64).to_be_bytes().to_vec()),
+        (8, vec![]),
+        (9, vec![]),
+        (0, vec![]),
+        (12, vec![]),
+        (13, vec![]),
+        (12 + 2 * 2, vec![0x00, 0xff]),
+        (13 + 2 * 5, b"Alice".to_vec()),
+    ];
+    let values = parse_record(&record(&columns), TextEncoding::Utf8).unwrap();
+    assert_eq!(
+        values,
+        vec![
+            Value::Int(-128),
+            Value::Int(127),
+            Value::Int(-32768),
+            Value::Int(32767),
+            Value::Int(-(1 << 23)),
+            Value::Int((1 << 23) - 1),
+            Value::Int(i32::MIN as i64),
+            Value::Int(i32::MAX as i64),
+            Value::Int(-(1 << 47)),
+            Value::Int((1 << 47) - 1),
+            Value::Int(i64::MIN),
+            Value::Int(i64::MAX),
+            Value::Float(-1.5),
+            Value::Int(0),
+            Value::Int(1),
+            Value::Null,
+            Value::Blob(vec![]),
+            Value::Text(String::new()),
+            Value::Blob(vec![0x00, 0xff]),
+            Value::Text("Alice".to_string()),
+        ]
+    );
+    let literals: Vec<_> = values[15..].iter().map(|v| v.to_string()).collect();
+    assert_eq!(literals, vec!["NULL", "X''", "''", "X'00ff'", "'Alice'"]);
+
+    // Reserved serial types and columns running past the payload are errors
+    assert!(parse_record(&record(&[(10, vec![])]), TextEncoding::Utf8).is_err());
+    assert!(parse_record(&record(&[(11, vec![])]), TextEncoding::Utf8).is_err());
+    assert!(parse_record(&record(&[(6, vec![0; 7])]), TextEncoding::Utf8).is_err());
+
+    // Index keys decode without their trailing rowid
+    let entry = record(&[(13 + 2 * 6, b"O'Neil".to_vec()), (1, vec![42])]);
+    let key = extract_index_key(&entry).unwrap();
+    let values = key.values(TextEncoding::Utf8).unwrap();
+    assert_eq!(values, vec![Value::Text("O'Neil".to_string())]);
+    assert_eq!(values[0].to_string(), "'O''Neil'");
+}
+
+#[test]
+fn test_index_key_excludes_trailing_rowid() {
+    use wal_validator::btree::cell::extract_index_key;
+
+    // One key column plus the rowid: entries for different rows share a key
+    let first = extract_index_key(&[3, 15, 1, b'a', 1]).unwrap();
+    let second = extract_index_key(&[3, 15, 1, b'a', 2]).unwrap();
+    assert_eq!(first, second);
+    assert_eq!(first.raw, vec![2, 15, b'a']);
+
+    // A record holding only the rowid has no key columns
+    let rowid_only = extract_index_key(&[2, 1, 7]).unwrap();
+    assert!(rowid_only.raw.is_empty());
+    assert_eq!(rowid_only, extract_index_key(&[2, 1, 8]).unwrap());
+}
+
+#[test]
+fn test_float_root_page_truncated() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+
+    // INTEGER affinity keeps a non-integral value as REAL (serial type 7)
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE t (x);
+         PRAGMA writable_schema=ON;
+         UPDATE sqlite_master SET rootpage = rootpage + 0.75 WHERE name = 't';",
+    )
+    .unwrap();
+    let stored: String = conn
+        .query_row("SELECT typeof(rootpage) FROM sqlite_master", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(stored, "real");
+    drop(conn);
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let btrees = BTreeScanner::new(&mut page_cache).discover_btrees().unwrap();
+    assert_eq!(btrees.len(), 1);
+    assert_eq!(btrees[0].root_page, 2);
+}
+
+#[test]
+fn test_collated_key_comparison() {
+    use wal_validator::btree::{Collation, KeyComparison};
+
+    let dir = TempDir::new().unwrap();
+
+    // Keys equal under NOCASE (inherited from the table) and RTRIM (from the
+    // index) but not byte-for-byte, then relabeled as UNIQUE
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x TEXT COLLATE NOCASE, y TEXT);
+        CREATE INDEX idx_x ON t (x);
+        CREATE INDEX idx_y ON t (y COLLATE RTRIM);
+        INSERT INTO t VALUES ('abc', 'a');
+        INSERT INTO t VALUES ('ABC', 'a  ');
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET sql = replace(sql, 'CREATE INDEX', 'CREATE UNIQUE INDEX')
+            WHERE type = 'index';
+    ",
+    );
+
+    let duplicate_indexes = |comparison| {
+        use wal_validator::validators::IssueLocation;
+
+        let config = ValidatorConfig {
+            key_comparison: comparison,
+            ..Default::default()
+        };
+        let mut names: Vec<_> = wal_validator::validate(&db_path, &wal_path, &config)
+            .unwrap()
+            .issues
+            .into_iter()
+            .filter(|i| i.validator == "duplicate-index-key")
+            .filter_map(|i| match i.location {
+                IssueLocation::Index { name, .. } => name,
+                _ => None,
+            })
+            .collect();
+        names.sort();
+        names
+    };
+
+    assert!(duplicate_indexes(KeyComparison::Numeric).is_empty());
+    let collated = duplicate_indexes(KeyComparison::Collated);
+    assert_eq!(collated, vec!["idx_x", "idx_y"]);
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache =
+        wal_validator::validator::PageCache::new(&db_path, header.page_size, header.page_count);
+    for commit in wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap() {
+        page_cache.apply_commit(&commit.unwrap());
+    }
+    let btrees = wal_validator::btree::BTreeScanner::new(&mut page_cache)
+        .discover_btrees()
+        .unwrap();
+    let collations = |name: &str| {
+        btrees
+            .iter()
+            .find(|b| b.name.as_deref() == Some(name))
+            .unwrap()
+            .collations
+            .clone()
+    };
+    assert_eq!(collations("idx_x"), vec![Collation::NoCase]);
+    assert_eq!(collations("idx_y"), vec![Collation::RTrim]);
+}
+
+#[test]
+fn test_collated_key_comparison_utf16() {
+    use wal_validator::btree::{KeyComparison, TextEncoding};
+    use wal_validator::validators::DuplicateDetails;
+
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    // t holds keys equal under NOCASE and RTRIM. u holds keys that only look
+    // equal when bytes are folded on their own: U+4100 and U+6100 differ by
+    // 0x20 in one byte, and U+2020 ends in bytes that read as two spaces.
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "
+        PRAGMA encoding='UTF-16le';
+        PRAGMA journal_mode=WAL;
+        PRAGMA wal_autocheckpoint=0;
+        CREATE TABLE t (x TEXT COLLATE NOCASE, y TEXT);
+        CREATE INDEX idx_tx ON t (x);
+        CREATE INDEX idx_ty ON t (y COLLATE RTRIM);
+        INSERT INTO t VALUES ('abc', 'a');
+        INSERT INTO t VALUES ('ABC', 'a  ');
+        CREATE TABLE u (x TEXT COLLATE NOCASE, y TEXT);
+        CREATE INDEX idx_ux ON u (x);
+        CREATE INDEX idx_uy ON u (y COLLATE RTRIM);
+        INSERT INTO u VALUES (char(0x4100), 'a');
+        INSERT INTO u VALUES (char(0x6100), 'a' || char(0x2020));
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET sql = replace(sql, 'CREATE INDEX', 'CREATE UNIQUE INDEX')
+            WHERE type = 'index';
+    ",
+    )
+    .unwrap();
+    std::mem::forget(conn);
+
+    let config = ValidatorConfig {
+        key_comparison: KeyComparison::Collated,
+        ..Default::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let duplicates: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "duplicate-index-key")
+        .collect();
+    assert_eq!(duplicates.len(), 2, "issues: {:?}", duplicates);
+
+    // Keys display decoded in the database's encoding
+    let mut keys: Vec<String> = duplicates
+        .iter()
+        .map(|issue| match &issue.duplicate_details {
+            Some(DuplicateDetails::IndexKey(entries)) => {
+                entries[0].key.display(TextEncoding::Utf16le).to_string()
+            }
+            _ => panic!("expected index key details: {:?}", issue),
+        })
+        .collect();
+    keys.sort();
+    assert_eq!(keys, vec!["('a')", "('abc')"]);
+}
+
+#[test]
+fn test_index_key_normalized() {
+    use wal_validator::btree::{Collation, IndexKey, KeyComparison, TextEncoding};
+
+    // ('ABC', 'x ', 1) and ('abc', 'x', 1.0), as records
+    let upper = IndexKey {
+        raw: [&[4, 19, 17, 1][..], b"ABC", b"x ", &[1]].concat(),
+    };
+    let lower = IndexKey {
+        raw: [&[4, 19, 15, 7][..], b"abc", b"x", &1.0f64.to_be_bytes()].concat(),
+    };
+    let collations = [Collation::NoCase, Collation::RTrim];
+    let encoding = TextEncoding::Utf8;
+
+    assert_eq!(
+        upper.normalized(&collations, encoding),
+        lower.normalized(&collations, encoding)
+    );
+    // Each column folds under its own collation only
+    assert_ne!(
+        upper.normalized(&[Collation::RTrim, Collation::NoCase], encoding),
+        lower.normalized(&[Collation::RTrim, Collation::NoCase], encoding)
+    );
+    assert_ne!(upper.normalized(&[], encoding), lower.normalized(&[], encoding));
+
+    // Comparing by collation groups by the normalized bytes
+    let comparable = upper
+        .comparable(KeyComparison::Collated, &collations, encoding)
+        .unwrap();
+    assert_eq!(comparable.raw, lower.normalized(&collations, encoding));
+
+    // A record that can't be parsed comes back unchanged
+    let truncated = IndexKey { raw: vec![4, 19, 15, 1, b'a'] };
+    assert_eq!(truncated.normalized(&collations, encoding), truncated.raw);
+}
+
+/// Create a checkpointed database (no WAL content) and an empty WAL file next to it
+fn create_checkpointed_db(dir: &TempDir, sql: &str) -> (PathBuf, PathBuf) {
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(sql).unwrap();
+    drop(conn);
+
+    std::fs::write(&wal_path, b"").unwrap();
+    (db_path, wal_path)
+}
+
+/// Create a database in WAL mode and run `sql` against it, leaving every
+/// commit in the WAL
+fn create_wal_db(dir: &TempDir, sql: &str) -> (PathBuf, PathBuf) {
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA wal_autocheckpoint=0;").unwrap();
+    conn.execute_batch(sql).unwrap();
+    // Keep the connection open so closing it doesn't checkpoint the WAL
+    std::mem::forget(conn);
+    (db_path, wal_path)
+}
+
+/// Overwrite bytes at `offset` within page `page_num` of a database file
+fn patch_page(db_path: &PathBuf, page_num: u32, offset: usize, bytes: &[u8]) {
+    let header = wal_validator::db::DbHeader::from_file(db_path).unwrap();
+    let mut data = std::fs::read(db_path).unwrap();
+    let start = (page_num as usize - 1) * header.page_size as usize + offset;
+    data[start..start + bytes.len()].copy_from_slice(bytes);
+    std::fs::write(db_path, data).unwrap();
+}
+
+#[test]
+fn test_cell_below_content_area_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+        INSERT INTO t VALUES (1, 'a');
+        INSERT INTO t VALUES (2, 'b');
+    ",
+    );
+
+    // Point the first cell of table t's root leaf (page 2) into the header area
+    patch_page(&db_path, 2, 8, &[0x00, 0x0A]);
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(
+        summary
+            .issues
+            .iter()
+            .any(|i| i.validator == "page-layout" && i.message.starts_with("Cell 0 ")),
+        "Expected a page layout issue, found: {:?}",
+        summary.issues
+    );
+}
+
+#[test]
+fn test_overlapping_cells_and_bad_free_space_detected() {
+    let layout_messages = |db_path: &PathBuf, wal_path: &PathBuf| -> Vec<String> {
+        let summary =
+            wal_validator::validate(db_path, wal_path, &ValidatorConfig::default()).unwrap();
+        summary
+            .issues
+            .into_iter()
+            .filter(|i| i.validator == "page-layout")
+            .map(|i| i.message)
+            .collect()
+    };
+
+    // Point the second cell of table t's root leaf (page 2) at the first one
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+        INSERT INTO t VALUES (1, 'a');
+        INSERT INTO t VALUES (2, 'b');
+    ",
+    );
+    assert!(layout_messages(&db_path, &wal_path).is_empty());
+    let page = &std::fs::read(&db_path).unwrap()[4096..8192];
+    let first_cell = u16::from_be_bytes([page[8], page[9]]);
+    patch_page(&db_path, 2, 10, &first_cell.to_be_bytes());
+    let messages = layout_messages(&db_path, &wal_path);
+    assert_eq!(messages.len(), 1, "messages: {:?}", messages);
+    assert!(
+        messages[0].starts_with(&format!("Cell 1 at offset {} overlaps cell 0", first_cell)),
+        "messages: {:?}",
+        messages
+    );
+
+    // Deleting a row leaves a freeblock behind
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+        INSERT INTO t VALUES (1, 'first row');
+        INSERT INTO t VALUES (2, 'second row');
+        INSERT INTO t VALUES (3, 'third row');
+        DELETE FROM t WHERE id = 2;
+    ",
+    );
+    assert!(layout_messages(&db_path, &wal_path).is_empty());
+    let page = std::fs::read(&db_path).unwrap()[4096..8192].to_vec();
+    let freeblock = u16::from_be_bytes([page[1], page[2]]) as usize;
+    assert_ne!(freeblock, 0);
+
+    // Claim fragmented bytes the content area doesn't have
+    patch_page(&db_path, 2, 7, &[page[7] + 3]);
+    let messages = layout_messages(&db_path, &wal_path);
+    assert_eq!(messages.len(), 1, "messages: {:?}", messages);
+    assert!(messages[0].starts_with("Cell content area holds "));
+
+    // A freeblock too small to hold its own header
+    patch_page(&db_path, 2, freeblock + 2, &[0, 2]);
+    let messages = layout_messages(&db_path, &wal_path);
+    assert_eq!(
+        messages,
+        [format!(
+            "Freeblock at offset {} has size 2, which doesn't fit between 4 bytes and the \
+             usable page size 4096",
+            freeblock
+        )]
+    );
+}
+
+#[test]
+fn test_validate_wal_only() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    // Drop the database so only the WAL remains
+    std::fs::remove_file(&db_path).unwrap();
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate_wal_only(&wal_path, &config).unwrap();
+    assert!(summary.total_commits > 0);
+    assert!(
+        summary
+            .issues
+            .iter()
+            .all(|i| i.severity != wal_validator::validators::Severity::Error),
+        "Unexpected errors: {:?}",
+        summary.issues
+    );
+}
+
+#[test]
+fn test_change_counter_going_backwards() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+        CREATE TABLE b (id INTEGER PRIMARY KEY);
+    ",
+    );
+
+    // Reset the change counter in the last frame that rewrites page 1
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let page_size = u32::from_be_bytes(wal[8..12].try_into().unwrap()) as usize;
+    let last_page1_frame = wal[32..]
+        .chunks_exact(24 + page_size)
+        .rposition(|frame| frame[0..4] == 1u32.to_be_bytes())
+        .unwrap();
+    let counter_offset = 32 + last_page1_frame * (24 + page_size) + 24 + 24;
+    wal[counter_offset..counter_offset + 4].copy_from_slice(&0u32.to_be_bytes());
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &wal).unwrap();
+
+    let repaired_path = dir.path().join("repaired.db-wal");
+    wal_validator::wal::repair_checksums(&patched_path, &repaired_path).unwrap();
+
+    let config = ValidatorConfig {
+        check_change_counter: true,
+        ..ValidatorConfig::default()
+    };
+    let summary = wal_validator::validate(&db_path, &repaired_path, &config).unwrap();
+    assert!(
+        summary
+            .issues
+            .iter()
+            .any(|i| i.validator == "change-counter" && i.message.contains("backwards")),
+        "Expected a change counter warning, found: {:?}",
+        summary.issues
+    );
+}
+
+#[test]
+fn test_json_report_includes_schema() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (a INTEGER, b TEXT);
+        CREATE INDEX idx_partial ON t(a) WHERE a > 0;
+        CREATE INDEX idx_expr ON t(lower(b));
+    ",
+    );
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let schema = wal_validator::discover_schema(&db_path).unwrap();
+
+    let mut out = Vec::new();
+    wal_validator::report::write_json_report(&mut out, &summary, Some(&schema), false).unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+    let classification = |name: &str| {
+        report["schema"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|o| o["name"] == name)
+            .map(|o| o["classification"].as_str().unwrap().to_string())
+            .unwrap()
+    };
+    assert_eq!(classification("t"), "checked");
+    assert_eq!(classification("idx_partial"), "skipped-partial");
+    assert_eq!(classification("idx_expr"), "skipped-expression");
+    assert!(report["issues"].as_array().unwrap().is_empty());
+
+    // Compact by default, indented on request, same content either way
+    assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1);
+    let mut pretty = Vec::new();
+    wal_validator::report::write_json_report(&mut pretty, &summary, Some(&schema), true).unwrap();
+    let pretty_text = String::from_utf8(pretty).unwrap();
+    assert!(pretty_text.contains("\n  \"total_commits\": "));
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&pretty_text).unwrap(), report);
+}
+
+#[test]
+fn test_stale_frame_with_matching_salt_ends_log() {
+    let dir = TempDir::new().unwrap();
+    let (_db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    let count_commits = |path: &PathBuf| {
+        wal_validator::wal::CommitIterator::new(path)
+            .unwrap()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+            .len()
+    };
+    let valid_commits = count_commits(&wal_path);
+    assert!(valid_commits > 0);
+
+    // Append a copy of the first frame: its salts match, but its checksum
+    // chains from the WAL header rather than from the last commit
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let page_size = u32::from_be_bytes(wal[8..12].try_into().unwrap()) as usize;
+    let first_frame = wal[32..32 + 24 + page_size].to_vec();
+    wal.extend_from_slice(&first_frame);
+    let stale_path = dir.path().join("stale.db-wal");
+    std::fs::write(&stale_path, &wal).unwrap();
+
+    assert_eq!(count_commits(&stale_path), valid_commits);
+}
+
+#[test]
+fn test_coverage_counts_skipped_indexes() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (a INTEGER UNIQUE, b TEXT);
+        CREATE INDEX idx_partial ON t(a) WHERE a > 0;
+        CREATE INDEX idx_expr ON t(lower(b));
+    ",
+    );
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert_eq!(
+        summary.coverage,
+        Some(wal_validator::Coverage {
+            tables: 1,
+            indexes: 1,
+            skipped_partial: 1,
+            skipped_expression: 1,
+            skipped_disabled: 0,
+        })
+    );
+}
+
+#[test]
+fn test_validators_run_after_their_dependencies() {
+    let config = ValidatorConfig::default();
+    let names: Vec<_> = wal_validator::validators::enabled_validators(&config)
+        .iter()
+        .map(|v| v.name())
+        .collect();
+
+    let position = |name: &str| names.iter().position(|n| *n == name).unwrap();
+    assert!(position("page-layout") < position("duplicate-rowid"));
+    assert!(position("page-layout") < position("duplicate-index-key"));
+}
+
+#[test]
+fn test_page_one_without_btree_header() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(&dir, "CREATE TABLE t (id INTEGER);");
+
+    // Keep only the 100-byte database header
+    let data = std::fs::read(&db_path).unwrap();
+    std::fs::write(&db_path, &data[..100]).unwrap();
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(
+        summary
+            .issues
+            .iter()
+            .any(|i| i.message.contains("page 1 truncated")),
+        "Expected a truncated page 1 issue, found: {:?}",
+        summary.issues
+    );
+}
+
+#[test]
+fn test_skip_base_reports_only_wal_issues() {
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    // Tables a (page 2) and b (page 3) in the base, each rewritten by a commit
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY, v TEXT);
+        CREATE TABLE b (id INTEGER PRIMARY KEY, v TEXT);
+        INSERT INTO a VALUES (1, 'a');
+        INSERT INTO b VALUES (1, 'b');
+        PRAGMA journal_mode=WAL;
+        PRAGMA wal_autocheckpoint=0;
+        INSERT INTO a VALUES (2, 'a');
+        INSERT INTO b VALUES (2, 'b');
+    ",
+    )
+    .unwrap();
+    std::mem::forget(conn);
+
+    // Point the first cell of a page into its header: page 2 in the base,
+    // which the first commit replaces, and page 3 in the second commit
+    patch_page(&db_path, 2, 8, &[0x00, 0x0A]);
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let commits = wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap();
+    let page_size = commits.wal_header().page_size;
+    let frame = commits
+        .flat_map(|commit| commit.unwrap().frames)
+        .filter(|frame| frame.header.page_number == 3)
+        .last()
+        .unwrap();
+    let cell_pointer = frame.file_offset(page_size) as usize + 24 + 8;
+    wal[cell_pointer..cell_pointer + 2].copy_from_slice(&[0x00, 0x0A]);
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &wal).unwrap();
+    let repaired_path = dir.path().join("repaired.db-wal");
+    wal_validator::wal::repair_checksums(&patched_path, &repaired_path).unwrap();
+
+    let layout_issues = |config: &ValidatorConfig| {
+        let summary = wal_validator::validate(&db_path, &repaired_path, config).unwrap();
+        summary
+            .issues
+            .iter()
+            .filter(|i| i.validator == "page-layout")
+            .map(|i| i.commit_index)
+            .collect::<Vec<_>>()
+    };
+    let config = ValidatorConfig::builder().only(["page-layout"]).build();
+    assert_eq!(layout_issues(&config), vec![None, Some(1)]);
+
+    let config = ValidatorConfig::builder().only(["page-layout"]).skip_base(true).build();
+    assert_eq!(layout_issues(&config), vec![Some(1)]);
+}
+
+#[test]
+fn test_since_commit_only_reports_later_commits() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    let config = ValidatorConfig {
+        describe_commits: true,
+        since_commit: Some(0),
+        ..ValidatorConfig::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+
+    assert!(summary.total_commits > 1);
+    assert!(!summary.issues.is_empty());
+    assert!(summary.issues.iter().all(|i| i.commit_index > Some(0)));
+}
+
+#[test]
+fn test_commit_operations_named_despite_unreadable_tree() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=512;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+         CREATE TABLE u (id INTEGER PRIMARY KEY);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+             INSERT INTO t SELECT i, zeroblob(100) FROM n;",
+    );
+
+    // Break a leaf of t in the base database, then commit a row to u
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let root = scanner.discover_btrees().unwrap()[0].root_page;
+    let leaf = scanner.collect_btree_pages(root).unwrap()[1];
+    patch_page(&db_path, leaf, 0, &[0]);
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "PRAGMA journal_mode=WAL;
+         PRAGMA wal_autocheckpoint=0;
+         INSERT INTO u VALUES (1);",
+    )
+    .unwrap();
+    std::mem::forget(conn);
+
+    let config = ValidatorConfig::builder()
+        .describe_commits(true)
+        .only(["commit-operations"])
+        .build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let messages: Vec<_> = summary.issues.iter().map(|i| i.message.as_str()).collect();
+    assert_eq!(messages, vec!["Commit modified table 'u'"]);
+}
+
+#[test]
+fn test_checksum_under_opposite_endianness() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    // Flip the magic so it claims the other checksum byte order
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    wal[3] ^= 0x01;
+    let flipped_path = dir.path().join("flipped.db-wal");
+    std::fs::write(&flipped_path, &wal).unwrap();
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &flipped_path, &config).unwrap();
+    assert!(
+        summary.issues.iter().any(|i| i.validator == "wal-checksum"
+            && i.severity == wal_validator::validators::Severity::Warning
+            && i.message.contains("frame 0 checksum validates under opposite endianness")
+            && i.commit_index.is_none()),
+        "Expected an endianness warning, found: {:?}",
+        summary.issues
+    );
+
+    // Re-checksum the second commit's first frame in the other byte order:
+    // the warning belongs to the state after commit 0, the last one applied
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let header = wal_validator::wal::WalHeader::parse(&wal[..32]).unwrap();
+    let commits: Vec<_> = wal_validator::wal::CommitIterator::new(&wal_path)
+        .unwrap()
+        .unwrap()
+        .map(|c| c.unwrap())
+        .collect();
+    let previous = commits[0].frames.last().unwrap().file_offset(header.page_size) as usize;
+    let frame = &commits[1].frames[0];
+    let offset = frame.file_offset(header.page_size) as usize;
+    let frame_size = 24 + header.page_size as usize;
+    let chain = (
+        u32::from_be_bytes(wal[previous + 16..previous + 20].try_into().unwrap()),
+        u32::from_be_bytes(wal[previous + 20..previous + 24].try_into().unwrap()),
+    );
+    let opposite = header.with_opposite_endianness();
+    let checksum = opposite.checksum(&wal[offset..offset + 8], chain);
+    let checksum = opposite.checksum(&wal[offset + 24..offset + frame_size], checksum);
+    wal[offset + 16..offset + 20].copy_from_slice(&checksum.0.to_be_bytes());
+    wal[offset + 20..offset + 24].copy_from_slice(&checksum.1.to_be_bytes());
+    std::fs::write(&flipped_path, &wal).unwrap();
+
+    let summary = wal_validator::validate(&db_path, &flipped_path, &config).unwrap();
+    let warning = summary
+        .issues
+        .iter()
+        .find(|i| i.validator == "wal-checksum")
+        .expect("endianness warning");
+    assert!(warning.message.starts_with(&format!("frame {} ", frame.frame_index)));
+    assert_eq!(warning.commit_index, Some(0));
+    assert_eq!(summary.total_commits, 1);
+
+    // Replay stops at the same frame instead of failing
+    assert!(wal_validator::first_divergence(&db_path, &flipped_path).is_ok());
+}
+
+#[test]
+fn test_index_column_count_mismatch_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (a INTEGER, b TEXT);
+        CREATE INDEX idx_ab ON t(a, b);
+        INSERT INTO t VALUES (1, 'x');
+        INSERT INTO t VALUES (2, 'y');
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET sql = 'CREATE INDEX idx_ab ON t(a)' WHERE name = 'idx_ab';
+    ",
+    );
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let mismatches: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "index-column-count")
+        .collect();
+    assert_eq!(mismatches.len(), 2, "Found: {:?}", summary.issues);
+    assert!(mismatches[0].message.contains("has 3 column(s), expected 2"));
+
+    // A record header running past the end of the page is counted as a problem
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let page_size = header.page_size as usize;
+    let page = std::fs::read(&db_path).unwrap()[2 * page_size..3 * page_size].to_vec();
+    let last_cell = (0..u16::from_be_bytes([page[3], page[4]]) as usize)
+        .map(|i| u16::from_be_bytes([page[8 + 2 * i], page[9 + 2 * i]]) as usize)
+        .max()
+        .unwrap();
+    patch_page(&db_path, 3, last_cell + 1, &[0x7f]);
+
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let problems: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "index-column-count")
+        .map(|i| i.message.as_str())
+        .collect();
+    assert_eq!(problems.len(), 2, "Found: {:?}", summary.issues);
+    assert!(problems.iter().any(|m| m.ends_with("has a record header that can't be parsed")));
+}
+
+#[test]
+fn test_parse_index_columns() {
+    use wal_validator::sql::parse_index_columns;
+
+    let columns = parse_index_columns(
+        "CREATE UNIQUE INDEX \"on\" ON t(\"a,b\" COLLATE NOCASE DESC, [c], lower(d), e + 1) -- (x, y)",
+    )
+    .unwrap();
+    let names: Vec<_> = columns.iter().map(|c| c.name.as_deref()).collect();
+    assert_eq!(names, vec![Some("a,b"), Some("c"), None, None]);
+    assert_eq!(columns[0].collation.as_deref(), Some("NOCASE"));
+    assert_eq!(columns[1].collation, None);
+
+    assert!(parse_index_columns("CREATE TABLE t (a)").is_none());
+}
+
+#[test]
+fn test_github_annotations() {
+    use wal_validator::validators::{IssueLocation, Severity, ValidationIssue};
+
+    let issues = vec![
+        ValidationIssue::new(
+            "root-page",
+            Severity::Error,
+            "objects 'a' and 'b' both claim root page 2",
+            IssueLocation::Page { page_number: 2 },
+            Some(3),
+        ),
+        ValidationIssue::new(
+            "commit-operations",
+            Severity::Info,
+            "100% of\nthe rows",
+            IssueLocation::Database,
+            None,
+        ),
+    ];
+
+    let mut out = Vec::new();
+    wal_validator::report::write_github_annotations(&mut out, &issues).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<_> = out.lines().collect();
+
+    assert_eq!(
+        lines[0],
+        "::error::[root-page] commit #3, page 2: objects 'a' and 'b' both claim root page 2"
+    );
+    assert_eq!(
+        lines[1],
+        "::notice::[commit-operations] base database, database: 100%25 of%0Athe rows"
+    );
+}
+
+#[test]
+fn test_sarif_log() {
+    use std::path::Path;
+    use wal_validator::validators::{IssueLocation, Severity, ValidationIssue};
+
+    let issues = vec![
+        ValidationIssue::new(
+            "root-page",
+            Severity::Error,
+            "objects 'a' and 'b' both claim root page 2",
+            IssueLocation::Page { page_number: 2 },
+            Some(3),
+        ),
+        ValidationIssue::new(
+            "duplicate-index-key",
+            Severity::Warning,
+            "duplicate key",
+            IssueLocation::Index {
+                name: Some("idx_users_email".to_string()),
+                root_page: 4,
+            },
+            None,
+        ),
+        ValidationIssue::new(
+            "root-page",
+            Severity::Info,
+            "note",
+            IssueLocation::Database,
+            None,
+        ),
+    ];
+
+    let mut out = Vec::new();
+    wal_validator::report::write_sarif(
+        &mut out,
+        &issues,
+        Some(Path::new("data/app.db")),
+        Path::new("data/app.db-wal"),
+    )
+    .unwrap();
+    let log: serde_json::Value = serde_json::from_slice(&out).unwrap();
+
+    assert_eq!(log["version"], "2.1.0");
+    let run = &log["runs"][0];
+    let rule_ids: Vec<_> = run["tool"]["driver"]["rules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|rule| rule["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(rule_ids, ["root-page", "duplicate-index-key"]);
+
+    let results = run["results"].as_array().unwrap();
+    let summary: Vec<_> = results
+        .iter()
+        .map(|r| {
+            (
+                r["ruleId"].as_str().unwrap(),
+                r["ruleIndex"].as_u64().unwrap(),
+                r["level"].as_str().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("root-page", 0, "error"),
+            ("duplicate-index-key", 1, "warning"),
+            ("root-page", 0, "note"),
+        ]
+    );
+
+    // Issues after a commit point at the WAL, base issues at the database
+    let location = &results[0]["locations"][0];
+    assert_eq!(location["physicalLocation"]["artifactLocation"]["uri"], "data/app.db-wal");
+    assert_eq!(location["logicalLocations"][0]["kind"], "page");
+    assert_eq!(results[0]["properties"]["commitIndex"], 3);
+    let location = &results[1]["locations"][0];
+    assert_eq!(location["physicalLocation"]["artifactLocation"]["uri"], "data/app.db");
+    assert_eq!(location["logicalLocations"][0]["name"], "idx_users_email");
+    assert_eq!(location["logicalLocations"][0]["kind"], "index");
+    assert!(results[1].get("properties").is_none());
+}
+
+#[test]
+fn test_final_db_size_after_vacuum_shrink() {
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "
+        PRAGMA journal_mode=WAL;
+        CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+            INSERT INTO t SELECT i, zeroblob(1000) FROM n;
+        PRAGMA wal_checkpoint(TRUNCATE);
+        PRAGMA wal_autocheckpoint=0;
+        DELETE FROM t;
+        VACUUM;
+    ",
+    )
+    .unwrap();
+    std::mem::forget(conn);
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let base_page_count = summary.base_page_count.unwrap();
+    let final_db_size = summary.final_db_size.unwrap();
+    assert!(final_db_size < base_page_count);
+
+    // The shrunken size wins over the larger base page count
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    for commit in wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap() {
+        page_cache.apply_commit(&commit.unwrap());
+    }
+    assert_eq!(page_cache.effective_page_count(), final_db_size);
+}
+
+#[test]
+fn test_page_cache_capacity_bounds_base_pages() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    // The WAL rewrites every other row, touching far more pages than the cap
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "
+        PRAGMA journal_mode=WAL;
+        CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 300)
+            INSERT INTO t SELECT i, randomblob(1000) FROM n;
+        PRAGMA wal_checkpoint(TRUNCATE);
+        PRAGMA wal_autocheckpoint=0;
+        UPDATE t SET data = randomblob(1000) WHERE id % 2 = 0;
+    ",
+    )
+    .unwrap();
+    std::mem::forget(conn);
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut unbounded = PageCache::new(&db_path, header.page_size, header.page_count)
+        .with_capacity(usize::MAX);
+    let mut bounded =
+        PageCache::new(&db_path, header.page_size, header.page_count).with_capacity(8);
+    for commit in wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap() {
+        let commit = commit.unwrap();
+        unbounded.apply_commit(&commit);
+        bounded.apply_commit(&commit);
+    }
+
+    let mut scanner = BTreeScanner::new(&mut bounded);
+    let root_page = scanner.discover_btrees().unwrap()[0].root_page;
+    assert!(scanner.collect_btree_pages(root_page).unwrap().len() > 50);
+    assert!(bounded.cached_page_count() <= 8);
+
+    // Evicted pages are read again and still match, WAL pages included
+    for page_num in (1..=bounded.effective_page_count()).rev() {
+        let expected = unbounded.get_page(page_num).unwrap();
+        assert_eq!(bounded.get_page_ref(page_num).unwrap(), expected.as_slice());
+        assert!(bounded.cached_page_count() <= 8);
+    }
+    let wal_pages = (1..=bounded.effective_page_count())
+        .filter(|&page_num| bounded.get_frame_index(page_num).is_some())
+        .count();
+    assert!(wal_pages > 50, "WAL pages: {}", wal_pages);
+}
+
+#[test]
+fn test_commit_diffs_list_changed_pages_and_types() {
+    use wal_validator::btree::BTreePageType;
+    use wal_validator::wal::{CommitDiffIterator, PageTypeChange};
+
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "
+        PRAGMA journal_mode=WAL;
+        CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+        PRAGMA wal_checkpoint(TRUNCATE);
+        PRAGMA wal_autocheckpoint=0;
+        CREATE TABLE u (x);
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 20)
+            INSERT INTO t SELECT i, zeroblob(1000) FROM n;
+    ",
+    )
+    .unwrap();
+    std::mem::forget(conn);
+
+    let diffs: Vec<_> = CommitDiffIterator::new(&db_path, &wal_path)
+        .unwrap()
+        .unwrap()
+        .with_page_types()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(diffs.len(), 2);
+
+    // Creating u writes the schema on page 1 and u's new root page
+    let create = &diffs[0];
+    assert_eq!(create.changed_pages, vec![1, 3]);
+    assert_eq!((create.old_page_count, create.new_page_count), (2, 3));
+    assert_eq!(
+        create.page_types,
+        vec![
+            PageTypeChange {
+                page_number: 1,
+                before: Some(BTreePageType::TableLeaf),
+                after: Some(BTreePageType::TableLeaf),
+            },
+            PageTypeChange {
+                page_number: 3,
+                before: None,
+                after: Some(BTreePageType::TableLeaf),
+            },
+        ]
+    );
+
+    // Filling t splits its root leaf into an interior page over new leaves
+    let insert = &diffs[1];
+    assert!(insert.new_page_count > 3);
+    assert!(insert.changed_pages.windows(2).all(|pair| pair[0] < pair[1]));
+    let root = insert.page_types.iter().find(|c| c.page_number == 2).unwrap();
+    assert_eq!(root.before, Some(BTreePageType::TableLeaf));
+    assert_eq!(root.after, Some(BTreePageType::TableInterior));
+    assert!(insert
+        .page_types
+        .iter()
+        .filter(|c| c.page_number > 3)
+        .all(|c| c.before.is_none() && c.after == Some(BTreePageType::TableLeaf)));
+}
+
+#[test]
+fn test_selecting_index_validator_overrides_skip_indexes() {
+    let enabled_names = |config: &ValidatorConfig| -> Vec<&'static str> {
+        wal_validator::validators::enabled_validators(config)
+            .iter()
+            .map(|v| v.name())
+            .collect()
+    };
+
+    // Index validators run by default, and skip_indexes turns them off
+    assert!(enabled_names(&ValidatorConfig::default()).contains(&"duplicate-index-key"));
+    let config = ValidatorConfig {
+        skip_indexes: true,
+        ..ValidatorConfig::default()
+    };
+    assert!(!enabled_names(&config).contains(&"duplicate-index-key"));
+
+    // ...but asking for one by name runs it anyway
+    let config = ValidatorConfig {
+        skip_indexes: true,
+        only: vec!["duplicate-index-key".to_string()],
+        ..ValidatorConfig::default()
+    };
+    assert_eq!(enabled_names(&config), vec!["duplicate-index-key"]);
+
+    // Validators behind a setting run when named, without the setting
+    for name in [
+        "new-pages",
+        "change-counter",
+        "reserved-bytes",
+        "key-order",
+        "not-null",
+        "foreign-key",
+        "rowid-resurrection",
+        "schema-cookie",
+        "commit-operations",
+        "fill-factor",
+    ] {
+        let config = ValidatorConfig::builder().only([name]).build();
+        assert_eq!(enabled_names(&config), vec![name]);
+    }
+}
+
+#[test]
+fn test_skipped_validators_never_run() {
+    use wal_validator::validators::{enabled_validators, validator_names};
+
+    let config = ValidatorConfig {
+        only: vec!["duplicate-rowid".to_string(), "page-layout".to_string()],
+        skip: vec!["page-layout".to_string()],
+        ..ValidatorConfig::default()
+    };
+    let names: Vec<_> = enabled_validators(&config).iter().map(|v| v.name()).collect();
+    assert_eq!(names, vec!["duplicate-rowid"]);
+
+    let config = ValidatorConfig {
+        skip: vec!["duplicate-rowid".to_string()],
+        ..ValidatorConfig::default()
+    };
+    let names: Vec<_> = enabled_validators(&config).iter().map(|v| v.name()).collect();
+    assert!(!names.contains(&"duplicate-rowid") && names.contains(&"page-layout"));
+
+    // Every validator the CLI can name is listed
+    let all = validator_names();
+    assert!(names.iter().all(|name| all.contains(name)));
+    assert!(all.contains(&"duplicate-index-key"));
+}
+
+#[test]
+fn test_hash_db_detects_silent_page_change() {
+    use wal_validator::validators::{IssueLocation, Severity};
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+         INSERT INTO t VALUES (1, 'a'), (2, 'b');",
+    );
+    let hash_path = dir.path().join("hashes.json");
+
+    // First run only records the hashes
+    let issues = wal_validator::check_page_hashes(&db_path, &wal_path, &hash_path).unwrap();
+    assert!(issues.is_empty());
+    assert!(hash_path.exists());
+
+    // Flip a byte in the table's page with no WAL frame behind it
+    let page_size = wal_validator::db::DbHeader::from_file(&db_path).unwrap().page_size;
+    patch_page(&db_path, 2, page_size as usize - 1, &[0xff]);
+
+    let issues = wal_validator::check_page_hashes(&db_path, &wal_path, &hash_path).unwrap();
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].severity, Severity::Error);
+    assert!(matches!(issues[0].location, IssueLocation::Page { page_number: 2 }));
+
+    // The new hashes were saved, so an unchanged file is clean again
+    let issues = wal_validator::check_page_hashes(&db_path, &wal_path, &hash_path).unwrap();
+    assert!(issues.is_empty());
+}
+
+#[test]
+fn test_consecutive_identical_frames_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+    ",
+    );
+
+    // Repeat the first frame of the commit right after itself, as a bad copy might
+    let wal = std::fs::read(&wal_path).unwrap();
+    let page_size = u32::from_be_bytes(wal[8..12].try_into().unwrap()) as usize;
+    let frame_size = 24 + page_size;
+    let first_frame = &wal[32..32 + frame_size];
+    assert_eq!(first_frame[4..8], [0; 4], "first frame must not end the commit");
+    let mut patched = wal[..32 + frame_size].to_vec();
+    patched.extend_from_slice(first_frame);
+    patched.extend_from_slice(&wal[32 + frame_size..]);
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &patched).unwrap();
+
+    let repaired_path = dir.path().join("repaired.db-wal");
+    wal_validator::wal::repair_checksums(&patched_path, &repaired_path).unwrap();
+
+    let summary =
+        wal_validator::validate(&db_path, &repaired_path, &ValidatorConfig::default()).unwrap();
+    let duplicates: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "duplicate-frame")
+        .collect();
+    assert_eq!(duplicates.len(), 1, "issues: {:?}", summary.issues);
+    assert_eq!(duplicates[0].commit_index, Some(0));
+    // Reported once, not again as a repeated page
+    assert!(summary.issues.iter().all(|i| i.validator != "repeated-page"));
+
+    // The untouched WAL has no duplicate frames
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.iter().all(|i| i.validator != "duplicate-frame"));
+}
+
+#[test]
+fn test_page_repeated_within_commit_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+    ",
+    );
+
+    // Write the commit's first page a second time, with a byte of its free
+    // space changed so the copy isn't an identical duplicate
+    let wal = std::fs::read(&wal_path).unwrap();
+    let page_size = u32::from_be_bytes(wal[8..12].try_into().unwrap()) as usize;
+    let frame_size = 24 + page_size;
+    let first_page = u32::from_be_bytes(wal[32..36].try_into().unwrap());
+    let mut copy = wal[32..32 + frame_size].to_vec();
+    copy[24 + 1000] ^= 0xff;
+    let mut patched = wal[..32 + frame_size].to_vec();
+    patched.extend_from_slice(&copy);
+    patched.extend_from_slice(&wal[32 + frame_size..]);
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &patched).unwrap();
+
+    let repaired_path = dir.path().join("repaired.db-wal");
+    wal_validator::wal::repair_checksums(&patched_path, &repaired_path).unwrap();
+
+    let summary =
+        wal_validator::validate(&db_path, &repaired_path, &ValidatorConfig::default()).unwrap();
+    let repeated: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "repeated-page")
+        .collect();
+    assert_eq!(repeated.len(), 1, "issues: {:?}", summary.issues);
+    assert_eq!(
+        repeated[0].message,
+        format!(
+            "Page {} is written by frames 0 and 1 of the same commit; the later frame wins",
+            first_page
+        )
+    );
+    assert!(summary.issues.iter().all(|i| i.validator != "duplicate-frame"));
+
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.iter().all(|i| i.validator != "repeated-page"));
+}
+
+#[test]
+fn test_invalid_newly_allocated_page_detected() {
+    use wal_validator::validators::{IssueLocation, Severity};
+
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    // The base holds table a; the WAL commit grows the database by b's root
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+        PRAGMA journal_mode=WAL;
+        PRAGMA wal_autocheckpoint=0;
+        CREATE TABLE b (id INTEGER PRIMARY KEY);
+    ",
+    )
+    .unwrap();
+    std::mem::forget(conn);
+
+    let config = ValidatorConfig::builder()
+        .check_new_pages(true)
+        .only(["new-pages"])
+        .build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(summary.issues.is_empty(), "issues: {:?}", summary.issues);
+
+    // Give the new root page a page type that no B-tree page has
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let commits = wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap();
+    let page_size = commits.wal_header().page_size;
+    let commit = commits.last().unwrap().unwrap();
+    assert_eq!(commit.db_size, 3);
+    let frame = commit.frames.iter().find(|f| f.header.page_number == 3).unwrap();
+    let page_type = frame.file_offset(page_size) as usize + 24;
+    wal[page_type] = 0x01;
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &wal).unwrap();
+    let repaired_path = dir.path().join("repaired.db-wal");
+    wal_validator::wal::repair_checksums(&patched_path, &repaired_path).unwrap();
+
+    let summary = wal_validator::validate(&db_path, &repaired_path, &config).unwrap();
+    assert_eq!(summary.issues.len(), 1, "issues: {:?}", summary.issues);
+    let issue = &summary.issues[0];
+    assert_eq!(issue.validator, "new-pages");
+    assert_eq!(issue.severity, Severity::Error);
+    assert_eq!(issue.commit_index, Some(0));
+    assert!(matches!(issue.location, IssueLocation::Page { page_number: 3 }));
+    assert!(issue.message.starts_with("Newly allocated page 3 has an invalid B-tree header"));
+}
+
+#[test]
+fn test_page_size_change_in_wal_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+    ",
+    );
+
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.iter().all(|i| i.validator != "page-size"));
+
+    // Rewrite the page size field in the commit's copy of page 1
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let commits = wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap();
+    let page_size = commits.wal_header().page_size;
+    let frame = commits
+        .flat_map(|commit| commit.unwrap().frames)
+        .find(|frame| frame.header.page_number == 1)
+        .unwrap();
+    let field = frame.file_offset(page_size) as usize + 24 + 16;
+    assert_eq!(wal[field..field + 2], (page_size as u16).to_be_bytes());
+    wal[field..field + 2].copy_from_slice(&(page_size as u16 * 2).to_be_bytes());
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &wal).unwrap();
+
+    let repaired_path = dir.path().join("repaired.db-wal");
+    wal_validator::wal::repair_checksums(&patched_path, &repaired_path).unwrap();
+
+    let summary =
+        wal_validator::validate(&db_path, &repaired_path, &ValidatorConfig::default()).unwrap();
+    let issues: Vec<_> = summary.issues.iter().filter(|i| i.validator == "page-size").collect();
+    assert_eq!(issues.len(), 1, "issues: {:?}", summary.issues);
+    assert_eq!(issues[0].severity, wal_validator::validators::Severity::Error);
+    assert_eq!(issues[0].commit_index, Some(0));
+    assert_eq!(
+        issues[0].message,
+        format!(
+            "Page 1 declares a page size of {}, but the database and WAL use {}-byte pages",
+            page_size * 2,
+            page_size
+        )
+    );
+}
+
+#[test]
+fn test_frame_past_commit_db_size_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+    ",
+    );
+
+    // Claim the commit leaves a one-page database although it writes page 2
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let page_size = u32::from_be_bytes(wal[8..12].try_into().unwrap()) as usize;
+    let frame_size = 24 + page_size;
+    let last_frame = wal.len() - frame_size;
+    assert_eq!(wal[last_frame + 4..last_frame + 8], 2u32.to_be_bytes());
+    wal[last_frame + 4..last_frame + 8].copy_from_slice(&1u32.to_be_bytes());
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &wal).unwrap();
+
+    let repaired_path = dir.path().join("repaired.db-wal");
+    wal_validator::wal::repair_checksums(&patched_path, &repaired_path).unwrap();
+
+    let summary =
+        wal_validator::validate(&db_path, &repaired_path, &ValidatorConfig::default()).unwrap();
+    let out_of_range: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "frame-page-range")
+        .collect();
+    assert_eq!(out_of_range.len(), 1, "issues: {:?}", summary.issues);
+    assert!(out_of_range[0].message.contains("writes page 2"));
+    assert_eq!(out_of_range[0].severity, wal_validator::validators::Severity::Error);
+
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.iter().all(|i| i.validator != "frame-page-range"));
+}
+
+#[test]
+fn test_commit_sequence_breaks_detected() {
+    use wal_validator::validator::PageCache;
+    use wal_validator::validators::{CommitSequenceValidator, ValidationContext, Validator};
+    use wal_validator::wal::{Commit, CommitIterator};
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+        CREATE TABLE b (id INTEGER PRIMARY KEY);
+    ",
+    );
+
+    let commits = CommitIterator::new(&wal_path).unwrap().unwrap();
+    let page_size = commits.wal_header().page_size;
+    let commits: Vec<Commit> = commits.map(|commit| commit.unwrap()).collect();
+    assert_eq!(commits.len(), 2);
+
+    // The validator only looks at the commits, so an empty base will do
+    let config = ValidatorConfig::default();
+    let messages = |sequence: &[Commit]| {
+        let mut page_cache = PageCache::new(&db_path, page_size, 0);
+        let mut validator = CommitSequenceValidator::new();
+        let mut messages = Vec::new();
+        for commit in sequence {
+            let mut ctx = ValidationContext::new(&mut page_cache, Some(commit), &config);
+            let issues = validator.validate(&mut ctx).unwrap();
+            messages.extend(issues.into_iter().map(|issue| issue.message));
+        }
+        messages
+    };
+    assert!(messages(&commits).is_empty());
+
+    let reversed = [commits[1].clone(), commits[0].clone()];
+    assert_eq!(messages(&reversed), vec!["Commit index 0 does not follow commit 1"]);
+
+    let mut gap = commits[1].clone();
+    let first_frame = gap.frames[0].frame_index;
+    for frame in &mut gap.frames {
+        frame.frame_index += 3;
+    }
+    assert_eq!(
+        messages(&[commits[0].clone(), gap]),
+        vec![format!(
+            "Commit starts at frame {} but the previous commit ended at frame {}",
+            first_frame + 3,
+            first_frame - 1
+        )]
+    );
+
+    // Shrink the database without the commit writing page 1
+    let mut shrink = commits[1].clone();
+    shrink.db_size = commits[0].db_size - 1;
+    shrink.frames.retain(|frame| frame.header.page_number != 1);
+    assert!(!shrink.frames.is_empty());
+    for (frame, index) in shrink.frames.iter_mut().zip(first_frame..) {
+        frame.frame_index = index;
+    }
+    assert_eq!(
+        messages(&[commits[0].clone(), shrink]),
+        vec![format!(
+            "Database shrank from {} to {} page(s) without page 1 recording the new size",
+            commits[0].db_size,
+            commits[0].db_size - 1
+        )]
+    );
+}
+
+#[test]
+fn test_smallest_page_size_with_overflowing_rows() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=512;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, body TEXT);
+         CREATE UNIQUE INDEX t_body ON t (body);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+             INSERT INTO t SELECT i, printf('%.1000c', 'x') || i FROM n;",
+    );
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    assert_eq!(header.page_size, 512);
+
+    // Every row spills onto overflow pages, but the rowid sits before the
+    // payload, so all of them are still collected
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let btrees = scanner.discover_btrees().unwrap();
+    let table = btrees.iter().find(|b| b.name.as_deref() == Some("t")).unwrap();
+    let mut rowids: Vec<i64> = scanner
+        .collect_table_rowids(table.root_page)
+        .unwrap()
+        .into_iter()
+        .map(|(rowid, _)| rowid)
+        .collect();
+    rowids.sort_unstable();
+    assert_eq!(rowids, (1..=200).collect::<Vec<i64>>());
+
+    // Index keys spill onto overflow pages too; the chain is followed to
+    // assemble each key and the rowid after it. Leaf cells hold every entry
+    // but the few separators stored in interior cells.
+    let index = btrees.iter().find(|b| b.name.as_deref() == Some("t_body")).unwrap();
+    let keys = scanner.collect_index_keys(index.root_page).unwrap();
+    let mut key_rowids: Vec<i64> = keys.iter().filter_map(|(_, rowid, _)| *rowid).collect();
+    key_rowids.sort_unstable();
+    assert_eq!(key_rowids.len(), keys.len());
+    assert!(keys.len() > 150, "only {} keys collected", keys.len());
+    key_rowids.dedup();
+    assert_eq!(key_rowids.len(), keys.len());
+    assert!(key_rowids.iter().all(|rowid| (1..=200).contains(rowid)));
+
+    // The separators are entries of their own, so only with them is every
+    // row indexed, each exactly once
+    let all_keys = scanner
+        .collect_index_keys_including_interior(index.root_page)
+        .unwrap();
+    let interior_keys: Vec<_> = all_keys
+        .iter()
+        .filter(|(_, _, location)| !keys.iter().any(|(_, _, leaf)| leaf == location))
+        .collect();
+    assert_eq!(interior_keys.len(), 200 - keys.len());
+    let file = std::fs::read(&db_path).unwrap();
+    assert!(interior_keys.iter().all(|(_, _, location)| {
+        // Index interior page type
+        file[(location.page_number as usize - 1) * 512] == 0x02
+    }));
+    let mut index_rowids = scanner.collect_index_rowids(index.root_page).unwrap();
+    index_rowids.sort_unstable();
+    assert_eq!(index_rowids, (1..=200).collect::<Vec<i64>>());
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(summary.issues.is_empty(), "issues: {:?}", summary.issues);
+}
+
+#[test]
+fn test_freelist_checked_against_header_and_btrees() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+             INSERT INTO t SELECT i, zeroblob(500) FROM n;
+         DELETE FROM t WHERE id > 50;",
+    );
+
+    // Deleting without VACUUM leaves the emptied pages on the freelist
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    assert!(header.freelist_head != 0);
+    assert!(header.freelist_count > 10, "free pages: {}", header.freelist_count);
+
+    let freelist_issues = || {
+        let summary =
+            wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+        summary
+            .issues
+            .into_iter()
+            .filter(|i| i.validator == "freelist")
+            .map(|i| i.message)
+            .collect::<Vec<_>>()
+    };
+    assert!(freelist_issues().is_empty());
+
+    // A header count that disagrees with the list
+    patch_page(&db_path, 1, 36, &(header.freelist_count + 1).to_be_bytes());
+    assert_eq!(
+        freelist_issues(),
+        vec![format!(
+            "Header declares {} freelist page(s), but the freelist holds {}",
+            header.freelist_count + 1,
+            header.freelist_count
+        )]
+    );
+    patch_page(&db_path, 1, 36, &header.freelist_count.to_be_bytes());
+
+    // A leaf of the freelist that is also the table's root page
+    patch_page(&db_path, header.freelist_head, 8, &2u32.to_be_bytes());
+    let issues = freelist_issues();
+    assert!(
+        issues.contains(&"Freelist page 2 is also a page of the B-tree 't'".to_string()),
+        "issues: {:?}",
+        issues
+    );
+}
+
+#[test]
+fn test_orphan_pages_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+             INSERT INTO t SELECT i, zeroblob(500) FROM n;
+         INSERT INTO t VALUES (1000, zeroblob(20000));
+         DELETE FROM t WHERE id BETWEEN 51 AND 200;",
+    );
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    assert!(header.freelist_count > 10, "free pages: {}", header.freelist_count);
+
+    let orphan_issues = || {
+        let summary =
+            wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+        summary
+            .issues
+            .into_iter()
+            .filter(|i| i.validator == "orphan-page")
+            .map(|i| (i.severity, i.message))
+            .collect::<Vec<_>>()
+    };
+    // Overflow chains and free pages are in use
+    assert!(orphan_issues().is_empty());
+
+    // Dropping the freelist from the header leaks every page on it
+    patch_page(&db_path, 1, 32, &[0; 8]);
+    let issues = orphan_issues();
+    assert_eq!(issues.len(), 1, "issues: {:?}", issues);
+    assert_eq!(issues[0].0, wal_validator::validators::Severity::Warning);
+    assert!(
+        issues[0].1.starts_with(&format!(
+            "{} page(s) are not reachable from any B-tree or the freelist: ",
+            header.freelist_count
+        )),
+        "message: {}",
+        issues[0].1
+    );
+    assert!(issues[0].1.contains(&format!("{}", header.freelist_head)));
+}
+
+#[test]
+fn test_lock_byte_page_number() {
+    use wal_validator::btree::{is_lock_byte_page, pending_byte_page};
+
+    // The page holding byte 2^30 of the file
+    assert_eq!(pending_byte_page(512), 2_097_153);
+    assert_eq!(pending_byte_page(1024), 1_048_577);
+    assert_eq!(pending_byte_page(4096), 262_145);
+    assert_eq!(pending_byte_page(65536), 16_385);
+
+    assert!(is_lock_byte_page(262_145, 4096));
+    assert!(!is_lock_byte_page(262_144, 4096));
+    assert!(!is_lock_byte_page(262_146, 4096));
+    assert!(!is_lock_byte_page(262_145, 8192));
+}
+
+#[test]
+fn test_zeroed_right_child_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+             INSERT INTO t SELECT i, zeroblob(500) FROM n;",
+    );
+
+    let link_issues = || {
+        let summary =
+            wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+        summary
+            .issues
+            .into_iter()
+            .filter(|i| i.validator == "page-link")
+            .map(|i| i.message)
+            .collect::<Vec<_>>()
+    };
+    assert!(link_issues().is_empty());
+
+    // Table t's root (page 2) is an interior page; its right child pointer
+    // follows the page header
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    patch_page(&db_path, 2, 8, &[0; 4]);
+    assert_eq!(
+        link_issues(),
+        vec![format!(
+            "Interior page 2 of B-tree 't': the right child is page 0, outside the database \
+             ({} pages)",
+            header.page_count
+        )]
+    );
+}
+
+#[test]
+fn test_btree_pages_iterator() {
+    use wal_validator::btree::{BTreePageType, BTreeScanner};
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, _wal_path) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=512;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 300)
+             INSERT INTO t SELECT i, zeroblob(100) FROM n;",
+    );
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let root = scanner.discover_btrees().unwrap()[0].root_page;
+
+    let pages: Vec<_> = scanner.pages(root).collect::<Result<_, _>>().unwrap();
+    assert_eq!(pages[0].0, root);
+    assert_eq!(pages[0].1.page_type, BTreePageType::TableInterior);
+    let leaf_rows: u32 = pages
+        .iter()
+        .filter(|(_, header)| header.page_type == BTreePageType::TableLeaf)
+        .map(|(_, header)| header.cell_count as u32)
+        .sum();
+    assert_eq!(leaf_rows, 300);
+    let numbers: Vec<u32> = pages.iter().map(|(page, _)| *page).collect();
+    assert_eq!(numbers, scanner.collect_btree_pages(root).unwrap());
+
+    // A page that isn't a B-tree page ends the iteration with its error
+    patch_page(&db_path, numbers[1], 0, &[0]);
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let mut pages = scanner.pages(root);
+    assert!(pages.next().unwrap().is_ok());
+    assert!(pages.next().unwrap().is_err());
+    assert!(pages.next().is_none());
+}
+
+#[test]
+fn test_collectors_end_on_self_referencing_child() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=512;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, data TEXT);
+         CREATE INDEX idx_data ON t (data);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 300)
+             INSERT INTO t SELECT i, printf('%.*c', 50, 'x') || i FROM n;",
+    );
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let btrees = scanner.discover_btrees().unwrap();
+    let (table, index) = (btrees[0].root_page, btrees[1].root_page);
+
+    // The right-most child pointer of each root names the root itself
+    patch_page(&db_path, table, 8, &table.to_be_bytes());
+    patch_page(&db_path, index, 8, &index.to_be_bytes());
+
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    assert!(!scanner.collect_table_rowids(table).unwrap().is_empty());
+    assert!(!scanner.collect_table_rowids_in_order(table).unwrap().is_empty());
+    assert!(!scanner.collect_table_records(table).unwrap().is_empty());
+    assert!(!scanner.collect_index_keys(index).unwrap().is_empty());
+    assert!(!scanner.collect_index_keys_in_order(index).unwrap().is_empty());
+    assert!(!scanner.collect_index_rowids(index).unwrap().is_empty());
+    assert!(!scanner.collect_index_column_counts(index).unwrap().is_empty());
+
+    wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::thorough()).unwrap();
+}
+
+#[test]
+fn test_page_with_two_parents_detected() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=512;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+         CREATE TABLE u (id INTEGER PRIMARY KEY, data BLOB);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+             INSERT INTO t SELECT i, zeroblob(100) FROM n;
+         INSERT INTO u SELECT * FROM t;",
+    );
+
+    let reference_issues = || {
+        let summary =
+            wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+        summary
+            .issues
+            .into_iter()
+            .filter(|i| i.validator == "page-reference")
+            .map(|i| i.message)
+            .collect::<Vec<_>>()
+    };
+    assert!(reference_issues().is_empty());
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let btrees = scanner.discover_btrees().unwrap();
+    let (t_root, u_root) = (btrees[0].root_page, btrees[1].root_page);
+    let u_leaf = scanner.collect_btree_pages(u_root).unwrap()[1];
+    let mut t_children = Vec::new();
+    scanner
+        .walk_pages(t_root, |visit| {
+            if visit.parent == Some(t_root) {
+                t_children.push(visit.page_number);
+            }
+            Ok(())
+        })
+        .unwrap();
+    assert!(t_children.len() > 2);
+
+    // The right-most child pointer of t's root now names a leaf of u
+    patch_page(&db_path, t_root, 8, &u_leaf.to_be_bytes());
+    assert_eq!(
+        reference_issues(),
+        vec![format!(
+            "Page {} is a child of page {} in 't' and of page {} in 'u'",
+            u_leaf, t_root, u_root
+        )]
+    );
+
+    // And then u's root page itself
+    patch_page(&db_path, t_root, 8, &u_root.to_be_bytes());
+    assert_eq!(
+        reference_issues(),
+        vec![format!(
+            "Page {} is the root page of 'u' but is also a child of page {} in 't'",
+            u_root, t_root
+        )]
+    );
+}
+
+#[test]
+fn test_index_keys_overflowing_with_room_left_on_page() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+
+    // Keys just over the local limit of a 4096-byte page still overflow,
+    // even though later cells leave more than enough bytes after them
+    let dir = TempDir::new().unwrap();
+    let (db_path, _) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=4096;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, body TEXT);
+         CREATE UNIQUE INDEX t_body ON t (body);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 20)
+             INSERT INTO t SELECT i, printf('%.1100c', 'x') || i FROM n;",
+    );
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let index = scanner
+        .discover_btrees()
+        .unwrap()
+        .into_iter()
+        .find(|b| b.name.as_deref() == Some("t_body"))
+        .unwrap();
+    let mut rowids = scanner.collect_index_rowids(index.root_page).unwrap();
+    rowids.sort_unstable();
+    assert_eq!(rowids, (1..=20).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_ndjson_writes_one_issue_per_line() {
+    use wal_validator::validators::{IssueLocation, Severity, ValidationIssue};
+
+    let issues = vec![
+        ValidationIssue::new("root-page", Severity::Error, "a", IssueLocation::Database, None),
+        ValidationIssue::new(
+            "page-layout",
+            Severity::Warning,
+            "b\nc",
+            IssueLocation::Page { page_number: 3 },
+            Some(1),
+        ),
+    ];
+
+    let mut output = Vec::new();
+    wal_validator::report::write_ndjson(&mut output, &issues).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<serde_json::Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["validator"], "root-page");
+    assert_eq!(lines[1]["message"], "b\nc");
+    assert_eq!(lines[1]["location"]["page_number"], 3);
+}
+
+#[test]
+fn test_json_issues_written_as_array() {
+    use wal_validator::btree::RowidLocation;
+    use wal_validator::validators::{DuplicateEntry, IssueLocation, Severity, ValidationIssue};
+
+    let entry = DuplicateEntry {
+        key: 7,
+        locations: vec![
+            RowidLocation {
+                page_number: 2,
+                cell_index: 0,
+                frame_index: None,
+            },
+            RowidLocation {
+                page_number: 4,
+                cell_index: 3,
+                frame_index: Some(5),
+            },
+        ],
+        rowids: Vec::new(),
+    };
+    let issues = vec![
+        ValidationIssue::duplicate_rowids(
+            "duplicate-rowid",
+            Some("t".into()),
+            2,
+            Some(1),
+            vec![entry],
+        ),
+        ValidationIssue::new("page-size", Severity::Warning, "a", IssueLocation::Database, None),
+    ];
+
+    let mut output = Vec::new();
+    wal_validator::report::write_json_issues(&mut output, &issues).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let array = json.as_array().unwrap();
+    assert_eq!(array.len(), 2);
+
+    let duplicate = &array[0];
+    assert_eq!(duplicate["validator"], "duplicate-rowid");
+    assert_eq!(duplicate["severity"], "error");
+    assert_eq!(duplicate["commit_index"], 1);
+    assert_eq!(duplicate["location"]["type"], "table");
+    assert_eq!(duplicate["location"]["name"], "t");
+    let details = &duplicate["duplicate_details"];
+    assert_eq!(details["kind"], "rowid");
+    assert_eq!(details["entries"][0]["key"], 7);
+    let locations = &details["entries"][0]["locations"];
+    assert_eq!(locations[0]["page_number"], 2);
+    assert_eq!(locations[0]["cell_index"], 0);
+    assert!(locations[0]["frame_index"].is_null());
+    assert_eq!(locations[1]["page_number"], 4);
+    assert_eq!(locations[1]["cell_index"], 3);
+    assert_eq!(locations[1]["frame_index"], 5);
+
+    assert_eq!(array[1]["severity"], "warning");
+    assert_eq!(array[1]["location"]["type"], "database");
+    assert!(array[1]["commit_index"].is_null());
+}
+
+#[test]
+fn test_report_summary_counts_issues() {
+    use wal_validator::btree::RowidLocation;
+    use wal_validator::report::ReportSummary;
+    use wal_validator::validators::{DuplicateEntry, IssueLocation, Severity, ValidationIssue};
+
+    let entry = |key| DuplicateEntry {
+        key,
+        locations: vec![
+            RowidLocation {
+                page_number: 2,
+                cell_index: 0,
+                frame_index: None,
+            };
+            2
+        ],
+        rowids: Vec::new(),
+    };
+    let issues = vec![
+        ValidationIssue::new("root-page", Severity::Error, "a", IssueLocation::Database, None),
+        ValidationIssue::duplicate_rowids("duplicate-rowid", None, 2, Some(1), vec![entry(1)]),
+        ValidationIssue::duplicate_rowids(
+            "duplicate-rowid",
+            None,
+            2,
+            Some(2),
+            vec![entry(1), entry(5)],
+        ),
+        ValidationIssue::new("new-pages", Severity::Warning, "b", IssueLocation::Database, Some(2)),
+        ValidationIssue::new("commit-sequence", Severity::Info, "c", IssueLocation::Database, None),
+    ];
+
+    let summary = ReportSummary::from_issues(&issues);
+    assert_eq!(summary.total_issues, 5);
+    assert_eq!((summary.base_issues, summary.wal_issues), (2, 3));
+    assert_eq!(
+        summary.by_validator.into_iter().collect::<Vec<_>>(),
+        [("commit-sequence", 1), ("duplicate-rowid", 2), ("new-pages", 1), ("root-page", 1)]
+    );
+    assert_eq!(
+        summary.by_severity,
+        [(Severity::Error, 3), (Severity::Warning, 1), (Severity::Info, 1)]
+    );
+    assert_eq!(summary.duplicate_entries, 3);
+    assert_eq!(ReportSummary::from_issues(&[]), ReportSummary::default());
+
+    let mut out = Vec::new();
+    let run = wal_validator::ValidationSummary {
+        issues,
+        ..Default::default()
+    };
+    wal_validator::report::write_json_report(&mut out, &run, None, false).unwrap();
+    let report: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(report["summary"]["by_severity"]["error"], 3);
+    assert_eq!(report["summary"]["by_validator"]["duplicate-rowid"], 2);
+}
+
+#[test]
+fn test_root_page_type_mismatch_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE a (id INTEGER PRIMARY KEY, v TEXT);
+         CREATE INDEX a_v ON a(v);
+         CREATE TABLE w (k TEXT PRIMARY KEY, v TEXT) WITHOUT ROWID;
+         PRAGMA writable_schema=ON;
+         UPDATE sqlite_master SET rootpage = (SELECT rootpage FROM sqlite_master WHERE name = 'a')
+             WHERE name = 'w';
+         UPDATE sqlite_master SET rootpage = (SELECT rootpage FROM sqlite_master WHERE name = 'a_v')
+             WHERE name = 'a';",
+    );
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+
+    let mismatches: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "root-page" && i.message.contains(" but its root page "))
+        .collect();
+    assert_eq!(mismatches.len(), 2, "issues: {:?}", summary.issues);
+    assert!(mismatches[0].message.starts_with("'a' is declared as a table"));
+    assert!(mismatches[1].message.starts_with("'w' is a WITHOUT ROWID table"));
+    assert!(mismatches[1].message.ends_with("is a table (rowid) page, not an index page"));
+}
+
+#[test]
+fn test_validate_bytes_matches_validate() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+        CREATE TABLE b (id INTEGER PRIMARY KEY);
+        INSERT INTO a VALUES (1), (2);
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET rootpage = (SELECT rootpage FROM sqlite_master WHERE name = 'a')
+            WHERE name = 'b';
+    ",
+    );
+
+    let config = ValidatorConfig::default();
+    let from_files = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+
+    let db = std::fs::read(&db_path).unwrap();
+    let wal = std::fs::read(&wal_path).unwrap();
+    let from_bytes = wal_validator::validate_bytes(&db, &wal, &config).unwrap();
+
+    assert!(!from_bytes.issues.is_empty());
+    assert_eq!(from_bytes.total_commits, from_files.total_commits);
+    assert_eq!(from_bytes.final_db_size, from_files.final_db_size);
+    let messages = |summary: &wal_validator::ValidationSummary| -> Vec<String> {
+        summary.issues.iter().map(|i| i.message.clone()).collect()
+    };
+    assert_eq!(messages(&from_bytes), messages(&from_files));
+
+    // An empty WAL is just a base database with no commits
+    let summary = wal_validator::validate_bytes(&db, &[], &config).unwrap();
+    assert_eq!(summary.total_commits, 0);
+}
+
+#[test]
+fn test_fill_factor_reported_and_impossible_one_flagged() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=512;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 30)
+             INSERT INTO t SELECT i, 'x' FROM n;",
+    );
+    let config = ValidatorConfig {
+        stats: true,
+        ..ValidatorConfig::default()
+    };
+
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(summary.issues.is_empty(), "issues: {:?}", summary.issues);
+    let fill = summary
+        .fill_factors
+        .iter()
+        .find(|f| f.name.as_deref() == Some("t"))
+        .unwrap();
+    assert_eq!(fill.leaf_pages, 1);
+    assert!(fill.ratio() > 0.0 && fill.ratio() < 1.0);
+
+    // Make every cell claim a 127-byte payload, more than the page can hold
+    let page = std::fs::read(&db_path).unwrap()[512..1024].to_vec();
+    let cell_count = u16::from_be_bytes([page[3], page[4]]) as usize;
+    for i in 0..cell_count {
+        let cell_ptr = u16::from_be_bytes([page[8 + 2 * i], page[9 + 2 * i]]) as usize;
+        patch_page(&db_path, 2, cell_ptr, &[0x7f]);
+    }
+
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(
+        summary.issues.iter().any(|i| i.validator == "fill-factor"),
+        "issues: {:?}",
+        summary.issues
+    );
+
+    // A tree that can't be walked is skipped with a warning
+    patch_page(&db_path, 2, 0, &[0]);
+    let config = ValidatorConfig::builder().stats(true).only(["fill-factor"]).build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(
+        summary.issues.iter().any(|i| i.validator == "fill-factor"
+            && i.severity == wal_validator::validators::Severity::Warning
+            && i.message.contains("can't be walked")),
+        "issues: {:?}",
+        summary.issues
+    );
+}
+
+#[test]
+fn test_unknown_wal_format_version_rejected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    wal[4..8].copy_from_slice(&3007001u32.to_be_bytes());
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &wal).unwrap();
+
+    let result = wal_validator::validate(&db_path, &patched_path, &ValidatorConfig::default());
+    assert!(matches!(
+        result,
+        Err(wal_validator::error::WalValidatorError::UnsupportedWalVersion(3007001))
+    ));
+
+    // Frame headers are only parsed under a known layout
+    use wal_validator::wal::{FrameHeader, WalHeader};
+    let header = WalHeader::parse(&wal[..32]).unwrap();
+    assert!(matches!(
+        FrameHeader::parse(&wal[32..56], &header),
+        Err(wal_validator::error::WalValidatorError::UnsupportedWalVersion(3007001))
+    ));
+    let supported = WalHeader::parse(&std::fs::read(&wal_path).unwrap()[..32]).unwrap();
+    assert!(FrameHeader::parse(&wal[32..56], &supported).is_ok());
+}
+
+#[test]
+fn test_page_history_lists_commits_writing_a_page() {
+    let dir = TempDir::new().unwrap();
+    let (_db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE a (id INTEGER PRIMARY KEY);
+        CREATE TABLE b (id INTEGER PRIMARY KEY);
+        INSERT INTO a VALUES (1);
+        INSERT INTO b VALUES (1);
+        INSERT INTO a VALUES (2);
+    ",
+    );
+
+    // Table a lives on page 2, created by commit 0 and written by both of its inserts
+    let writes = wal_validator::wal::page_history(&wal_path, 2).unwrap();
+    let commits: Vec<u64> = writes.iter().map(|w| w.commit_index).collect();
+    assert_eq!(commits, vec![0, 2, 4]);
+
+    // Each insert commit writes only page 2, so its frame ends the commit
+    assert!(writes[1].is_commit_frame && writes[2].is_commit_frame);
+
+    assert!(wal_validator::wal::page_history(&wal_path, 99).unwrap().is_empty());
+}
+
+#[test]
+fn test_frame_file_offsets_locate_frames() {
+    let dir = TempDir::new().unwrap();
+    let (_db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    let wal = std::fs::read(&wal_path).unwrap();
+    let commits = wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap();
+    let page_size = commits.wal_header().page_size;
+    let mut frames = 0;
+    for commit in commits {
+        let commit = commit.unwrap();
+        assert_eq!(commit.start_offset, commit.frames[0].file_offset(page_size));
+        for frame in &commit.frames {
+            // The frame header starts with its page number, and the page follows it
+            let offset = frame.file_offset(page_size) as usize;
+            assert_eq!(wal[offset..offset + 4], frame.header.page_number.to_be_bytes());
+            assert_eq!(wal[offset + 24..offset + 24 + page_size as usize], frame.page_data[..]);
+            frames += 1;
+        }
+    }
+    assert!(frames > 1);
+}
+
+#[test]
+fn test_commit_positions_recorded_in_summary() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.commit_positions.is_empty());
+
+    let config = ValidatorConfig::builder().commit_positions(true).build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let commits = wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap();
+    let expected: Vec<_> = commits
+        .map(|commit| wal_validator::CommitPosition::of(&commit.unwrap()))
+        .collect();
+    assert!(expected.len() > 1);
+    assert_eq!(summary.commit_positions, expected);
+    for pair in summary.commit_positions.windows(2) {
+        assert_eq!(pair[0].end_offset, pair[1].start_offset);
+        assert_eq!(pair[0].last_frame + 1, pair[1].first_frame);
+    }
+
+    let mut json = Vec::new();
+    wal_validator::report::write_json_report(&mut json, &summary, None, false).unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    let positions = json["commit_positions"].as_array().unwrap();
+    assert_eq!(positions.len(), expected.len());
+    assert_eq!(positions[1]["start_offset"], expected[1].start_offset);
+    assert_eq!(positions[1]["end_offset"], expected[1].end_offset);
+}
+
+#[test]
+fn test_nonzero_reserved_bytes_detected() {
+    use wal_validator::validators::IssueLocation;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+         INSERT INTO t VALUES (1, 'a');",
+    );
+
+    // Claim 8 reserved bytes per page; the last cell of the table page sits
+    // at the very end of the page, so its bytes now land in the reserved region
+    patch_page(&db_path, 1, 20, &[8]);
+
+    let config = ValidatorConfig {
+        check_reserved_bytes: true,
+        ..ValidatorConfig::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(
+        summary.issues.iter().any(|i| i.validator == "reserved-bytes"
+            && matches!(i.location, IssueLocation::Page { page_number: 2 })),
+        "issues: {:?}",
+        summary.issues
+    );
+
+    // The check is opt-in
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.iter().all(|i| i.validator != "reserved-bytes"));
+}
+
+#[test]
+fn test_database_with_reserved_bytes_validated() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+    use wal_validator::validators::Severity;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) =
+        create_checkpointed_db(&dir, "CREATE TABLE x (a); DROP TABLE x; VACUUM;");
+
+    // SQLite keeps the reserved bytes of an existing database, so reserving
+    // them on the empty page 1 makes every later page leave them unused. The
+    // empty cell content area of page 1 starts at the end of its usable space.
+    let page_size = wal_validator::db::DbHeader::from_file(&db_path).unwrap().page_size;
+    patch_page(&db_path, 1, 20, &[32]);
+    patch_page(&db_path, 1, 105, &(page_size as u16 - 32).to_be_bytes());
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "PRAGMA journal_mode=WAL;
+         PRAGMA wal_autocheckpoint=0;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+         CREATE INDEX t_v ON t (v);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 500)
+             INSERT INTO t SELECT i, printf('row %05d', i) FROM n;
+         DELETE FROM t WHERE id % 7 = 0;",
+    )
+    .unwrap();
+    let check: String = conn.query_row("PRAGMA integrity_check", [], |r| r.get(0)).unwrap();
+    assert_eq!(check, "ok");
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    assert_eq!(header.reserved_bytes, 32);
+
+    let config = ValidatorConfig::builder().check_reserved_bytes(true).build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(summary.total_commits > 0);
+    assert!(
+        summary.issues.iter().all(|i| i.severity == Severity::Info),
+        "issues: {:?}",
+        summary.issues
+    );
+
+    // A cell pointer into the reserved region points at no cell, even when
+    // the bytes there happen to parse as one (rowid 9999)
+    conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);").unwrap();
+    drop(conn);
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let table_rowids = || {
+        let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+        let mut scanner = BTreeScanner::new(&mut page_cache);
+        let root = scanner.discover_btrees().unwrap()[0].root_page;
+        scanner.collect_table_rowids(root).unwrap()
+    };
+    let rowids = table_rowids();
+    let leaf = rowids[0].1.page_number;
+    let cell = header.page_size as usize - 16;
+    patch_page(&db_path, leaf, cell, &[0x01, 0xCE, 0x0F, 0x01]);
+    patch_page(&db_path, leaf, 8, &(cell as u16).to_be_bytes());
+    let patched = table_rowids();
+    assert_eq!(patched.len(), rowids.len() - 1);
+    assert!(patched.iter().all(|(rowid, _)| *rowid != 9999));
+}
+
+#[test]
+fn test_first_divergence_after_checkpoint() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    // The passive checkpoint copied every frame into the database file
+    assert_eq!(wal_validator::first_divergence(&db_path, &wal_path).unwrap(), None);
+
+    patch_page(&db_path, 2, 100, &[0xab]);
+    assert_eq!(wal_validator::first_divergence(&db_path, &wal_path).unwrap(), Some(2));
+}
+
+#[test]
+fn test_cell_extending_past_page_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+         INSERT INTO t VALUES (1, 'a'), (2, 'b');",
+    );
+
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.is_empty(), "issues: {:?}", summary.issues);
+
+    // Grow the payload size of the cell at the end of the page from 4 to 127 bytes
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let page_size = header.page_size as usize;
+    let data = std::fs::read(&db_path).unwrap();
+    let page = &data[page_size..2 * page_size];
+    let last_cell = (0..u16::from_be_bytes([page[3], page[4]]) as usize)
+        .map(|i| u16::from_be_bytes([page[8 + 2 * i], page[9 + 2 * i]]) as usize)
+        .max()
+        .unwrap();
+    patch_page(&db_path, 2, last_cell, &[0x7f]);
+
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    let layout: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "page-layout")
+        .collect();
+    assert_eq!(layout.len(), 1, "issues: {:?}", summary.issues);
+    assert!(layout[0].message.contains("past the usable page size"));
+}
+
+#[test]
+fn test_unreadable_page_reported_by_page_layout() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+    use wal_validator::validators::IssueLocation;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=512;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, data BLOB);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+             INSERT INTO t SELECT i, zeroblob(100) FROM n;",
+    );
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let root = scanner.discover_btrees().unwrap()[0].root_page;
+    let leaf = scanner.collect_btree_pages(root).unwrap()[1];
+
+    // A leaf that isn't a B-tree page is a finding, not a failed run
+    patch_page(&db_path, leaf, 0, &[0]);
+    let config = ValidatorConfig::builder().only(["page-layout"]).build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let layout: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "page-layout")
+        .collect();
+    assert_eq!(layout.len(), 1, "issues: {:?}", summary.issues);
+    assert!(matches!(
+        layout[0].location,
+        IssueLocation::Page { page_number } if page_number == root
+    ));
+    assert!(layout[0].message.contains("can't be walked"), "{}", layout[0].message);
+}
+
+#[test]
+fn test_out_of_range_root_page_detected() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY);
+         PRAGMA writable_schema=ON;
+         UPDATE sqlite_master SET rootpage = 500 WHERE name = 't';",
+    );
+
+    // Reported as a finding instead of failing the run on a zero-filled page
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    let root_issues: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "root-page")
+        .collect();
+    assert_eq!(root_issues.len(), 1, "issues: {:?}", summary.issues);
+    assert!(root_issues[0].message.starts_with("object 't' has out-of-range root page 500"));
+}
+
+#[test]
+fn test_validator_timings() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.validator_timings.is_empty());
+
+    let config = ValidatorConfig {
+        time_validators: true,
+        ..ValidatorConfig::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+
+    // One entry per enabled validator, accumulated across states, slowest first
+    let mut names: Vec<&str> = summary.validator_timings.iter().map(|(name, _)| *name).collect();
+    let enabled = wal_validator::validators::enabled_validators(&config).len();
+    assert_eq!(names.len(), enabled);
+    assert!(names.contains(&"duplicate-rowid"));
+    assert!(summary.validator_timings.windows(2).all(|w| w[0].1 >= w[1].1));
+    names.sort_unstable();
+    names.dedup();
+    assert_eq!(names.len(), enabled);
+}
+
+#[test]
+fn test_page_dump_layout() {
+    use wal_validator::dump::{write_hex_dump, DumpOptions};
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+    let page = wal_validator::read_page(&db_path, &wal_path, 1).unwrap();
+
+    let mut out = Vec::new();
+    write_hex_dump(&mut out, &page, &DumpOptions::default()).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text.lines().count(), page.len() / 16);
+    assert_eq!(
+        text.lines().next().unwrap(),
+        "00000000  53 51 4c 69 74 65 20 66  6f 72 6d 61 74 20 33 00  |SQLite format 3.|"
+    );
+
+    let options = DumpOptions {
+        width: 32,
+        ascii: false,
+    };
+    let mut out = Vec::new();
+    write_hex_dump(&mut out, &page, &options).unwrap();
+    let text = String::from_utf8(out).unwrap();
+    assert_eq!(text.lines().count(), page.len() / 32);
+    assert!(text.lines().nth(1).unwrap().starts_with("00000020  "));
+    assert!(!text.contains('|'));
+}
+
+#[test]
+fn test_recover_skips_corrupt_page() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::recover::{recover, RecoverFormat};
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "PRAGMA page_size=1024;
+         CREATE TABLE t (id INTEGER PRIMARY KEY, body TEXT);
+         WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 200)
+             INSERT INTO t SELECT i, 'row ' || i || ', ' || printf('%.*c', i * 10, 'x') FROM n;",
+    );
+
+    // Break the page type of a leaf page away from the end of the table
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let root_page = scanner.discover_btrees().unwrap()[0].root_page;
+    let leaf = *scanner
+        .collect_btree_pages(root_page)
+        .unwrap()
+        .iter()
+        .find(|&&page| page != root_page)
+        .unwrap();
+    patch_page(&db_path, leaf, 0, &[0xff]);
+
+    let out_dir = dir.path().join("recovered");
+    let report = recover(&db_path, &wal_path, &out_dir, RecoverFormat::Csv).unwrap();
+    assert_eq!(report.tables.len(), 1);
+    let table = &report.tables[0];
+    assert_eq!(table.skipped_pages, vec![leaf]);
+    assert!(table.rows > 0 && table.rows < 200, "rows: {}", table.rows);
+
+    let csv = std::fs::read_to_string(out_dir.join("t.csv")).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("id,body"));
+    assert_eq!(lines.count() as u64, table.rows);
+
+    // Rows spilling onto overflow pages come back whole
+    let long_row = csv.lines().find(|l| l.starts_with("200,")).unwrap();
+    assert_eq!(long_row.len(), "200,\"row 200, \"".len() + 2000);
+}
+
+#[test]
+fn test_recover_empty_table_writes_csv_header() {
+    use wal_validator::recover::{recover, RecoverFormat};
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE empty (id INTEGER PRIMARY KEY, body TEXT);
+         CREATE TABLE plain (a, b);",
+    );
+
+    let out_dir = dir.path().join("recovered");
+    let report = recover(&db_path, &wal_path, &out_dir, RecoverFormat::Csv).unwrap();
+    assert_eq!(report.tables.len(), 2);
+    assert!(report.tables.iter().all(|t| t.rows == 0));
+
+    let empty = std::fs::read_to_string(out_dir.join("empty.csv")).unwrap();
+    assert_eq!(empty, "id,body\n");
+    let plain = std::fs::read_to_string(out_dir.join("plain.csv")).unwrap();
+    assert_eq!(plain, "rowid,a,b\n");
+}
+
+#[test]
+fn test_duplicate_unique_key_names_conflicting_rowids() {
+    use wal_validator::validators::DuplicateDetails;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        CREATE INDEX idx_x ON t (x);
+        INSERT INTO t VALUES ('z');
+        INSERT INTO t VALUES ('a');
+        INSERT INTO t VALUES ('b');
+        INSERT INTO t VALUES ('a');
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET sql = 'CREATE UNIQUE INDEX idx_x ON t (x)' WHERE name = 'idx_x';
+    ",
+    );
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let issue = summary
+        .issues
+        .iter()
+        .rfind(|i| i.validator == "duplicate-index-key")
+        .expect("duplicate key reported");
+
+    let Some(DuplicateDetails::IndexKey(entries)) = &issue.duplicate_details else {
+        panic!("expected index key details: {:?}", issue);
+    };
+    assert_eq!(entries.len(), 1);
+    let mut rowids = entries[0].rowids.clone();
+    rowids.sort();
+    assert_eq!(rowids, vec![Some(2), Some(4)]);
+}
+
+#[test]
+fn test_duplicate_unique_key_with_rowids_of_different_sizes() {
+    use wal_validator::validators::DuplicateDetails;
+
+    let dir = TempDir::new().unwrap();
+
+    // Rowid 1 is stored in one byte and 300 in two, so the index records have
+    // different headers even though their keys are equal
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        CREATE INDEX idx_x ON t (x);
+        INSERT INTO t (rowid, x) VALUES (1, 'a');
+        INSERT INTO t (rowid, x) VALUES (300, 'a');
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET sql = 'CREATE UNIQUE INDEX idx_x ON t (x)' WHERE name = 'idx_x';
+    ",
+    );
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let issue = summary
+        .issues
+        .iter()
+        .rfind(|i| i.validator == "duplicate-index-key")
+        .expect("duplicate key reported");
+
+    let Some(DuplicateDetails::IndexKey(entries)) = &issue.duplicate_details else {
+        panic!("expected index key details: {:?}", issue);
+    };
+    assert_eq!(entries.len(), 1);
+    let mut rowids = entries[0].rowids.clone();
+    rowids.sort();
+    assert_eq!(rowids, vec![Some(1), Some(300)]);
+}
+
+#[test]
+fn test_csv_report_round_trips() {
+    use wal_validator::btree::{IndexKey, RowidLocation};
+    use wal_validator::validators::{DuplicateEntry, ValidationIssue};
+
+    let location = |page_number, cell_index, frame_index| RowidLocation {
+        page_number,
+        cell_index,
+        frame_index,
+    };
+    let issues = vec![
+        ValidationIssue::duplicate_rowids(
+            "duplicate-rowid",
+            Some("odd, \"name\"".to_string()),
+            2,
+            None,
+            vec![DuplicateEntry {
+                key: 7,
+                locations: vec![location(3, 0, None), location(4, 1, Some(5))],
+                rowids: Vec::new(),
+            }],
+        ),
+        ValidationIssue::duplicate_index_keys(
+            "duplicate-index-key",
+            Some("idx".to_string()),
+            6,
+            Some(3),
+            vec![DuplicateEntry {
+                key: IndexKey {
+                    raw: vec![0x02, 0x0f, b'a'],
+                },
+                locations: vec![location(6, 2, Some(9))],
+                rowids: vec![Some(1)],
+            }],
+        ),
+    ];
+
+    let mut out = Vec::new();
+    wal_validator::report::write_csv(&mut out, &issues).unwrap();
+
+    let mut reader = csv::Reader::from_reader(&out[..]);
+    let header: Vec<_> = reader.headers().unwrap().iter().map(str::to_string).collect();
+    assert_eq!(
+        header,
+        [
+            "validator",
+            "table_or_index",
+            "root_page",
+            "key_or_rowid",
+            "page_number",
+            "cell_index",
+            "frame_index",
+            "commit_index",
+        ]
+    );
+    let rows: Vec<Vec<String>> = reader
+        .records()
+        .map(|r| r.unwrap().iter().map(str::to_string).collect())
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ["duplicate-rowid", "odd, \"name\"", "2", "7", "3", "0", "", ""],
+            ["duplicate-rowid", "odd, \"name\"", "2", "7", "4", "1", "5", ""],
+            ["duplicate-index-key", "idx", "6", "020f61", "6", "2", "9", "3"],
+        ]
+    );
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_parallel_validation_matches_sequential() {
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch(
+        "
+        PRAGMA journal_mode=WAL;
+        PRAGMA wal_autocheckpoint=0;
+        CREATE TABLE t (x);
+        CREATE INDEX idx_x ON t (x);
+    ",
+    )
+    .unwrap();
+    for i in 0..12 {
+        conn.execute("INSERT INTO t VALUES (?1)", [i % 5]).unwrap();
+    }
+    // Declaring the index unique makes every later state hold duplicate keys
+    conn.execute_batch(
+        "
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET sql = 'CREATE UNIQUE INDEX idx_x ON t (x)' WHERE name = 'idx_x';
+        PRAGMA writable_schema=OFF;
+    ",
+    )
+    .unwrap();
+    for i in 0..12 {
+        conn.execute("INSERT INTO t VALUES (?1)", [i % 3]).unwrap();
+    }
+    conn.execute_batch("CREATE TABLE u (y);").unwrap();
+    std::mem::forget(conn);
+
+    let describe = |summary: &wal_validator::ValidationSummary| {
+        let mut issues: Vec<_> = summary
+            .issues
+            .iter()
+            .map(|i| (i.commit_index, i.validator, i.message.clone()))
+            .collect();
+        issues.sort();
+        issues
+    };
+
+    let config = ValidatorConfig::thorough()
+        .into_builder()
+        .describe_commits(true)
+        .check_change_counter(true)
+        .build();
+    let sequential = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let expected = describe(&sequential);
+    assert!(expected.iter().any(|(_, v, _)| *v == "duplicate-index-key"));
+    assert!(expected.iter().any(|(_, v, _)| *v == "schema-cookie"));
+
+    for threads in [1, 3, 8] {
+        let parallel =
+            wal_validator::validate_parallel(&db_path, &wal_path, &config, threads).unwrap();
+        assert_eq!(describe(&parallel), expected, "with {} thread(s)", threads);
+        assert_eq!(parallel.total_commits, sequential.total_commits);
+        assert_eq!(parallel.final_db_size, sequential.final_db_size);
+    }
+}
+
+#[test]
+fn test_noop_commit_after_checkpoint_skipped() {
+    use wal_validator::validators::Severity;
+
+    let dir = TempDir::new().unwrap();
+
+    // A passive checkpoint copies the only commit into the base file but
+    // leaves its frames in the WAL, so replaying it changes nothing
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        BEGIN;
+        CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+        INSERT INTO users VALUES (1, 'Alice');
+        COMMIT;
+        PRAGMA wal_checkpoint(PASSIVE);
+    ",
+    );
+
+    let config = ValidatorConfig {
+        skip_noop_commits: true,
+        ..Default::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert_eq!(summary.total_commits, 1);
+    assert_eq!(summary.noop_commits, 1);
+    assert_eq!(summary.issues.len(), 1, "issues: {:?}", summary.issues);
+    assert_eq!(summary.issues[0].validator, "noop-commit");
+    assert_eq!(summary.issues[0].severity, Severity::Info);
+    assert_eq!(summary.issues[0].commit_index, Some(0));
+
+    // Without the option nothing is counted or skipped
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert_eq!(summary.noop_commits, 0);
+    assert!(summary.issues.is_empty());
+}
+
+#[test]
+fn test_identical_page_rewrite_reported_as_noop_frame() {
+    use wal_validator::validators::Severity;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+        INSERT INTO users VALUES (1, 'Alice');
+    ",
+    );
+
+    // Commit the last commit's pages a second time, byte for byte
+    let commits = wal_validator::wal::CommitIterator::new(&wal_path).unwrap().unwrap();
+    let page_size = commits.wal_header().page_size;
+    let last = commits.last().unwrap().unwrap();
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let copy = wal[last.frames[0].file_offset(page_size) as usize..].to_vec();
+    wal.extend_from_slice(&copy);
+    let patched_path = dir.path().join("patched.db-wal");
+    std::fs::write(&patched_path, &wal).unwrap();
+    let repaired_path = dir.path().join("repaired.db-wal");
+    wal_validator::wal::repair_checksums(&patched_path, &repaired_path).unwrap();
+    let frames = last.frames.len();
+
+    let config = ValidatorConfig {
+        detect_noop_frames: true,
+        ..Default::default()
+    };
+    let summary = wal_validator::validate(&db_path, &repaired_path, &config).unwrap();
+    assert_eq!(summary.noop_frames, frames as u64);
+    let noop: Vec<_> = summary.issues.iter().filter(|i| i.validator == "noop-frame").collect();
+    assert_eq!(noop.len(), 1, "issues: {:?}", summary.issues);
+    assert_eq!(noop[0].severity, Severity::Info);
+    assert_eq!(
+        noop[0].message,
+        format!(
+            "{} frame(s) rewrote pages with identical content; \
+             1 commit(s) left the database unchanged",
+            frames
+        )
+    );
+
+    let mut json = Vec::new();
+    wal_validator::report::write_json_report(&mut json, &summary, None, false).unwrap();
+    assert!(String::from_utf8(json).unwrap().contains("\"validator\":\"noop-frame\""));
+
+    let summary =
+        wal_validator::validate(&db_path, &repaired_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.iter().all(|i| i.validator != "noop-frame"));
+}
+
+#[test]
+fn test_expected_schema_drift_reported() {
+    use wal_validator::validators::Severity;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT);
+         CREATE TABLE audit (id INTEGER PRIMARY KEY, note TEXT);
+         CREATE INDEX idx_email ON users (email);",
+    );
+
+    let schema_path = dir.path().join("schema.txt");
+    std::fs::write(
+        &schema_path,
+        "# users and their indexes\n\
+         table users\n\
+         index idx_email create index idx_email on users(email)\n\
+         index \"idx users name\"\n",
+    )
+    .unwrap();
+
+    let issues =
+        wal_validator::check_expected_schema(&db_path, &wal_path, &schema_path).unwrap();
+    let messages: Vec<_> = issues.iter().map(|i| (i.severity, i.message.as_str())).collect();
+    assert_eq!(
+        messages,
+        vec![
+            (Severity::Error, "Expected index 'idx users name' is missing"),
+            (Severity::Warning, "Unexpected table 'audit'"),
+        ]
+    );
+
+    // A changed definition is an error too
+    std::fs::write(&schema_path, "index idx_email CREATE INDEX idx_email ON users (id)\n").unwrap();
+    let issues =
+        wal_validator::check_expected_schema(&db_path, &wal_path, &schema_path).unwrap();
+    assert!(issues
+        .iter()
+        .any(|i| i.severity == Severity::Error && i.message.starts_with("Index 'idx_email'")));
+}
+
+#[test]
+fn test_shm_max_frame_limits_replay() {
+    use wal_validator::validators::Severity;
+    use wal_validator::wal::ShmHeader;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        INSERT INTO t VALUES (1);
+        INSERT INTO t VALUES (2);
+    ",
+    );
+    let shm_path = dir.path().join("test.db-shm");
+
+    let shm = ShmHeader::from_file(&shm_path).unwrap();
+    assert!(shm.describes(&wal_validator::wal::WalHeader::from_file(&wal_path).unwrap()));
+    assert_eq!(shm.max_frame, 4);
+
+    let config = ValidatorConfig {
+        max_frame: Some(shm.max_frame as u64),
+        ..Default::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert_eq!(summary.total_commits, 3);
+    assert!(summary.issues.is_empty(), "issues: {:?}", summary.issues);
+
+    // Pretend the last commit was never acknowledged in the WAL-index
+    let mut data = std::fs::read(&shm_path).unwrap();
+    for copy in [0, 48] {
+        data[copy + 16..copy + 20].copy_from_slice(&3u32.to_ne_bytes());
+    }
+    let patched_path = dir.path().join("patched.db-shm");
+    std::fs::write(&patched_path, &data).unwrap();
+    let shm = ShmHeader::from_file(&patched_path).unwrap();
+
+    let config = ValidatorConfig {
+        max_frame: Some(shm.max_frame as u64),
+        ..Default::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert_eq!(summary.total_commits, 2);
+    assert_eq!(summary.issues.len(), 1, "issues: {:?}", summary.issues);
+    assert_eq!(summary.issues[0].severity, Severity::Info);
+    assert!(summary.issues[0].message.starts_with("1 frame(s) present in WAL beyond"));
+
+    // The cross-check still sees the commit past mxFrame
+    assert_eq!((summary.committed_frames, summary.wal_committed_frames), (3, 4));
+    let issues = wal_validator::check_shm(&patched_path, &summary);
+    assert!(
+        issues.iter().any(|i| i.message
+            == "WAL-index marks 3 frame(s) as committed, but the WAL holds 4 committed frame(s)"),
+        "issues: {:?}",
+        issues
+    );
+}
+
+#[test]
+fn test_older_wal_generation_followed_past_salt_change() {
+    use wal_validator::wal::{CommitIterator, CommitIteratorOptions};
+
+    let dir = TempDir::new().unwrap();
+
+    // After a complete checkpoint the next write restarts the WAL from the
+    // start under new salts, leaving the older frames after it
+    let (_db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        INSERT INTO t VALUES (1);
+        INSERT INTO t VALUES (2);
+        INSERT INTO t VALUES (3);
+        INSERT INTO t VALUES (4);
+        PRAGMA wal_checkpoint(PASSIVE);
+        INSERT INTO t VALUES (5);
+    ",
+    );
+
+    let commits: Vec<_> = CommitIterator::new(&wal_path)
+        .unwrap()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(commits.len(), 1);
+    assert_eq!(commits[0].generation, 0);
+
+    let options = CommitIteratorOptions {
+        follow_generations: true,
+        ..CommitIteratorOptions::default()
+    };
+    let commits: Vec<_> = CommitIterator::new_with_options(&wal_path, options)
+        .unwrap()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let generations: Vec<_> = commits.iter().map(|c| c.generation).collect();
+
+    // The CREATE TABLE commit (2 frames) lost its first frame to the new
+    // generation, so what survives of it is dropped; the four INSERTs chain
+    // from its surviving commit frame and are verified
+    assert_eq!(generations, vec![0, 1, 1, 1, 1]);
+    assert_eq!(commits[1].frames[0].frame_index, 2);
+    assert!(commits[1..].iter().all(|c| c.checksums_verified()));
+}
+
+#[test]
+fn test_incomplete_trailing_commit_surfaced() {
+    use wal_validator::error::WalValidatorError;
+    use wal_validator::wal::{CommitIterator, CommitIteratorOptions};
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        INSERT INTO t VALUES (zeroblob(20000));
+    ",
+    );
+
+    let commits: Vec<_> = CommitIterator::new(&wal_path)
+        .unwrap()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let last = commits.last().unwrap();
+    let start_frame = last.frames[0].frame_index;
+    let frames_in_last = last.frames.len();
+    assert!(frames_in_last > 2);
+
+    // Cut off the commit frame, as if the writer died mid-transaction
+    let wal = std::fs::read(&wal_path).unwrap();
+    let frame_size = 24 + 4096;
+    std::fs::write(&wal_path, &wal[..wal.len() - frame_size]).unwrap();
+
+    let mut commit_iter = CommitIterator::new(&wal_path).unwrap().unwrap();
+    assert_eq!(commit_iter.by_ref().count(), commits.len() - 1);
+    let tail = commit_iter.incomplete_tail();
+    assert_eq!(tail.len(), frames_in_last - 1);
+    assert_eq!(tail[0].frame_index, start_frame);
+
+    let options = CommitIteratorOptions {
+        strict: true,
+        ..CommitIteratorOptions::default()
+    };
+    let result: Result<Vec<_>, _> = CommitIterator::new_with_options(&wal_path, options)
+        .unwrap()
+        .unwrap()
+        .collect();
+    assert!(matches!(
+        result,
+        Err(WalValidatorError::IncompleteCommit { start_frame: s }) if s == start_frame
+    ));
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(summary.issues.iter().any(|i| i.validator == "incomplete-commit"
+        && i.message.contains(&format!("{} frame(s)", frames_in_last - 1))));
+}
+
+#[test]
+fn test_frame_iterator_reports_running_checksum_at_failure() {
+    use wal_validator::error::WalValidatorError;
+    use wal_validator::wal::FrameIterator;
+
+    let dir = TempDir::new().unwrap();
+    let (_db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        INSERT INTO t VALUES (zeroblob(20000));
+    ",
+    );
+
+    let frames: Vec<_> = FrameIterator::new(&wal_path)
+        .unwrap()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let commits = wal_validator::wal::CommitIterator::new(&wal_path)
+        .unwrap()
+        .unwrap()
+        .count();
+    assert_eq!(frames.iter().filter(|f| f.header.is_commit()).count(), commits);
+
+    // Flip a byte in the page data of a frame in the middle of the last
+    // transaction, which is not a commit frame
+    let bad = frames.len() - 2;
+    assert!(!frames[bad].header.is_commit() && !frames[bad - 1].header.is_commit());
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let offset = 32 + bad * (24 + 4096) + 24 + 100;
+    wal[offset] ^= 0xff;
+    std::fs::write(&wal_path, &wal).unwrap();
+
+    let mut frame_iter = FrameIterator::new(&wal_path).unwrap().unwrap();
+    for frame in frame_iter.by_ref().take(bad) {
+        frame.unwrap();
+    }
+    assert!(matches!(
+        frame_iter.next(),
+        Some(Err(WalValidatorError::ChecksumMismatch { frame_index })) if frame_index == bad as u64
+    ));
+    let previous = &frames[bad - 1].header;
+    assert_eq!(
+        frame_iter.running_checksum(),
+        (previous.checksum1, previous.checksum2)
+    );
+    assert!(frame_iter.next().is_none());
+}
+
+#[test]
+fn test_out_of_order_leaf_pages_detected() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+        CREATE INDEX t_v ON t (v);
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 500)
+            INSERT INTO t SELECT i, printf('%.*c', i % 40 + 1, 'k') || (500 - i) FROM n;
+    ",
+    );
+
+    // Text keys of different lengths are ordered by value, not by their
+    // serial types, so a healthy index passes
+    let config = ValidatorConfig {
+        check_ordering: true,
+        ..ValidatorConfig::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(summary.issues.is_empty(), "issues: {:?}", summary.issues);
+
+    // Swap the contents of the first and last leaf pages of the table
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let table = scanner
+        .discover_btrees()
+        .unwrap()
+        .into_iter()
+        .find(|b| b.name.as_deref() == Some("t"))
+        .unwrap();
+    let rowids = scanner.collect_table_rowids_in_order(table.root_page).unwrap();
+    let first = rowids.first().unwrap().1.page_number;
+    let last = rowids.last().unwrap().1.page_number;
+    assert_ne!(first, last);
+
+    let page_size = header.page_size as usize;
+    let mut data = std::fs::read(&db_path).unwrap();
+    let first_start = (first as usize - 1) * page_size;
+    let last_start = (last as usize - 1) * page_size;
+    let first_page = data[first_start..first_start + page_size].to_vec();
+    data.copy_within(last_start..last_start + page_size, first_start);
+    data[last_start..last_start + page_size].copy_from_slice(&first_page);
+    std::fs::write(&db_path, data).unwrap();
+
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let issues: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "key-order")
+        .collect();
+    assert_eq!(issues.len(), 1, "issues: {:?}", summary.issues);
+    assert!(issues[0].message.contains("of table 't'"));
+
+    // The page after the swapped-in last leaf is the first to go backwards
+    let second = rowids.iter().find(|(_, l)| l.page_number != first).unwrap().1.page_number;
+    assert!(matches!(
+        issues[0].location,
+        wal_validator::validators::IssueLocation::Page { page_number } if page_number == second
+    ));
+}
+
+#[test]
+fn test_autovacuum_ptrmap_entries_checked() {
+    use wal_validator::btree::{PtrmapLayout, PtrmapPage, PtrmapType};
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        PRAGMA page_size=1024;
+        PRAGMA auto_vacuum=INCREMENTAL;
+        CREATE TABLE t (id INTEGER PRIMARY KEY, body TEXT);
+        CREATE INDEX t_body ON t (body);
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 300)
+            INSERT INTO t SELECT i, printf('%.*c', i * 7 % 3000, 'x') || i FROM n;
+        DELETE FROM t WHERE id % 3 = 0;
+    ",
+    );
+
+    // Scanning walks past the pointer-map pages, and every entry checks out
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.is_empty(), "issues: {:?}", summary.issues);
+
+    let header = wal_validator::db::DbHeader::from_file(&db_path).unwrap();
+    let layout = PtrmapLayout::from_header(&header).unwrap();
+    assert!(layout.is_ptrmap_page(2));
+    let data = std::fs::read(&db_path).unwrap();
+    let page_size = header.page_size as usize;
+    let ptrmap = PtrmapPage::parse(&data[page_size..2 * page_size], 2, &layout, header.page_count);
+    assert_eq!(ptrmap.entries[0].page_number, 3);
+    assert!(ptrmap.entries.iter().any(|e| e.kind() == Some(PtrmapType::FreePage)));
+
+    // Point the entry of an overflow page at the wrong parent
+    let (index, entry) = ptrmap
+        .entries
+        .iter()
+        .enumerate()
+        .find(|(_, e)| e.kind() == Some(PtrmapType::Overflow1))
+        .unwrap();
+    let wrong_parent = entry.parent + 1;
+    patch_page(&db_path, 2, index * 5 + 1, &wrong_parent.to_be_bytes());
+
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert_eq!(summary.issues.len(), 1, "issues: {:?}", summary.issues);
+    assert_eq!(summary.issues[0].validator, "ptrmap");
+    assert!(summary.issues[0].message.contains(&format!(
+        "entry for page {} marks it as the first overflow page of a cell on page {}",
+        entry.page_number, wrong_parent
+    )));
+}
+
+#[test]
+fn test_validate_streaming_matches_validate() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        INSERT INTO t VALUES (1);
+        INSERT INTO t VALUES (zeroblob(20000));
+    ",
+    );
+
+    // Cut off the last commit frame so the run reports the uncommitted tail
+    let wal = std::fs::read(&wal_path).unwrap();
+    std::fs::write(&wal_path, &wal[..wal.len() - (24 + 4096)]).unwrap();
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(!summary.issues.is_empty());
+
+    let mut streamed = Vec::new();
+    let streamed_summary =
+        wal_validator::validate_streaming(&db_path, &wal_path, &config, |issue| {
+            streamed.push((issue.validator, issue.commit_index, issue.message.clone()));
+        })
+        .unwrap();
+
+    let collected: Vec<_> = summary
+        .issues
+        .iter()
+        .map(|issue| (issue.validator, issue.commit_index, issue.message.clone()))
+        .collect();
+    assert_eq!(streamed, collected);
+    assert!(streamed_summary.issues.is_empty());
+    assert_eq!(streamed_summary.total_commits, summary.total_commits);
+}
+
+#[test]
+fn test_shm_cross_checked_against_wal() {
+    use wal_validator::validators::Severity;
+    use wal_validator::wal::ShmHeader;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        INSERT INTO t VALUES (1);
+    ",
+    );
+    let shm_path = dir.path().join("test.db-shm");
+
+    assert!(ShmHeader::from_file(&shm_path).unwrap().checksum_matches);
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    let issues = wal_validator::check_shm(&shm_path, &summary);
+    assert!(issues.is_empty(), "issues: {:?}", issues);
+
+    // Claim an extra committed frame without updating the checksum
+    let mut data = std::fs::read(&shm_path).unwrap();
+    let max_frame = ShmHeader::parse(&data).unwrap().max_frame;
+    for copy in [0, 48] {
+        data[copy + 16..copy + 20].copy_from_slice(&(max_frame + 1).to_ne_bytes());
+    }
+    let patched_path = dir.path().join("patched.db-shm");
+    std::fs::write(&patched_path, &data).unwrap();
+
+    let issues = wal_validator::check_shm(&patched_path, &summary);
+    let messages: Vec<_> = issues.iter().map(|i| i.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "WAL-index header checksum doesn't match its contents".to_string(),
+            format!(
+                "WAL-index marks {} frame(s) as committed, but the WAL holds {} committed frame(s)",
+                max_frame + 1,
+                max_frame
+            ),
+        ]
+    );
+    assert!(issues.iter().all(|i| i.severity == Severity::Warning));
+
+    // Copies that disagree make the header unusable, which is still a warning
+    data[16] ^= 1;
+    std::fs::write(&patched_path, &data).unwrap();
+    let issues = wal_validator::check_shm(&patched_path, &summary);
+    assert_eq!(issues.len(), 1);
+    assert!(issues[0].message.starts_with("WAL-index header is unusable"));
+
+    // So is a file that can't be read at all
+    let issues = wal_validator::check_shm(&dir.path().join("missing.db-shm"), &summary);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].severity, Severity::Warning);
+    assert!(issues[0].message.starts_with("WAL-index can't be read"));
+}
+
+#[test]
+fn test_schema_cookie_changes_reported() {
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "
+        CREATE TABLE t (x);
+        INSERT INTO t VALUES (1);
+        CREATE INDEX t_x ON t (x);
+    ",
+    );
+
+    let config = ValidatorConfig {
+        describe_commits: true,
+        ..Default::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let changes: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "schema-cookie")
+        .map(|i| (i.commit_index, i.message.as_str()))
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            (Some(0), "Schema cookie changed from 0 to 1"),
+            (Some(2), "Schema cookie changed from 1 to 2"),
+        ]
+    );
+
+    // Off unless commits are described
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.iter().all(|i| i.validator != "schema-cookie"));
+}
+
+#[test]
+fn test_opened_page_reader_matches_per_read_access() {
+    use wal_validator::db::{DbHeader, PageReader};
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, _) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, v BLOB);
+        WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100)
+        INSERT INTO t SELECT i, randomblob(300) FROM n;
+    ",
+    );
+
+    let header = DbHeader::from_file(&db_path).unwrap();
+    let per_read = PageReader::new(&db_path, header.page_size, header.page_count);
+    let opened = PageReader::open(&db_path, header.page_size, header.page_count).unwrap();
+    assert_eq!(opened.file_size(), per_read.file_size());
+    for page_num in 1..=header.page_count {
+        assert_eq!(*opened.page(page_num).unwrap(), per_read.read_page(page_num).unwrap()[..]);
+    }
+    assert!(opened.page(header.page_count + 1).is_err());
+
+    // A page cache borrows pages an in-memory reader holds instead of copying them
+    let bytes = std::fs::read(&db_path).unwrap();
+    let in_memory = PageReader::from_bytes(bytes, header.page_size, header.page_count);
+    let mut page_cache = PageCache::from_reader(in_memory);
+    for page_num in 1..=header.page_count {
+        let expected = per_read.read_page(page_num).unwrap();
+        assert_eq!(page_cache.get_page_ref(page_num).unwrap(), &expected[..]);
+    }
+    assert_eq!(page_cache.cached_page_count(), 0);
+}
+
+#[test]
+fn test_commit_iterator_reads_frames_without_seeking() {
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use wal_validator::wal::CommitIterator;
+
+    /// A WAL source that counts the seeks made on it
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        seeks: std::rc::Rc<std::cell::Cell<usize>>,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.inner.read(buf)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.seeks.set(self.seeks.get() + 1);
+            self.inner.seek(pos)
+        }
+    }
+
+    let dir = TempDir::new().unwrap();
+    let (_, wal_path) = create_test_db_with_wal(&dir);
+    let seeks = std::rc::Rc::new(std::cell::Cell::new(0));
+    let reader = CountingReader {
+        inner: Cursor::new(std::fs::read(&wal_path).unwrap()),
+        seeks: seeks.clone(),
+    };
+
+    let mut commits = CommitIterator::from_reader(reader).unwrap().unwrap();
+    let seeks_for_header = seeks.get();
+    let frames: usize = commits.by_ref().map(|commit| commit.unwrap().frames.len()).sum();
+    assert!(frames > 3);
+    assert_eq!(seeks.get(), seeks_for_header);
+}
+
+#[test]
+fn test_seek_to_commit() {
+    use wal_validator::error::WalValidatorError;
+    use wal_validator::wal::CommitIterator;
+
+    let dir = TempDir::new().unwrap();
+    let (_, wal_path) = create_test_db_with_wal(&dir);
+
+    let commits: Vec<_> = CommitIterator::new(&wal_path)
+        .unwrap()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert!(commits.len() >= 3);
+
+    let mut iter = CommitIterator::new(&wal_path).unwrap().unwrap();
+    let first_frames = iter.build_commit_index().unwrap();
+    let expected: Vec<_> = commits.iter().map(|c| c.frames[0].frame_index).collect();
+    assert_eq!(first_frames, expected);
+
+    // Indexing leaves the iterator at the first commit
+    assert_eq!(iter.next().unwrap().unwrap().index, 0);
+
+    // Jump forward and back, yielding the same commits as a full pass
+    for n in [2, 1, 0, commits.len() as u64 - 1] {
+        iter.seek_to_commit(n).unwrap();
+        let commit = iter.next().unwrap().unwrap();
+        let expected = &commits[n as usize];
+        assert_eq!(commit.index, n);
+        assert_eq!(commit.start_offset, expected.start_offset);
+        assert_eq!(commit.frames.len(), expected.frames.len());
+        assert_eq!(commit.frames[0].page_data, expected.frames[0].page_data);
+    }
+    assert!(iter.next().is_none());
+
+    let result = iter.seek_to_commit(commits.len() as u64);
+    assert!(matches!(
+        result,
+        Err(WalValidatorError::CommitNotFound { commit_count, .. })
+            if commit_count == commits.len() as u64
+    ));
+}
+
+#[test]
+fn test_skip_bad_frames_and_unverified_checksums() {
+    use wal_validator::wal::{BadFrameAction, CommitIterator, CommitIteratorOptions};
+
+    let dir = TempDir::new().unwrap();
+    let (_, wal_path) = create_test_db_with_wal(&dir);
+
+    let iter = CommitIterator::new(&wal_path).unwrap().unwrap();
+    let page_size = iter.wal_header().page_size as u64;
+    let commits: Vec<_> = iter.collect::<Result<_, _>>().unwrap();
+    assert!(commits.len() >= 3);
+    assert!(commits.iter().all(|c| c.checksums_verified()));
+
+    // Corrupt the page data of the second commit, a single-frame INSERT
+    assert_eq!(commits[1].frames.len(), 1);
+    let bad_frame = commits[1].frames[0].frame_index;
+    let mut wal = std::fs::read(&wal_path).unwrap();
+    let offset = 32 + bad_frame * (24 + page_size) + 24 + page_size - 1;
+    wal[offset as usize] ^= 0xFF;
+    std::fs::write(&wal_path, wal).unwrap();
+
+    // By default the log ends at the bad frame
+    let strict: Vec<_> = CommitIterator::new(&wal_path)
+        .unwrap()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(strict.len(), 1);
+
+    let options = CommitIteratorOptions {
+        on_bad_frame: BadFrameAction::Skip,
+        ..CommitIteratorOptions::default()
+    };
+    let mut iter = CommitIterator::new_with_options(&wal_path, options).unwrap().unwrap();
+    let skipped: Vec<_> = iter.by_ref().collect::<Result<_, _>>().unwrap();
+    assert_eq!(iter.skipped_frames(), &[bad_frame]);
+    // The skipped commit is left out; the ones after it are still yielded
+    assert_eq!(skipped.len(), commits.len() - 1);
+    assert_eq!(skipped[1].frames[0].page_data, commits[2].frames[0].page_data);
+    assert!(skipped[0].checksums_verified());
+    // Nothing after the skipped frame can be verified against the chain
+    assert!(skipped[1..].iter().all(|c| !c.checksums_verified()));
+
+    let options = CommitIteratorOptions {
+        verify_checksums: false,
+        ..CommitIteratorOptions::default()
+    };
+    let unverified: Vec<_> = CommitIterator::new_with_options(&wal_path, options)
+        .unwrap()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(unverified.len(), commits.len());
+    assert!(unverified.iter().all(|c| !c.checksums_verified()));
+}
+
+#[test]
+fn test_validator_config_builder() {
+    use wal_validator::btree::KeyComparison;
+
+    let config = ValidatorConfig::builder()
+        .skip_indexes(true)
+        .check_ordering(true)
+        .key_comparison(KeyComparison::Collated)
+        .since_commit(2)
+        .only(["duplicate-rowid", "key-order"])
+        .skip(vec!["freelist".to_string()])
+        .build();
+    assert!(config.skip_indexes && config.check_ordering);
+    assert!(!config.check_new_pages);
+    assert!(matches!(config.key_comparison, KeyComparison::Collated));
+    assert_eq!(config.since_commit, Some(2));
+    assert_eq!(config.only, ["duplicate-rowid", "key-order"]);
+    assert_eq!(config.skip, ["freelist"]);
+
+    // Refining a preset keeps its settings
+    let config = ValidatorConfig::thorough().into_builder().stats(true).build();
+    assert!(!config.skip_indexes && config.check_new_pages && config.stats);
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_test_db_with_wal(&dir);
+    let config = ValidatorConfig::builder().build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(summary.issues.is_empty());
+}
+
+/// Names of the validators a configuration runs, sorted
+fn enabled_validator_names(config: &ValidatorConfig) -> Vec<&'static str> {
+    let mut names: Vec<_> = wal_validator::validators::enabled_validators(config)
+        .iter()
+        .map(|v| v.name())
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+#[test]
+fn test_fast_preset_validators() {
+    assert_eq!(enabled_validator_names(&ValidatorConfig::fast()), ["duplicate-rowid"]);
+}
+
+#[test]
+fn test_corruption_only_preset_validators() {
+    assert_eq!(
+        enabled_validator_names(&ValidatorConfig::corruption_only()),
+        [
+            "commit-sequence",
+            "duplicate-frame",
+            "duplicate-index-key",
+            "duplicate-rowid",
+            "frame-page-range",
+            "freelist",
+            "index-column-count",
+            "new-pages",
+            "orphan-page",
+            "page-layout",
+            "page-link",
+            "page-reference",
+            "page-size",
+            "ptrmap",
+            "repeated-page",
+            "root-page",
+        ]
+    );
+}
+
+#[test]
+fn test_thorough_preset_validators() {
+    // Every validator, including the reports (commit descriptions, fill factors)
+    let mut all = wal_validator::validators::validator_names();
+    all.sort();
+    assert_eq!(enabled_validator_names(&ValidatorConfig::thorough()), all);
+}
+
+#[test]
+fn test_listed_values_truncated_at_limit() {
+    let dir = TempDir::new().unwrap();
+    // Point index i at the empty index of u, so it misses all 12 rows of t
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER);
+        CREATE INDEX i ON t (v);
+        CREATE TABLE u (id INTEGER PRIMARY KEY, v INTEGER);
+        CREATE INDEX j ON u (v);
+        WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 12)
+        INSERT INTO t SELECT x, x FROM n;
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET rootpage = (SELECT rootpage FROM sqlite_master WHERE name = 'j')
+            WHERE name = 'i';
+    ",
+    );
+
+    let missing_message = |max_entries: Option<usize>| {
+        let mut config = ValidatorConfig::builder().only(["index-integrity"]).build();
+        config.max_entries_per_issue = max_entries;
+        let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+        let issue = summary.issues.iter().find(|i| i.message.contains("is missing")).unwrap();
+        issue.message.clone()
+    };
+
+    // Ten values are listed by default, followed by the count of the rest
+    assert!(missing_message(None).ends_with(": 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, ... and 2 more"));
+    assert!(missing_message(Some(3)).ends_with(": 1, 2, 3, ... and 9 more"));
+    // Exactly at the limit, nothing is left out
+    assert!(missing_message(Some(12)).ends_with(": 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12"));
+
+    let config = ValidatorConfig::builder()
+        .max_entries_per_issue(2)
+        .max_locations_per_entry(3)
+        .build();
+    assert_eq!(config.truncate_entries(&[1, 2]), (&[1, 2][..], 0));
+    assert_eq!(config.truncate_entries(&[1, 2, 3]), (&[1, 2][..], 1));
+    assert_eq!(config.truncate_locations(&[1, 2, 3]), (&[1, 2, 3][..], 0));
+    assert_eq!(config.truncate_locations(&[1, 2, 3, 4, 5]), (&[1, 2, 3][..], 2));
+
+    // Without limits, every entry and location is shown
+    let config = ValidatorConfig::default();
+    assert_eq!(config.truncate_entries(&[1; 50]).1, 0);
+    assert_eq!(config.truncate_locations(&[1; 50]).1, 0);
+}
+
+#[test]
+fn test_null_in_not_null_column_detected() {
+    let dir = TempDir::new().unwrap();
+    // Declare NOT NULL after the fact, so that row 2 holds a NULL SQLite
+    // would have refused. Row 1 predates ADD COLUMN and has no 'age' value.
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT);
+        INSERT INTO t VALUES (1, 'a');
+        ALTER TABLE t ADD COLUMN age INTEGER NOT NULL DEFAULT 0;
+        INSERT INTO t VALUES (2, NULL, 3);
+        INSERT INTO t VALUES (3, 'c', 4);
+        PRAGMA writable_schema=ON;
+        UPDATE sqlite_master SET sql = 'CREATE TABLE t (id INTEGER PRIMARY KEY NOT NULL, '
+            || 'name TEXT NOT NULL, age INTEGER NOT NULL)' WHERE name = 't';
+    ",
+    );
+
+    let columns = wal_validator::sql::parse_table_columns(
+        "CREATE TABLE u (a TEXT NOT NULL, b INT CHECK (b IS NOT NULL), c, PRIMARY KEY (c, a))",
+    )
+    .unwrap();
+    let flags: Vec<_> = columns.iter().map(|c| (c.not_null, c.primary_key)).collect();
+    assert_eq!(flags, [(true, true), (false, false), (false, true)]);
+
+    let config = ValidatorConfig::default();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(!summary.issues.iter().any(|i| i.validator == "not-null"));
+
+    let config = ValidatorConfig::builder().check_constraints(true).build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let messages: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "not-null")
+        .map(|i| i.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        ["Row 2 of table 't' (cell 1) holds NULL in NOT NULL column 'name'"]
+    );
+
+    // Naming the validator is enough to turn it on
+    let config = ValidatorConfig::builder().only(["not-null"]).build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let named: Vec<_> = summary.issues.iter().map(|i| i.message.as_str()).collect();
+    assert_eq!(named, messages);
+}
+
+#[test]
+fn test_dangling_foreign_keys_detected() {
+    let dir = TempDir::new().unwrap();
+    // Foreign keys aren't enforced unless PRAGMA foreign_keys is on
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE);
+        CREATE TABLE orders (
+            id INTEGER PRIMARY KEY,
+            user_id INTEGER REFERENCES users,
+            email TEXT,
+            CONSTRAINT fk_email FOREIGN KEY (email) REFERENCES users (email)
+        );
+        CREATE TABLE tags (name TEXT);
+        CREATE TABLE labels (tag TEXT REFERENCES tags);
+        INSERT INTO users VALUES (1, 'a@example.com');
+        INSERT INTO tags VALUES ('x');
+        INSERT INTO labels VALUES ('x');
+        INSERT INTO orders VALUES (1, 1, 'a@example.com');
+        INSERT INTO orders VALUES (2, 99, 'a@example.com');
+        INSERT INTO orders VALUES (3, NULL, 'b@example.com');
+    ",
+    );
+
+    let foreign_keys = wal_validator::sql::parse_foreign_keys(
+        "CREATE TABLE c (a REFERENCES p, b, CONSTRAINT f FOREIGN KEY (a, b) REFERENCES q (x, y))",
+    );
+    assert_eq!(foreign_keys.len(), 2);
+    assert_eq!(foreign_keys[0].columns, ["a"]);
+    assert!(foreign_keys[0].parent_columns.is_empty());
+    assert_eq!(foreign_keys[1].columns, ["a", "b"]);
+    assert_eq!(foreign_keys[1].parent_table, "q");
+    assert_eq!(foreign_keys[1].parent_columns, ["x", "y"]);
+
+    let config = ValidatorConfig::builder().check_foreign_keys(true).build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let messages: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "foreign-key")
+        .map(|i| i.message.as_str())
+        .collect();
+    assert_eq!(
+        messages,
+        [
+            "Row 2 of table 'orders' has user_id = 99, but table 'users' has no row with id = 99",
+            "Row 3 of table 'orders' has email = 'b@example.com', but table 'users' has no row \
+             with email = 'b@example.com'",
+            // The parent key falls back to the PRIMARY KEY, never to the rowid
+            "Foreign key on tag of table 'labels' references table 'tags', which has no \
+             PRIMARY KEY to match",
+        ]
+    );
+}
+
+#[test]
+fn test_resurrected_rowids_reported() {
+    use wal_validator::validators::IssueLocation;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_wal_db(
+        &dir,
+        "CREATE TABLE t (id INTEGER PRIMARY KEY, v TEXT);
+         INSERT INTO t VALUES (1, 'a'), (2, 'b'), (3, 'c'), (4, 'd');
+         DELETE FROM t WHERE id IN (2, 3);
+         INSERT INTO t VALUES (5, 'e');
+         INSERT INTO t VALUES (3, 'ghost');",
+    );
+
+    // Off by default, since an application may reuse a rowid on purpose
+    let summary =
+        wal_validator::validate(&db_path, &wal_path, &ValidatorConfig::default()).unwrap();
+    assert!(summary.issues.iter().all(|i| i.validator != "rowid-resurrection"));
+
+    let config = ValidatorConfig::builder().check_resurrected_rows(true).build();
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    let issues: Vec<_> = summary
+        .issues
+        .iter()
+        .filter(|i| i.validator == "rowid-resurrection")
+        .collect();
+    assert_eq!(issues.len(), 1, "issues: {:?}", summary.issues);
+    assert_eq!(issues[0].commit_index, Some(4));
+    assert_eq!(
+        issues[0].message,
+        "1 rowid(s) deleted by an earlier commit reappeared: 3 (deleted in commit 2)"
+    );
+    assert!(matches!(
+        &issues[0].location,
+        IssueLocation::Table { name: Some(name), .. } if name == "t"
+    ));
+}
+
+#[test]
+fn test_schema_from_page_cache() {
+    use wal_validator::db::DbHeader;
+    use wal_validator::schema::Schema;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, _) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT NOT NULL UNIQUE);
+        CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users (id));
+        CREATE INDEX users_by_email ON users (email COLLATE NOCASE);
+        CREATE INDEX orders_by_user ON orders (user_id);
+    ",
+    );
+
+    let header = DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let schema = Schema::from_page_cache(&mut page_cache).unwrap();
+
+    assert_eq!(schema.btrees().count(), 5);
+    let tables: Vec<_> = schema.tables().filter_map(|t| t.name()).collect();
+    assert_eq!(tables, ["users", "orders"]);
+
+    // The UNIQUE constraint's automatic index has no statement to parse
+    let indexes: Vec<_> = schema
+        .indexes_on("USERS")
+        .map(|i| (i.name().unwrap(), i.index_columns.is_some()))
+        .collect();
+    assert_eq!(indexes, [("sqlite_autoindex_users_1", false), ("users_by_email", true)]);
+    let by_email = schema.indexes().find(|i| i.is_named("users_by_email")).unwrap();
+    let columns = by_email.index_columns.as_ref().unwrap();
+    assert_eq!(columns[0].collation.as_deref(), Some("NOCASE"));
+
+    let users = schema.table("users").unwrap();
+    let columns = users.table_columns.as_ref().unwrap();
+    assert!(columns[0].is_rowid_alias && columns[1].not_null);
+    assert!(!users.without_rowid);
+
+    let orders = schema.table("orders").unwrap();
+    assert_eq!(orders.foreign_keys.len(), 1);
+    assert_eq!(orders.foreign_keys[0].parent_table, "users");
+    assert!(schema.table("users_by_email").is_none());
+}
+
+#[test]
+fn test_overflowing_schema_statement_discovered() {
+    use wal_validator::db::DbHeader;
+    use wal_validator::schema::Schema;
+    use wal_validator::validator::PageCache;
+
+    // 60 columns make a CREATE TABLE far longer than a 512-byte page
+    let columns: Vec<String> = (0..60).map(|i| format!("column_{i} TEXT NOT NULL")).collect();
+    let create = format!("CREATE TABLE wide (id INTEGER PRIMARY KEY, {})", columns.join(", "));
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        &format!(
+            "
+        PRAGMA page_size = 512;
+        {create};
+        CREATE INDEX wide_by_last ON wide (column_59);
+    "
+        ),
+    );
+
+    let header = DbHeader::from_file(&db_path).unwrap();
+    assert_eq!(header.page_size, 512);
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let schema = Schema::from_page_cache(&mut page_cache).unwrap();
+
+    let wide = schema.table("wide").expect("overflowing table should be discovered");
+    assert_eq!(wide.btree.sql.as_deref(), Some(create.as_str()));
+    assert_eq!(wide.table_columns.as_ref().unwrap().len(), 61);
+    assert_eq!(schema.indexes_on("wide").count(), 1);
+
+    let config = ValidatorConfig {
+        check_constraints: true,
+        ..Default::default()
+    };
+    let result = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(result.issues.is_empty(), "unexpected issues: {:?}", result.issues);
+}
+
+#[test]
+fn test_unique_index_detected_by_statement_prefix() {
+    use wal_validator::btree::BTreeScanner;
+    use wal_validator::db::DbHeader;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, _) = create_checkpointed_db(
+        &dir,
+        "
+        CREATE TABLE t (id INTEGER PRIMARY KEY, unique_code TEXT, v TEXT UNIQUE);
+        CREATE INDEX \"unique_stuff\" ON t (unique_code);
+        CREATE INDEX by_v ON t (v) WHERE v <> 'UNIQUE';
+        CREATE UNIQUE INDEX by_code ON t (unique_code, v);
+    ",
+    );
+
+    let header = DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let btrees = BTreeScanner::new(&mut page_cache).discover_btrees().unwrap();
+    let unique: Vec<_> = btrees
+        .iter()
+        .filter(|b| !b.is_table)
+        .map(|b| (b.name.as_deref().unwrap(), b.is_unique))
+        .collect();
+    assert_eq!(
+        unique,
+        [
+            ("sqlite_autoindex_t_1", true),
+            ("unique_stuff", false),
+            ("by_v", false),
+            ("by_code", true),
+        ]
+    );
+}
+
+#[test]
+fn test_utf16_database_text_decoded() {
+    use wal_validator::btree::{BTreeScanner, TextEncoding, Value};
+    use wal_validator::db::DbHeader;
+    use wal_validator::validator::PageCache;
+
+    let dir = TempDir::new().unwrap();
+    let (db_path, wal_path) = create_checkpointed_db(
+        &dir,
+        "
+        PRAGMA encoding = 'UTF-16le';
+        CREATE TABLE \"café\" (id INTEGER PRIMARY KEY, name TEXT NOT NULL);
+        CREATE INDEX café_by_name ON \"café\" (name);
+        INSERT INTO \"café\" VALUES (1, 'crème brûlée');
+        INSERT INTO \"café\" VALUES (2, '日本');
+    ",
+    );
+
+    let header = DbHeader::from_file(&db_path).unwrap();
+    assert_eq!(header.encoding(), TextEncoding::Utf16le);
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut scanner = BTreeScanner::new(&mut page_cache);
+    let btrees = scanner.discover_btrees().unwrap();
+    let names: Vec<_> = btrees.iter().filter_map(|b| b.name.as_deref()).collect();
+    assert_eq!(names, ["café", "café_by_name"]);
+    assert!(btrees[0].sql.as_deref().unwrap().starts_with("CREATE TABLE \"café\""));
+
+    let names: Vec<_> = scanner
+        .collect_table_records(btrees[0].root_page)
+        .unwrap()
+        .into_iter()
+        .map(|(_, record, _)| record[1].clone())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            Value::Text("crème brûlée".to_string()),
+            Value::Text("日本".to_string()),
+        ]
+    );
+
+    let config = ValidatorConfig {
+        check_constraints: true,
+        ..Default::default()
+    };
+    let summary = wal_validator::validate(&db_path, &wal_path, &config).unwrap();
+    assert!(summary.issues.is_empty(), "unexpected issues: {:?}", summary.issues);
+}
+
+#[test]
+fn test_schema_cache_reused_until_schema_changes() {
+    use wal_validator::db::DbHeader;
+    use wal_validator::schema::SchemaCache;
+    use wal_validator::validator::PageCache;
+    use wal_validator::wal::CommitIterator;
+
+    let dir = TempDir::new().unwrap();
+    let db_path = dir.path().join("test.db");
+    let wal_path = dir.path().join("test.db-wal");
+    let conn = Connection::open(&db_path).unwrap();
+    conn.execute_batch("CREATE TABLE t (id INTEGER PRIMARY KEY, v BLOB);").unwrap();
+    conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA wal_autocheckpoint=0;").unwrap();
+    for sql in [
+        "INSERT INTO t VALUES (1, x'00')",
+        // Grows the database, rewriting the page count in page 1's header
+        "INSERT INTO t VALUES (2, zeroblob(20000))",
+        "CREATE INDEX i ON t (v)",
+        "INSERT INTO t VALUES (3, x'01')",
+        "PRAGMA writable_schema=ON; \
+         UPDATE sqlite_master SET sql = 'CREATE INDEX i ON t (v DESC)' WHERE name = 'i'; \
+         PRAGMA writable_schema=OFF;",
+    ] {
+        conn.execute_batch(sql).unwrap();
+    }
+    std::mem::forget(conn);
+
+    let header = DbHeader::from_file(&db_path).unwrap();
+    let mut page_cache = PageCache::new(&db_path, header.page_size, header.page_count);
+    let mut cache = SchemaCache::new();
+    cache.schema(&mut page_cache).unwrap();
+
+    let mut discoveries = Vec::new();
+    let mut index_sql = Vec::new();
+    let mut writes_page_one = Vec::new();
+    for commit in CommitIterator::new(&wal_path).unwrap().unwrap() {
+        let commit = commit.unwrap();
+        writes_page_one.push(commit.frames.iter().any(|f| f.header.page_number == 1));
+        page_cache.apply_commit(&commit);
+        let schema = cache.schema(&mut page_cache).unwrap();
+        discoveries.push(cache.discoveries());
+        index_sql.push(schema.indexes().find_map(|i| i.btree.sql.clone()));
+    }
+
+    // Only the CREATE INDEX and the sqlite_master edit, which leaves the
+    // schema cookie alone, read the schema again
+    assert!(writes_page_one[1]);
+    assert_eq!(discoveries, [1, 1, 2, 2, 3]);
+    assert_eq!(index_sql[2].as_deref(), Some("CREATE INDEX i ON t (v)"));
+    assert_eq!(index_sql[4].as_deref(), Some("CREATE INDEX i ON t (v DESC)"));
+}